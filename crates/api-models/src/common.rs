@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use utoipa::ToSchema;
+#[cfg(feature = "server")]
+use validator::Validate;
+
+/// 分页查询信息
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "server", derive(ToSchema, Validate))]
+pub struct PageQuery {
+    #[cfg_attr(feature = "server", schema(example = 1))]
+    #[cfg_attr(feature = "server", validate(range(min = 1)))]
+    /// 分页查询的开始页数
+    pub page_index: u32,
+
+    #[cfg_attr(feature = "server", schema(example = 20))]
+    #[cfg_attr(feature = "server", validate(range(min = 1, max = 100)))]
+    /// 分页查询的每页大小
+    pub page_size: u32,
+}
+
+/// 封装符合json-api的单个返回对象
+///
+/// 具体参考：<https://jsonapi.org>
+#[derive(Deserialize, Debug, Serialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct Reply<T> {
+    pub data: T,
+}
+
+/// 封装符合json-api的列表对象
+#[derive(Deserialize, Debug, Serialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ReplyList<T> {
+    pub data: Vec<T>,
+    #[cfg_attr(feature = "server", schema(example = 146))]
+    /// 分页查询总数
+    pub total: u32,
+
+    #[cfg_attr(feature = "server", schema(example = 1))]
+    /// 分页查询的开始页数
+    pub page_size: u32,
+
+    #[cfg_attr(feature = "server", schema(example = 20))]
+    /// 分页查询的每页大小
+    pub page_index: u32,
+}
@@ -0,0 +1,9 @@
+//! REST接口的请求和返回值类型
+//!
+//! 服务端（`web-service`）和Rust客户端（`rust-backend-client`）共用这里的定义，服务端修改后客户端编译期即可发现不兼容。
+//!
+//! 默认只依赖 `serde`，客户端不需要编译axum、sqlx等服务端依赖。
+//! 服务端启用 `server` 特性后额外生成OpenAPI文档、参数校验、GraphQL输入类型，并提供与数据库模型之间的转换。
+
+pub mod common;
+pub mod projects;
@@ -0,0 +1,198 @@
+use crate::common::PageQuery;
+#[cfg(feature = "server")]
+use async_graphql::InputObject;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use utoipa::{IntoParams, ToSchema};
+#[cfg(feature = "server")]
+use validator::Validate;
+
+/// 搜索项目列表信息
+///
+/// - `project_name`为可选参数
+/// - `any_tags`、`all_tags`为可选参数，不填时不按标签过滤
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "server", derive(ToSchema, Validate))]
+pub struct ProjectSearch {
+    #[cfg_attr(feature = "server", schema(example = "foo"))]
+    #[cfg_attr(feature = "server", validate(length(min = 1, max = 100)))]
+    /// 查询的项目名称（模糊搜索）
+    pub project_name: Option<String>,
+
+    #[cfg_attr(feature = "server", schema(example = json!(["team:payments", "team:search"])))]
+    #[serde(default)]
+    #[cfg_attr(feature = "server", validate(length(max = 20)))]
+    /// 至少包含其中一个标签
+    pub any_tags: Vec<String>,
+
+    #[cfg_attr(feature = "server", schema(example = json!(["env:prod"])))]
+    #[serde(default)]
+    #[cfg_attr(feature = "server", validate(length(max = 20)))]
+    /// 包含所有标签
+    pub all_tags: Vec<String>,
+
+    /// 查询分页信息
+    #[cfg_attr(feature = "server", validate(nested))]
+    pub page_query: PageQuery,
+}
+
+#[cfg(feature = "server")]
+impl ProjectSearch {
+    /// 转换为数据库层的搜索条件
+    pub fn filter(&self) -> database::models::ProjectFilter {
+        database::models::ProjectFilter {
+            project_name: self.project_name.clone(),
+            any_tags: self.any_tags.clone(),
+            all_tags: self.all_tags.clone(),
+        }
+    }
+}
+
+/// 按相关度搜索项目
+///
+/// - `keyword`同时匹配项目名称和项目说明
+/// - `threshold`为可选参数，不填时使用服务端配置的默认值（配置项 `search.similarity_threshold`，默认0.3）
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "server", derive(ToSchema, Validate))]
+pub struct ProjectRankedSearch {
+    #[cfg_attr(feature = "server", schema(example = "paymnt"))]
+    #[cfg_attr(feature = "server", validate(length(min = 1, max = 100)))]
+    /// 搜索关键字（支持拼写错误）
+    pub keyword: String,
+
+    #[cfg_attr(feature = "server", schema(example = 0.3))]
+    #[cfg_attr(feature = "server", validate(range(min = 0.0, max = 1.0)))]
+    /// 相似度阈值（0~1），值越大结果越精确
+    pub threshold: Option<f32>,
+
+    /// 查询分页信息
+    #[cfg_attr(feature = "server", validate(nested))]
+    pub page_query: PageQuery,
+}
+
+/// 命中片段高亮
+///
+/// 命中部分使用`<em></em>`包裹，其余内容已经过HTML转义
+#[derive(Deserialize, Debug, Serialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ProjectHighlight {
+    #[cfg_attr(feature = "server", schema(example = "new <em>payment</em>s gateway"))]
+    /// 项目名称高亮片段
+    pub project_name: Option<String>,
+
+    #[cfg_attr(feature = "server", schema(example = "…handles <em>payment</em> callbacks…"))]
+    /// 项目说明高亮片段
+    pub comment: Option<String>,
+}
+
+/// 按相关度搜索的单条结果
+#[derive(Deserialize, Debug, Serialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ProjectSearchHit {
+    /// 项目信息
+    pub project: ProjectInfo,
+
+    #[cfg_attr(feature = "server", schema(example = 0.57))]
+    /// 相关度得分（0~1）
+    pub score: f32,
+
+    /// 命中片段高亮
+    pub highlight: ProjectHighlight,
+}
+
+/// 从数据库层的 ProjectSearchHit 转换为接口层的 ProjectSearchHit
+#[cfg(feature = "server")]
+impl From<database::models::ProjectSearchHit> for ProjectSearchHit {
+    fn from(hit: database::models::ProjectSearchHit) -> Self {
+        Self {
+            project: hit.project.into(),
+            score: hit.score,
+            highlight: ProjectHighlight {
+                project_name: hit.name_highlight,
+                comment: hit.comment_highlight,
+            },
+        }
+    }
+}
+
+/// 项目名称输入提示参数
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "server", derive(IntoParams, Validate))]
+#[cfg_attr(feature = "server", into_params(parameter_in = Query))]
+pub struct ProjectSuggestQuery {
+    #[cfg_attr(feature = "server", param(example = "pay"))]
+    #[cfg_attr(feature = "server", validate(length(min = 1, max = 100)))]
+    /// 用户已经输入的内容
+    pub q: String,
+
+    #[cfg_attr(feature = "server", param(example = 10))]
+    #[cfg_attr(feature = "server", validate(range(min = 1, max = 50)))]
+    /// 最多返回的名称个数，默认10个
+    pub limit: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "server", derive(ToSchema, InputObject))]
+#[cfg_attr(feature = "server", graphql(name = "ProjectCreateInput"))]
+pub struct ProjectCreate {
+    /// 新建项目名称
+    #[cfg_attr(feature = "server", schema(example = "foo"))]
+    pub project_name: String,
+
+    /// 项目说明
+    #[cfg_attr(feature = "server", schema(example = "comment"))]
+    pub comment: String,
+
+    /// 父项目ID，不填时创建顶层项目
+    #[cfg_attr(feature = "server", schema(example = 3))]
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+}
+
+#[derive(Deserialize, Debug, Serialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ProjectInfo {
+    #[cfg_attr(feature = "server", schema(example = 15))]
+    /// 项目ID
+    pub id: i32,
+
+    #[cfg_attr(feature = "server", schema(example = "bar"))]
+    /// 项目名称
+    pub project_name: String,
+
+    /// 项目说明
+    #[cfg_attr(feature = "server", schema(example = "foo_bar"))]
+    pub comment: String,
+
+    #[cfg_attr(feature = "server", schema(example = json!(["env:prod", "team:payments"])))]
+    /// 项目标签，按名称排序
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// 从数据库层的 ProjectInfo 转换为接口层的 ProjectInfo
+#[cfg(feature = "server")]
+impl From<database::models::ProjectInfo> for ProjectInfo {
+    fn from(db_project: database::models::ProjectInfo) -> Self {
+        Self {
+            id: db_project.id,
+            project_name: db_project.project_name,
+            comment: db_project.comment,
+            tags: db_project.tags,
+        }
+    }
+}
+
+/// 更新项目的信息
+#[derive(Deserialize, Debug, Serialize)]
+#[cfg_attr(feature = "server", derive(ToSchema, InputObject))]
+#[cfg_attr(feature = "server", graphql(name = "ProjectUpdateInput"))]
+pub struct ProjectUpdate {
+    #[cfg_attr(feature = "server", schema(example = "bar"))]
+    /// 新的项目名称，不填时保持不变
+    pub project_name: Option<String>,
+
+    #[cfg_attr(feature = "server", schema(example = "foo"))]
+    /// 新的项目说明，不填时保持不变
+    pub comment: Option<String>,
+}
@@ -9,27 +9,30 @@ pub mod traits;
 
 use self::task_type_a::TaskTypeACreator;
 use self::task_type_b::TaskTypeBCreator;
+pub use crate::redis_interaction::in_flight_messages;
 use crate::redis_interaction::{consumer_task_worker_with_heartbeat, create_task_group};
 use crate::traits::RedisHandlerTrait;
 use color_eyre::Result;
 use color_eyre::eyre::Context;
 use futures::future::try_join_all;
-use shared_lib::models::config::AppConfig;
+use shared_lib::models::config_reload::ConfigReceiver;
+use shared_lib::startup::connect_redis;
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::watch::Receiver;
 use tokio::try_join;
-use tracing::{info, warn};
+use tracing::info;
 
 /// 启动redis消费者
 ///
 /// ## 参数说明
-/// - `app_config`: 程序配置
-/// - `shutdown_rx`: 用于接收关闭信号
+/// - `config`: 程序配置，消费者的并发数、心跳间隔等参数支持热加载，参考 [`shared_lib::models::config_reload`]
+/// - `shutdown_rx`: 用于接收关闭信号，收到信号后不再读取新消息，已经读取的消息处理完并确认后退出
+///
+/// 启动失败（例如超过 `startup.timeout_secs` 仍然无法连接Redis）时返回错误，由监督器按 `supervisor.consumer` 的重启策略处理
 ///
 /// ## 通用处理
 ///
-/// 代码中的内部函数 `guard_start_create_task_consumers` 是一个通用redis处理器，封装了相关逻辑，用户仅需要创建一个
+/// 代码中的内部函数 `start_create_task_consumers` 是一个通用redis处理器，封装了相关逻辑，用户仅需要创建一个
 /// 实现了[`RedisHandlerTrait`]特征的处理器，传递给通用处理器即可。
 ///
 /// 主要核心处理函数在handler，这是一个实现了[`crate::traits::RedisHandlerTrait`] 特征的处理器。
@@ -41,8 +44,8 @@ use tracing::{info, warn};
 /// let task2 = TaskTypeBCreator::new();
 ///
 /// try_join!(
-///     guard_start_create_task_consumers(Arc::clone(&app_config), task1, shutdown_rx.clone()),
-///     guard_start_create_task_consumers(Arc::clone(&app_config), task2, shutdown_rx.clone()),
+///     start_create_task_consumers(config.clone(), task1, shutdown_rx.clone()),
+///     start_create_task_consumers(config.clone(), task2, shutdown_rx.clone()),
 /// )?;
 /// ```
 ///
@@ -61,15 +64,15 @@ use tracing::{info, warn};
 /// - 需要生成比较多的消费者
 /// - 需要比较多的redis链接（特别是当前每个Redis消费者需要2个链接）
 ///
-pub async fn start_job_consumers(app_config: Arc<AppConfig>, shutdown_rx: Receiver<bool>) -> Result<()> {
+pub async fn start_job_consumers(config: ConfigReceiver, shutdown_rx: Receiver<bool>) -> Result<()> {
     info!(
         "Starting redis job consumers with redis info {}...",
-        &app_config.redis.redis_conn_str
+        &config.borrow().redis.redis_conn_str
     );
 
     try_join!(
-        guard_start_create_task_consumers(Arc::clone(&app_config), TaskTypeACreator::new(), shutdown_rx.clone()),
-        guard_start_create_task_consumers(Arc::clone(&app_config), TaskTypeBCreator::new(), shutdown_rx.clone())
+        start_create_task_consumers(config.clone(), TaskTypeACreator::new(), shutdown_rx.clone()),
+        start_create_task_consumers(config.clone(), TaskTypeBCreator::new(), shutdown_rx.clone())
     )?;
 
     info!("Redis job consumers stopped");
@@ -77,43 +80,38 @@ pub async fn start_job_consumers(app_config: Arc<AppConfig>, shutdown_rx: Receiv
     Ok(())
 }
 
-async fn guard_start_create_task_consumers<T: RedisHandlerTrait>(
-    app_config: Arc<AppConfig>,
-    redis_task: Arc<T>,
-    shutdown_rx: Receiver<bool>,
-) -> Result<()> {
-    loop {
-        let re = start_create_task_consumers(Arc::clone(&app_config), Arc::clone(&redis_task), shutdown_rx.clone()).await;
-        match re {
-            Ok(_) => break,
-            Err(err) => {
-                warn!("{}", err);
-                warn!("Failed to start create task consumers, retrying...");
-                tokio::time::sleep(Duration::from_secs(5)).await;
-            }
-        }
-    }
-
-    Ok(())
+/// 所有任务处理器读取的Redis流名称
+///
+/// 命令行投递任务、查看流状态时使用，新增任务处理器时需要同步添加
+pub fn task_stream_names() -> [&'static str; 2] {
+    [TaskTypeACreator::new().stream_name(), TaskTypeBCreator::new().stream_name()]
 }
 
 async fn start_create_task_consumers<T: RedisHandlerTrait>(
-    app_config: Arc<AppConfig>,
+    config: ConfigReceiver,
     redis_task: Arc<T>,
-    shutdown_rx: Receiver<bool>,
+    mut shutdown_rx: Receiver<bool>,
 ) -> Result<()> {
-    create_task_group(app_config.redis.redis_conn_str.clone(), Arc::clone(&redis_task)).await?;
+    // 消费者个数、Redis连接和键名不支持热加载，使用启动时的配置
+    let app_config = Arc::clone(&config.borrow());
 
+    // 等待Redis可用期间收到关闭信号时直接退出
+    let conn = tokio::select! {
+        conn = connect_redis(&app_config) => conn?,
+        _ = shutdown_rx.wait_for(|shutdown| *shutdown) => return Ok(()),
+    };
+    create_task_group(conn, &app_config.consumer, Arc::clone(&redis_task)).await?;
+
+    // 每个消费者使用独立的连接，避免阻塞读取影响其他消费者
     let consumers: Vec<_> = (0..app_config.redis.max_consumer_count)
         .map(|i| {
             let consumer_name = format!("{}_{}", redis_task.consumer_name_template(), i);
+            let (app_config, config, redis_task, shutdown_rx) = (&app_config, config.clone(), Arc::clone(&redis_task), shutdown_rx.clone());
 
-            consumer_task_worker_with_heartbeat(
-                app_config.redis.redis_conn_str.clone(),
-                Arc::clone(&redis_task),
-                consumer_name,
-                shutdown_rx.clone(),
-            )
+            async move {
+                let conn = connect_redis(app_config).await?;
+                consumer_task_worker_with_heartbeat(conn, config, redis_task, consumer_name, shutdown_rx).await
+            }
         })
         .collect();
 
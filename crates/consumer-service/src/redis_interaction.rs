@@ -6,6 +6,7 @@
 //! - 💓 心跳机制
 //! - 📝 消息处理
 //! - 🛑 优雅关闭
+//! - 📤 任务投递和 🔍 运行状态查询（供命令行工具使用）
 //!
 //! 该模块的核心功能是从 Redis 流中读取消息，并通过实现了 `RedisHandlerTrait` 的处理器来处理这些消息。
 
@@ -16,34 +17,29 @@ use color_eyre::eyre::Context;
 use futures::StreamExt;
 use futures::stream::iter;
 use redis::aio::ConnectionManager;
-use redis::streams::{StreamId, StreamReadOptions, StreamReadReply};
+use redis::streams::{StreamId, StreamInfoGroup, StreamInfoGroupsReply, StreamReadOptions, StreamReadReply};
 use redis::{AsyncCommands, RedisError, RedisResult, Value};
-use shared_lib::models::redis_constants::{CONSUMER_GROUP_NAME, CONSUMER_HEARTBEAT_KEY, HEARTBEAT_INTERVAL_SECONDS};
+use shared_lib::models::config::ConsumerConfig;
+use shared_lib::models::config_reload::ConfigReceiver;
 use shared_lib::models::redis_task::RedisConsumerHeartBeat;
+use shared_lib::shutdown::wait_for_shutdown;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
+use tokio::sync::watch;
 use tokio::sync::watch::Receiver;
 use tokio::try_join;
 use tracing::{debug, error, trace, warn};
 
-/// 🔗 创建 Redis 连接管理器
-///
-/// 这个函数用于创建一个异步的 Redis 连接管理器，它能够自动管理连接池。
-/// 连接管理器会在连接断开时自动重连，确保连接的稳定性。
-///
-/// # 参数
-///
-/// * `conn_str` - Redis 连接字符串，格式通常为 `redis://host:port`
-///
-/// # 返回值
-///
-/// 返回一个 `Result<ConnectionManager>`，成功时包含连接管理器实例
-///
-/// # 错误
+/// 已经读取但还没有确认的消息数
+static IN_FLIGHT_MESSAGES: AtomicUsize = AtomicUsize::new(0);
+
+/// 📊 已经读取但还没有处理完并确认的消息数
 ///
-/// 如果连接字符串无效或连接失败，将返回相应的错误
-pub async fn new_redis_connection_manager(conn_str: &str) -> Result<ConnectionManager> {
-    Ok(ConnectionManager::new(redis::Client::open(conn_str)?).await?)
+/// 优雅退出超时时用于报告放弃了多少条消息，这些消息留在消费者组的待处理列表中，
+/// 重启后由同名消费者重新读取，或者由重平衡任务转移给其他消费者
+pub fn in_flight_messages() -> usize {
+    IN_FLIGHT_MESSAGES.load(Ordering::Relaxed)
 }
 
 /// 📊 创建 Redis 消费者组
@@ -53,7 +49,8 @@ pub async fn new_redis_connection_manager(conn_str: &str) -> Result<ConnectionMa
 ///
 /// # 参数
 ///
-/// * `conn_str` - Redis 连接字符串
+/// * `conn` - Redis 连接管理器
+/// * `config` - 消费者配置，用于获取消费者组名称
 /// * `redis_task` - 实现了 `RedisHandlerTrait` 的任务处理器，用于获取流名称
 ///
 /// # 行为
@@ -66,15 +63,14 @@ pub async fn new_redis_connection_manager(conn_str: &str) -> Result<ConnectionMa
 /// # 返回值
 ///
 /// 返回 `Result<()>`，即使创建失败也会返回 `Ok(())`
-pub async fn create_task_group<T: RedisHandlerTrait>(conn_str: String, redis_task: Arc<T>) -> Result<()> {
-    let conn = new_redis_connection_manager(&conn_str).await?;
-
-    let re: RedisResult<()> = conn
-        .clone()
-        .xgroup_create_mkstream(redis_task.stream_name(), CONSUMER_GROUP_NAME, "$")
-        .await;
+pub async fn create_task_group<T: RedisHandlerTrait>(
+    mut conn: ConnectionManager,
+    config: &ConsumerConfig,
+    redis_task: Arc<T>,
+) -> Result<()> {
+    let re: RedisResult<()> = conn.xgroup_create_mkstream(redis_task.stream_name(), &config.group_name, "$").await;
     if let Err(err) = re {
-        warn!("Failed to create redis task group {}: {}", CONSUMER_GROUP_NAME, err);
+        warn!("Failed to create redis task group {}: {}", config.group_name, err);
     }
 
     Ok(())
@@ -87,7 +83,8 @@ pub async fn create_task_group<T: RedisHandlerTrait>(conn_str: String, redis_tas
 ///
 /// # 参数
 ///
-/// * `conn_str` - Redis 连接字符串
+/// * `conn` - Redis 连接管理器，消息消费者和心跳发送器共用
+/// * `config` - 程序配置的接收端，消费者配置（消费者组、心跳间隔、并发数等）修改后自动生效
 /// * `redis_task` - 实现了 `RedisHandlerTrait` 的任务处理器
 /// * `consumer_name` - 消费者的唯一名称，用于标识和心跳
 /// * `shutdown_rx` - 用于接收关闭信号的接收器
@@ -99,21 +96,40 @@ pub async fn create_task_group<T: RedisHandlerTrait>(conn_str: String, redis_tas
 ///
 /// # 优雅关闭
 ///
-/// 当 `shutdown_rx` 接收到关闭信号时，两个任务都会优雅地停止
+/// 当 `shutdown_rx` 接收到关闭信号时，消息消费者不再读取新消息，处理完已经读取的消息后停止，
+/// 心跳发送器在消息消费者停止后才停止，避免处理时间较长的消息被重平衡任务转移给其他消费者
 ///
 /// # 返回值
 ///
 /// 返回 `Result<()>`，如果任一任务失败，整个函数都会失败
 pub async fn consumer_task_worker_with_heartbeat<T: RedisHandlerTrait>(
-    conn_str: String,
+    conn: ConnectionManager,
+    config: ConfigReceiver,
     redis_task: Arc<T>,
     consumer_name: String,
     shutdown_rx: Receiver<bool>,
 ) -> Result<()> {
-    let conn = new_redis_connection_manager(&conn_str).await?;
+    let (stopped_tx, stopped_rx) = watch::channel(false);
     _ = try_join!(
-        consumer_task_send_heartbeat(conn.clone(), Arc::clone(&redis_task), consumer_name.clone(), shutdown_rx.clone()),
-        consumer_task_worker(conn.clone(), Arc::clone(&redis_task), consumer_name.clone(), shutdown_rx.clone()),
+        consumer_task_send_heartbeat(
+            conn.clone(),
+            config.clone(),
+            Arc::clone(&redis_task),
+            consumer_name.clone(),
+            stopped_rx
+        ),
+        async {
+            let result = consumer_task_worker(
+                conn.clone(),
+                config,
+                Arc::clone(&redis_task),
+                consumer_name.clone(),
+                shutdown_rx.clone(),
+            )
+            .await;
+            _ = stopped_tx.send(true);
+            result
+        },
     )
     .context(format!("Creating consumer {consumer_name} with auto heartbeat"))?;
 
@@ -131,11 +147,13 @@ pub async fn consumer_task_worker_with_heartbeat<T: RedisHandlerTrait>(
 /// * `streams` - 要读取消息的 Redis 流数组
 /// * `opts` - `StreamReadOptions`，用于定制读取行为
 /// * `redis_task` - 消息处理器，用于处理读取的消息
+/// * `config` - 消费者配置，用于确认消息和控制并发数
+/// * `shutdown_rx` - 关闭信号的接收器，只会打断阻塞读取新消息，已经读取的消息会处理完并确认
 ///
 /// # 流程
 ///
 /// 1. 🔄 从指定的流中读取待处理消息（偏移量为 "0"），然后调用 `consume_redis_message` 处理
-/// 2. 🆕 从流中读取未发送的消息（偏移量为 ">"），然后调用 `consume_redis_message` 处理
+/// 2. 🆕 从流中读取未发送的消息（偏移量为 ">"），然后调用 `consume_redis_message` 处理，收到关闭信号时不再读取
 ///
 /// # 返回值
 ///
@@ -145,12 +163,18 @@ pub async fn xread_group<T: RedisHandlerTrait>(
     streams: &[String],
     opts: &StreamReadOptions,
     redis_task: &Arc<T>,
+    config: &ConsumerConfig,
+    shutdown_rx: &mut Receiver<bool>,
 ) -> Result<()> {
     let pending_msg = conn.xread_options::<String, &str, StreamReadReply>(streams, &["0"], opts).await?;
-    consume_redis_message(conn, pending_msg, redis_task).await?;
+    consume_redis_message(conn, pending_msg, redis_task, config).await?;
 
-    let undelivered_msg = conn.xread_options::<String, &str, StreamReadReply>(streams, &[">"], opts).await?;
-    consume_redis_message(conn, undelivered_msg, redis_task).await?;
+    // 读取请求已经发出但还没有收到回复时被打断的消息会留在待处理列表中，下次读取待处理消息时处理
+    let undelivered_msg = tokio::select! {
+        _ = wait_for_shutdown(shutdown_rx) => return Ok(()),
+        reply = conn.xread_options::<String, &str, StreamReadReply>(streams, &[">"], opts) => reply?,
+    };
+    consume_redis_message(conn, undelivered_msg, redis_task, config).await?;
 
     Ok(())
 }
@@ -164,18 +188,19 @@ pub async fn xread_group<T: RedisHandlerTrait>(
 /// * `conn` - Redis 连接管理器的可变引用，用于确认（acknowledge）消息
 /// * `reply` - 包含了读取消息的流应答
 /// * `redis_task` - 消息处理器，用于实际处理每一条消息
+/// * `config` - 消费者配置，`handler_concurrency` 控制同时处理的消息数
 ///
 /// # 流程
 ///
 /// 1. 🔄 对每个流的键遍历消息 ID
 /// 2. 🚀 对每个消息 ID，在并发中调用 `consume_single_redis_message` 处理
-/// 3. 📊 使用 `buffer_unordered(5)` 并发处理最多 5 条消息
+/// 3. 📊 使用 `buffer_unordered` 并发处理最多 `handler_concurrency`（默认5）条消息
 /// 4. ✅ 在所有消息处理完成后，调用 `xack` 批量确认消息
 /// 5. ⚠️ 如果确认失败，记录错误但不中断流程
 ///
 /// # 性能特性
 ///
-/// - 并发处理最多 5 条消息提高处理效率
+/// - 并发处理多条消息提高处理效率
 /// - 使用批量确认减少 Redis 网络开销
 ///
 /// # 返回值
@@ -185,27 +210,30 @@ pub async fn consume_redis_message<T: RedisHandlerTrait>(
     conn: &mut ConnectionManager,
     reply: StreamReadReply,
     redis_task: &Arc<T>,
+    config: &ConsumerConfig,
 ) -> Result<()> {
     for key in reply.keys {
         if key.ids.is_empty() {
             continue;
         }
 
+        IN_FLIGHT_MESSAGES.fetch_add(key.ids.len(), Ordering::Relaxed);
         let tasks = key
             .ids
             .iter()
             .map(|id| consume_single_redis_message(Arc::clone(redis_task), id))
             .collect::<Vec<_>>();
 
-        iter(tasks).buffer_unordered(5).collect::<Vec<_>>().await;
+        iter(tasks).buffer_unordered(config.handler_concurrency).collect::<Vec<_>>().await;
 
         let xack_ret: Result<(), RedisError> = conn
             .xack(
                 redis_task.stream_name(),
-                CONSUMER_GROUP_NAME,
+                &config.group_name,
                 &key.ids.iter().map(|it| &it.id).collect::<Vec<_>>(),
             )
             .await;
+        IN_FLIGHT_MESSAGES.fetch_sub(key.ids.len(), Ordering::Relaxed);
 
         if let Err(err) = xack_ret {
             error!(
@@ -257,49 +285,47 @@ async fn consume_single_redis_message<T: RedisHandlerTrait>(redis_task: Arc<T>,
 
 /// 🏃‍♂️ Redis 消息消费者工作器
 ///
-/// 这个异步函数持续地从 Redis 流中读取消息并调用处理器处理，每秒阻塞读取，轮询新消息。
+/// 这个异步函数持续地从 Redis 流中读取消息并调用处理器处理，阻塞读取，轮询新消息。
 /// 它实现了一个带有优雅关闭机制的消费者工作循环。
 ///
 /// # 参数
 ///
 /// * `conn` - Redis 连接管理器，用于与 Redis 服务器通信
+/// * `config` - 程序配置的接收端，包括消费者组、阻塞时间和批量大小
 /// * `redis_task` - 消息处理器，用于处理读取的消息
 /// * `consumer_name` - 用于标识消费者的唯一名称
 /// * `shutdown_rx` - 可以发出关闭信号的接收器
 ///
 /// # 流程
 ///
-/// 1. 🔧 初始化读取选项，设置消费者组、阻塞时间(1秒)和最大读取计数(10)
-/// 2. 🔄 在主循环中，等待 `shutdown_rx` 来决定是否关闭
+/// 1. 🔧 每次读取前使用最新的消费者配置设置读取选项（消费者组、阻塞时间和最大读取计数），配置热加载后下一次读取即生效
+/// 2. 🔄 在主循环中，检查 `shutdown_rx` 来决定是否关闭
 /// 3. 📡 如果没有关闭信号，通过 `xread_group` 从流中消费消息
-/// 4. 🔄 如果读取失败，休眠 5 秒并重试
+/// 4. 🔄 如果读取失败，休眠 5 秒并重试（收到关闭信号时直接退出）
 /// 5. 📝 记录开始与结束日志
 ///
 /// # 配置参数
 ///
-/// - **阻塞时间**: 1000ms (1秒) - 如果没有消息可读，最多等待 1 秒
-/// - **批量大小**: 10 - 每次最多读取 10 条消息
+/// - **阻塞时间**: `read_block`，默认 1 秒 - 如果没有消息可读，最多等待的时间
+/// - **批量大小**: `read_count`，默认 10 - 每次最多读取的消息数
 /// - **重试间隔**: 5 秒 - 连接失败后等待 5 秒再重试
 ///
 /// # 优雅关闭
 ///
-/// 使用 `tokio::select!` 监听关闭信号，确保能够及时响应停止请求
+/// 关闭信号只会打断阻塞读取和重试前的休眠，已经读取的消息处理完并确认后才会退出
 ///
 /// # 返回值
 ///
 /// 返回 `Result<()>`，表示操作状态
 async fn consumer_task_worker<T: RedisHandlerTrait>(
     mut conn: ConnectionManager,
+    config: ConfigReceiver,
     redis_task: Arc<T>,
     consumer_name: String,
     shutdown_rx: Receiver<bool>,
 ) -> Result<()> {
     debug!("Redis job consumer {} started", consumer_name);
 
-    let opts = StreamReadOptions::default()
-        .group(CONSUMER_GROUP_NAME, &consumer_name)
-        .block(1000)
-        .count(10);
     let streams = vec![redis_task.stream_name().to_string()];
 
     let mut shutdown_rx = shutdown_rx.clone();
@@ -309,21 +335,18 @@ async fn consumer_task_worker<T: RedisHandlerTrait>(
             break;
         }
 
-        tokio::select! {
-          _ = shutdown_rx.changed() => {
-              if *shutdown_rx.borrow() {
-                  break;
-              }
-          }
-          result = xread_group(&mut conn,&streams,&opts,&redis_task) => {
-              match result {
-                  Ok(_) => {}
-                  Err(err) => {
-                        warn!("{} xread group failed, err: {}, reconnecting...", consumer_name, err);
-                        tokio::time::sleep(Duration::from_secs(5)).await;
-                  }
-              }
-          }
+        let consumer_config = config.borrow().consumer.clone();
+        let opts = StreamReadOptions::default()
+            .group(&consumer_config.group_name, &consumer_name)
+            .block(consumer_config.read_block.as_millis() as usize)
+            .count(consumer_config.read_count);
+
+        if let Err(err) = xread_group(&mut conn, &streams, &opts, &redis_task, &consumer_config, &mut shutdown_rx).await {
+            warn!("{} xread group failed, err: {}, reconnecting...", consumer_name, err);
+            tokio::select! {
+                _ = wait_for_shutdown(&mut shutdown_rx) => break,
+                _ = tokio::time::sleep(Duration::from_secs(5)) => {}
+            }
         }
     }
 
@@ -340,13 +363,14 @@ async fn consumer_task_worker<T: RedisHandlerTrait>(
 /// # 参数
 ///
 /// * `conn` - Redis 连接管理器，用于向 Redis 写入心跳数据
+/// * `config` - 程序配置的接收端，包括心跳键名和发送间隔
 /// * `redis_task` - 消息处理器，提供流名称上下文
 /// * `consumer_name` - 消费者的唯一名称，作为心跳数据的标识符
-/// * `shutdown_rx` - 接收关闭信号的接收器
+/// * `stopped_rx` - 消息消费者停止的信号，消费者停止后才停止发送心跳
 ///
 /// # 流程
 ///
-/// 1. ⏱️ 设置心跳发送的时间间隔，通过 `tokio::time::interval` 实现
+/// 1. ⏱️ 设置心跳发送的时间间隔，通过 `tokio::time::interval` 实现，配置热加载修改了间隔时重新设置
 /// 2. 🔄 在心跳间隔内等待关闭信号或定时器触发
 /// 3. 📊 每次心跳时构建包含流名称、消费者名称和当前时间戳的心跳数据
 /// 4. 📝 将心跳数据序列化为 JSON 字符串
@@ -362,29 +386,33 @@ async fn consumer_task_worker<T: RedisHandlerTrait>(
 ///
 /// # 优雅关闭
 ///
-/// 使用 `tokio::select!` 监听关闭信号，确保能够及时响应停止请求
+/// 消息消费者停止后（`stopped_rx` 收到信号）才停止发送心跳
 ///
 /// # 返回值
 ///
 /// 返回 `Result<()>`，表示操作状态
 async fn consumer_task_send_heartbeat<T: RedisHandlerTrait>(
     mut conn: ConnectionManager,
+    mut config: ConfigReceiver,
     redis_task: Arc<T>,
     consumer_name: String,
-    mut shutdown_rx: Receiver<bool>,
+    mut stopped_rx: Receiver<bool>,
 ) -> Result<()> {
-    let mut interval = tokio::time::interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECONDS));
+    // 心跳键名不支持热加载
+    let heartbeat_key = config.borrow().consumer.heartbeat_key.clone();
+    let mut heartbeat_interval = config.borrow_and_update().consumer.heartbeat_interval;
+    let mut interval = tokio::time::interval(heartbeat_interval);
 
     loop {
-        if *shutdown_rx.borrow() {
-            break;
-        }
-
         tokio::select! {
-            _ = shutdown_rx.changed() => {
-              if *shutdown_rx.borrow() {
-                  break;
-              }
+            _ = wait_for_shutdown(&mut stopped_rx) => break,
+            Ok(()) = config.changed() => {
+                let new_interval = config.borrow_and_update().consumer.heartbeat_interval;
+                if new_interval != heartbeat_interval {
+                    debug!("Consumer {} heartbeat interval changed to {:?}", consumer_name, new_interval);
+                    heartbeat_interval = new_interval;
+                    interval = tokio::time::interval(heartbeat_interval);
+                }
             }
           _ = interval.tick() => {
                 let redis_heartbeat = RedisConsumerHeartBeat {
@@ -395,7 +423,7 @@ async fn consumer_task_send_heartbeat<T: RedisHandlerTrait>(
 
                 if let Ok(json_data) = serde_json::to_string(&redis_heartbeat) {
                     trace!("Sending heartbeat to Redis: {}", json_data);
-                    let res :Result<(), RedisError> = conn.hset(CONSUMER_HEARTBEAT_KEY, &consumer_name, json_data).await;
+                    let res :Result<(), RedisError> = conn.hset(&heartbeat_key, &consumer_name, json_data).await;
                     if let Err(err) = res {
                         warn!("Consumer {} redis heartbeat error: {}", consumer_name, err);
                     }
@@ -406,3 +434,75 @@ async fn consumer_task_send_heartbeat<T: RedisHandlerTrait>(
 
     Ok(())
 }
+
+/// 📤 向 Redis 流中写入一条任务消息
+///
+/// 消息保存在 `message` 字段中，与消费者读取的字段保持一致（参考 `consume_single_redis_message`）。
+///
+/// # 参数
+///
+/// * `conn` - Redis 连接管理器
+/// * `stream_name` - 流名称，例如 `task_type_a`
+/// * `payload` - 消息内容，通常是任务的JSON序列化数据
+///
+/// # 返回值
+///
+/// 返回 Redis 生成的消息 ID
+pub async fn enqueue_task(conn: &mut ConnectionManager, stream_name: &str, payload: &str) -> RedisResult<String> {
+    conn.xadd(stream_name, "*", &[("message", payload)]).await
+}
+
+/// 🔍 Redis 流的运行状态
+#[derive(Debug, Clone)]
+pub struct StreamStats {
+    /// 流名称
+    pub stream_name: String,
+    /// 流中保存的消息数量
+    pub length: usize,
+    /// 消费者组信息，包括消费者数量、待确认（pending）消息数量等
+    pub groups: Vec<StreamInfoGroup>,
+}
+
+/// 🔍 查询 Redis 流的运行状态
+///
+/// 流不存在时返回长度为0、没有消费者组的状态，不会返回错误
+pub async fn inspect_stream(conn: &mut ConnectionManager, stream_name: &str) -> RedisResult<StreamStats> {
+    let exists: bool = conn.exists(stream_name).await?;
+    if !exists {
+        return Ok(StreamStats {
+            stream_name: stream_name.to_string(),
+            length: 0,
+            groups: Vec::new(),
+        });
+    }
+
+    let length: usize = conn.xlen(stream_name).await?;
+    let groups: StreamInfoGroupsReply = conn.xinfo_groups(stream_name).await?;
+
+    Ok(StreamStats {
+        stream_name: stream_name.to_string(),
+        length,
+        groups: groups.groups,
+    })
+}
+
+/// 💓 读取所有消费者的心跳信息，按消费者名称排序
+///
+/// `heartbeat_key` 为心跳的Hash键名（[`ConsumerConfig::heartbeat_key`]），无法解析的心跳数据会被忽略并记录警告
+pub async fn consumer_heartbeats(conn: &mut ConnectionManager, heartbeat_key: &str) -> RedisResult<Vec<RedisConsumerHeartBeat>> {
+    let raw: Vec<(String, String)> = conn.hgetall(heartbeat_key).await?;
+
+    let mut heartbeats: Vec<RedisConsumerHeartBeat> = raw
+        .into_iter()
+        .filter_map(|(consumer_name, data)| match serde_json::from_str(&data) {
+            Ok(heartbeat) => Some(heartbeat),
+            Err(err) => {
+                warn!("Invalid heartbeat of consumer {}: {}", consumer_name, err);
+                None
+            }
+        })
+        .collect();
+    heartbeats.sort_by(|a, b| a.consumer_name.cmp(&b.consumer_name));
+
+    Ok(heartbeats)
+}
@@ -8,9 +8,9 @@
 //!     - 消费者分组
 //!     - 读取的stream信息
 //!     - 心跳时间(unix timestamp)
-//! - 每个消费者每5秒写入一次心跳数据
-//! - 有一个cronjob定时任务，每隔10秒检查 `_consumer_status` 中的消费者心跳状态：
-//!     - 如果已经60秒没有心跳数据写入（12次失败），则认为此消费者失效，发起数据再平衡功能
+//! - 每个消费者每5秒写入一次心跳数据（`consumer.heartbeat_interval_secs`）
+//! - 有一个cronjob定时任务，每隔10秒（`cron.rebalance_schedule`）检查 `_consumer_status` 中的消费者心跳状态：
+//!     - 如果已经60秒（`consumer.heartbeat_timeout_secs`）没有心跳数据写入（12次失败），则认为此消费者失效，发起数据再平衡功能
 //!         - 将pending消息随机分发到同组的其他有效消费者
 //!         - 分发完成后，删除其心跳数据
 //!     - 如果心跳正常，则什么也不做
@@ -21,9 +21,7 @@ use color_eyre::Result;
 use redis::aio::ConnectionManager;
 use redis::{AsyncCommands, RedisResult, Value};
 use shared_lib::execute_with_lock;
-use shared_lib::models::redis_constants::{
-    BATCH_SIZE, CONSUMER_GROUP_NAME, CONSUMER_HEARTBEAT_KEY, HEARTBEAT_TIMEOUT_SECONDS, LOCK_TTL_SECONDS, REBALANCE_LOCK_KEY,
-};
+use shared_lib::models::config::ConsumerConfig;
 use shared_lib::models::redis_task::RedisConsumerHeartBeat;
 use std::collections::HashMap;
 use tracing::{debug, error, info, warn};
@@ -40,10 +38,10 @@ pub struct ConsumerStatus {
 /// 执行一次Redis消息重平衡检查
 ///
 /// 这个函数会执行一次重平衡检查，由外部 cron 调度器来调用
-pub async fn execute_rebalance_once(conn: &mut ConnectionManager) -> Result<()> {
+pub async fn execute_rebalance_once(conn: &mut ConnectionManager, config: &ConsumerConfig) -> Result<()> {
     debug!("🔄 执行重平衡检查");
 
-    match rebalance_with_retry(conn).await {
+    match rebalance_with_retry(conn, config).await {
         Ok(()) => {
             debug!("✅ 重平衡检查完成");
             Ok(())
@@ -59,17 +57,12 @@ pub async fn execute_rebalance_once(conn: &mut ConnectionManager) -> Result<()>
 // 这里不需要重复实现心跳写入功能
 
 /// 执行重平衡（带分布式锁）
-async fn rebalance_with_retry(conn: &mut ConnectionManager) -> Result<()> {
+async fn rebalance_with_retry(conn: &mut ConnectionManager, config: &ConsumerConfig) -> Result<()> {
     let conn_clone = conn.clone();
-    match execute_with_lock(
-        conn,
-        REBALANCE_LOCK_KEY,
-        std::time::Duration::from_secs(LOCK_TTL_SECONDS),
-        async move {
-            let mut conn = conn_clone;
-            rebalance(&mut conn).await
-        },
-    )
+    match execute_with_lock(conn, &config.rebalance_lock_key, config.rebalance_lock_ttl, async move {
+        let mut conn = conn_clone;
+        rebalance(&mut conn, config).await
+    })
     .await
     {
         Ok(Some(_)) => {
@@ -88,11 +81,11 @@ async fn rebalance_with_retry(conn: &mut ConnectionManager) -> Result<()> {
 }
 
 /// 重平衡逻辑
-async fn rebalance(conn: &mut ConnectionManager) -> RedisResult<()> {
+async fn rebalance(conn: &mut ConnectionManager, config: &ConsumerConfig) -> RedisResult<()> {
     debug!("🔍 开始检查消费者状态...");
 
     // 1. 获取所有消费者状态
-    let consumer_statuses = get_all_consumer_statuses(conn).await?;
+    let consumer_statuses = get_all_consumer_statuses(conn, config).await?;
 
     if consumer_statuses.is_empty() {
         debug!("📭 没有发现任何消费者状态");
@@ -107,7 +100,7 @@ async fn rebalance(conn: &mut ConnectionManager) -> RedisResult<()> {
     for status in consumer_statuses {
         let time_since_heartbeat = current_time - status.heartbeat.last_heartbeat;
 
-        if time_since_heartbeat > HEARTBEAT_TIMEOUT_SECONDS {
+        if time_since_heartbeat > config.heartbeat_timeout.as_secs() as i64 {
             warn!(
                 "💀 发现失效消费者: {} ({}秒无响应)",
                 status.heartbeat.consumer_name, time_since_heartbeat
@@ -124,7 +117,7 @@ async fn rebalance(conn: &mut ConnectionManager) -> RedisResult<()> {
 
     // 3. 对每个失效的消费者执行重平衡
     for failed_consumer in failed_consumers {
-        if let Err(e) = rebalance_failed_consumer(conn, &failed_consumer, &active_consumers_by_group).await {
+        if let Err(e) = rebalance_failed_consumer(conn, config, &failed_consumer, &active_consumers_by_group).await {
             error!("❌ 重平衡失效消费者 {} 失败: {}", failed_consumer.heartbeat.consumer_name, e);
         }
     }
@@ -134,16 +127,21 @@ async fn rebalance(conn: &mut ConnectionManager) -> RedisResult<()> {
 
 /// 获取消费者所属的组信息（简化版本）
 ///
-/// 由于系统设计是所有消费者都在同一个统一的组中，直接返回组名
-async fn get_consumer_group(_conn: &mut ConnectionManager, _stream_name: &str, _consumer_name: &str) -> RedisResult<String> {
+/// 由于系统设计是所有消费者都在同一个统一的组中，直接返回配置的组名
+async fn get_consumer_group(
+    _conn: &mut ConnectionManager,
+    config: &ConsumerConfig,
+    _stream_name: &str,
+    _consumer_name: &str,
+) -> RedisResult<String> {
     // 简化逻辑：系统设计所有消费者都在同一个组中
     // 这样可以避免复杂的Redis查询和解析逻辑
-    Ok(CONSUMER_GROUP_NAME.to_string())
+    Ok(config.group_name.clone())
 }
 
 /// 获取所有消费者状态
-async fn get_all_consumer_statuses(conn: &mut ConnectionManager) -> RedisResult<Vec<ConsumerStatus>> {
-    let heartbeat_map: HashMap<String, String> = conn.hgetall(CONSUMER_HEARTBEAT_KEY).await?;
+async fn get_all_consumer_statuses(conn: &mut ConnectionManager, config: &ConsumerConfig) -> RedisResult<Vec<ConsumerStatus>> {
+    let heartbeat_map: HashMap<String, String> = conn.hgetall(&config.heartbeat_key).await?;
 
     let mut statuses = Vec::new();
     for (consumer_name, heartbeat_json) in heartbeat_map {
@@ -152,7 +150,7 @@ async fn get_all_consumer_statuses(conn: &mut ConnectionManager) -> RedisResult<
                 // 验证消费者名称一致性
                 if heartbeat.consumer_name == consumer_name {
                     // 获取消费者所属的组信息
-                    if let Ok(group) = get_consumer_group(conn, &heartbeat.stream_name, &heartbeat.consumer_name).await {
+                    if let Ok(group) = get_consumer_group(conn, config, &heartbeat.stream_name, &heartbeat.consumer_name).await {
                         let status = ConsumerStatus { heartbeat, group };
                         statuses.push(status);
                     } else {
@@ -174,6 +172,7 @@ async fn get_all_consumer_statuses(conn: &mut ConnectionManager) -> RedisResult<
 /// 重平衡失效消费者的pending消息
 async fn rebalance_failed_consumer(
     conn: &mut ConnectionManager,
+    config: &ConsumerConfig,
     failed_consumer: &ConsumerStatus,
     active_consumers_by_group: &HashMap<String, Vec<ConsumerStatus>>,
 ) -> RedisResult<()> {
@@ -185,7 +184,7 @@ async fn rebalance_failed_consumer(
         _ => {
             warn!("⚠️ 组 {} 中没有活跃的消费者，跳过重平衡", failed_consumer.group);
             // 仍然删除失效消费者的状态
-            remove_consumer_status(conn, &failed_consumer.heartbeat.consumer_name).await?;
+            remove_consumer_status(conn, config, &failed_consumer.heartbeat.consumer_name).await?;
             return Ok(());
         }
     };
@@ -211,6 +210,7 @@ async fn rebalance_failed_consumer(
         // 3. 将pending消息批量分发给同组的活跃消费者
         let _ = redistribute_messages_batch(
             conn,
+            config.rebalance_batch_size,
             &failed_consumer.heartbeat.stream_name,
             &failed_consumer.group,
             &pending_messages,
@@ -220,7 +220,7 @@ async fn rebalance_failed_consumer(
     }
 
     // 4. 删除失效消费者的状态记录
-    remove_consumer_status(conn, &failed_consumer.heartbeat.consumer_name).await?;
+    remove_consumer_status(conn, config, &failed_consumer.heartbeat.consumer_name).await?;
 
     info!("✅ 完成消费者 {} 的重平衡", failed_consumer.heartbeat.consumer_name);
     Ok(())
@@ -249,6 +249,7 @@ async fn get_pending_messages(conn: &mut ConnectionManager, stream: &str, group:
 /// 批量重新分发消息到活跃的消费者
 async fn redistribute_messages_batch(
     conn: &mut ConnectionManager,
+    batch_size: usize,
     stream: &str,
     group: &str,
     message_ids: &[String],
@@ -263,7 +264,7 @@ async fn redistribute_messages_batch(
     let mut redistributed_count = 0;
 
     // 将消息ID按批次大小分组处理
-    for (chunk_idx, chunk) in message_ids.chunks(BATCH_SIZE).enumerate() {
+    for (chunk_idx, chunk) in message_ids.chunks(batch_size).enumerate() {
         // 为每个批次轮询选择消费者（简单但有效的分配策略）
         let target_consumer = match consumer_names.get(chunk_idx % consumer_names.len()) {
             Some(&consumer) => consumer,
@@ -306,16 +307,20 @@ async fn redistribute_messages_batch(
 }
 
 /// 删除消费者状态记录
-async fn remove_consumer_status(conn: &mut ConnectionManager, consumer_name: &str) -> RedisResult<()> {
-    let _: i32 = conn.hdel(CONSUMER_HEARTBEAT_KEY, consumer_name).await?;
+async fn remove_consumer_status(conn: &mut ConnectionManager, config: &ConsumerConfig, consumer_name: &str) -> RedisResult<()> {
+    let _: i32 = conn.hdel(&config.heartbeat_key, consumer_name).await?;
     info!("🗑️ 已删除失效消费者状态: {}", consumer_name);
     Ok(())
 }
 
 /// 获取组内所有消费者状态（用于监控）
 #[allow(dead_code)]
-pub async fn get_group_consumers(conn: &mut ConnectionManager, group_name: &str) -> RedisResult<Vec<ConsumerStatus>> {
-    let all_statuses = get_all_consumer_statuses(conn).await?;
+pub async fn get_group_consumers(
+    conn: &mut ConnectionManager,
+    config: &ConsumerConfig,
+    group_name: &str,
+) -> RedisResult<Vec<ConsumerStatus>> {
+    let all_statuses = get_all_consumer_statuses(conn, config).await?;
 
     let group_consumers: Vec<ConsumerStatus> = all_statuses.into_iter().filter(|status| status.group == group_name).collect();
 
@@ -323,10 +328,9 @@ pub async fn get_group_consumers(conn: &mut ConnectionManager, group_name: &str)
 }
 
 /// 手动触发重平衡（用于调试和管理）
-#[allow(dead_code)]
-pub async fn trigger_manual_rebalance(conn: &mut ConnectionManager) -> RedisResult<()> {
+pub async fn trigger_manual_rebalance(conn: &mut ConnectionManager, config: &ConsumerConfig) -> RedisResult<()> {
     info!("🔧 手动触发重平衡");
-    rebalance(conn).await
+    rebalance(conn, config).await
 }
 
 #[cfg(test)]
@@ -342,6 +346,7 @@ mod tests {
     #[tokio::test]
     async fn test_consumer_heartbeat() {
         let mut conn = get_test_connection().await;
+        let config = ConsumerConfig::default();
 
         // 创建测试心跳数据
         let test_heartbeat = RedisConsumerHeartBeat {
@@ -351,22 +356,23 @@ mod tests {
         };
 
         let heartbeat_json = serde_json::to_string(&test_heartbeat).unwrap();
-        let _: () = conn.hset(CONSUMER_HEARTBEAT_KEY, "test_consumer", heartbeat_json).await.unwrap();
+        let _: () = conn.hset(&config.heartbeat_key, "test_consumer", heartbeat_json).await.unwrap();
 
         // 验证心跳数据被正确写入
-        let statuses = get_all_consumer_statuses(&mut conn).await.unwrap();
+        let statuses = get_all_consumer_statuses(&mut conn, &config).await.unwrap();
         assert!(statuses.iter().any(|s| s.heartbeat.consumer_name == "test_consumer"));
 
         // 清理测试数据
-        let _: () = conn.del(CONSUMER_HEARTBEAT_KEY).await.unwrap();
+        let _: () = conn.del(&config.heartbeat_key).await.unwrap();
     }
 
     #[tokio::test]
     async fn test_rebalance() {
         let mut conn = get_test_connection().await;
+        let config = ConsumerConfig::default();
 
         // 创建一个过期的消费者心跳状态
-        let old_time = Utc::now().timestamp() - HEARTBEAT_TIMEOUT_SECONDS - 10;
+        let old_time = Utc::now().timestamp() - config.heartbeat_timeout.as_secs() as i64 - 10;
         let old_heartbeat = RedisConsumerHeartBeat {
             stream_name: "test_stream".to_string(),
             consumer_name: "old_consumer".to_string(),
@@ -374,17 +380,17 @@ mod tests {
         };
 
         let heartbeat_json = serde_json::to_string(&old_heartbeat).unwrap();
-        let _: () = conn.hset(CONSUMER_HEARTBEAT_KEY, "old_consumer", heartbeat_json).await.unwrap();
+        let _: () = conn.hset(&config.heartbeat_key, "old_consumer", heartbeat_json).await.unwrap();
 
         // 执行重平衡
-        let result = rebalance(&mut conn).await;
+        let result = rebalance(&mut conn, &config).await;
         assert!(result.is_ok());
 
         // 验证过期消费者状态被删除
-        let statuses = get_all_consumer_statuses(&mut conn).await.unwrap();
+        let statuses = get_all_consumer_statuses(&mut conn, &config).await.unwrap();
         assert!(!statuses.iter().any(|s| s.heartbeat.consumer_name == "old_consumer"));
 
         // 清理测试数据
-        let _: () = conn.del(CONSUMER_HEARTBEAT_KEY).await.unwrap();
+        let _: () = conn.del(&config.heartbeat_key).await.unwrap();
     }
 }
@@ -1,5 +1,10 @@
 use color_eyre::Result;
+use color_eyre::eyre::Context;
+use redis::aio::ConnectionManager;
 use shared_lib::models::config::AppConfig;
+use shared_lib::models::config_reload::ConfigReceiver;
+use shared_lib::shutdown::wait_for_shutdown;
+use shared_lib::startup::connect_redis;
 use std::sync::Arc;
 use tokio::sync::watch::Receiver;
 use tokio_cron_scheduler::{Job, JobScheduler};
@@ -8,35 +13,53 @@ use tracing::{error, info};
 pub mod jobs;
 
 /// 启动定时任务
-pub async fn start_cron_tasks(config: Arc<AppConfig>, shutdown_rx: Receiver<bool>) -> Result<()> {
+///
+/// 重平衡任务的执行计划和参数支持热加载，参考 [`shared_lib::models::config_reload`]：
+/// 每次执行时读取最新的消费者配置，执行计划变化时替换调度器中的任务。
+pub async fn start_cron_tasks(mut config: ConfigReceiver, mut shutdown_rx: Receiver<bool>) -> Result<()> {
     info!("🕐 启动定时任务调度器...");
 
+    // 创建Redis连接用于重平衡任务，连接不支持热加载；等待Redis可用期间收到关闭信号时直接退出
+    let app_config = Arc::clone(&config.borrow());
+    let redis_conn = tokio::select! {
+        conn = connect_redis(&app_config) => conn?,
+        _ = wait_for_shutdown(&mut shutdown_rx) => return Ok(()),
+    };
+
     // 创建 cron 调度器
     let mut sched = JobScheduler::new().await?;
 
-    // 创建Redis连接用于重平衡任务
-    let redis_client = redis::Client::open(config.redis.redis_conn_str.clone())?;
-    let redis_conn = redis_client.get_connection_manager().await?;
-
-    // 添加Redis消息重平衡任务 - 每10秒执行一次
-    let rebalance_job = Job::new_async("0/10 * * * * *", move |_uuid, _l| {
-        let mut conn = redis_conn.clone();
-        Box::pin(async move {
-            if let Err(e) = jobs::balance::execute_rebalance_once(&mut conn).await {
-                error!("❌ Redis重平衡任务执行失败: {}", e);
-            }
-        })
-    })?;
-
-    // 添加任务到调度器
-    sched.add(rebalance_job).await?;
+    // 添加Redis消息重平衡任务 - 默认每10秒执行一次
+    let mut schedule = config.borrow_and_update().cron.rebalance_schedule.clone();
+    let mut job_id = sched.add(rebalance_job(&schedule, &redis_conn, &config)?).await?;
 
     // 启动调度器（非阻塞）
     sched.start().await?;
-    info!("✅ 定时任务调度器已启动，Redis重平衡任务每10秒执行一次");
+    info!("✅ 定时任务调度器已启动，Redis重平衡任务执行计划: {}", schedule);
+
+    // 等待关闭信号（这里才真正阻塞等待），期间处理配置变化
+    loop {
+        tokio::select! {
+            _ = wait_for_shutdown(&mut shutdown_rx) => break,
+            Ok(()) = config.changed() => {
+                let new_schedule = config.borrow_and_update().cron.rebalance_schedule.clone();
+                if new_schedule == schedule {
+                    continue;
+                }
 
-    // 等待关闭信号（这里才真正阻塞等待）
-    wait_for_shutdown(shutdown_rx).await;
+                // 新的执行计划无效时保留原有任务
+                match rebalance_job(&new_schedule, &redis_conn, &config) {
+                    Ok(job) => {
+                        sched.remove(&job_id).await?;
+                        job_id = sched.add(job).await?;
+                        info!("🔄 Redis重平衡任务执行计划修改为: {}", new_schedule);
+                        schedule = new_schedule;
+                    }
+                    Err(err) => error!("❌ 无法修改Redis重平衡任务执行计划，继续使用 {}: {:?}", schedule, err),
+                }
+            }
+        }
+    }
 
     info!("📴 收到关闭信号，停止定时任务调度器...");
     sched.shutdown().await?;
@@ -45,13 +68,27 @@ pub async fn start_cron_tasks(config: Arc<AppConfig>, shutdown_rx: Receiver<bool
     Ok(())
 }
 
-/// 等待关闭信号
-async fn wait_for_shutdown(mut shutdown_rx: Receiver<bool>) {
-    // 如果已经是 true，直接返回
-    if *shutdown_rx.borrow() {
-        return;
-    }
+/// 创建Redis消息重平衡任务，每次执行时读取最新的消费者配置
+fn rebalance_job(schedule: &str, redis_conn: &ConnectionManager, config: &ConfigReceiver) -> Result<Job> {
+    let redis_conn = redis_conn.clone();
+    let config = config.clone();
+
+    Job::new_async(schedule, move |_uuid, _l| {
+        let mut conn = redis_conn.clone();
+        let consumer_config = config.borrow().consumer.clone();
+        Box::pin(async move {
+            if let Err(e) = jobs::balance::execute_rebalance_once(&mut conn, &consumer_config).await {
+                error!("❌ Redis重平衡任务执行失败: {}", e);
+            }
+        })
+    })
+    .with_context(|| format!("Invalid cron.rebalance_schedule `{schedule}`"))
+}
 
-    // 等待信号变化
-    let _ = shutdown_rx.changed().await;
+/// 立即执行一次Redis消息重平衡，供命令行手动触发
+pub async fn rebalance_now(config: &AppConfig) -> Result<()> {
+    let mut redis_conn = connect_redis(config).await?;
+
+    jobs::balance::trigger_manual_rebalance(&mut redis_conn, &config.consumer).await?;
+    Ok(())
 }
@@ -1,45 +1,247 @@
+use crate::migration::{apply_migrations, is_transient};
 use crate::{DatabaseError, DatabaseResult};
 use shared_lib::models::config::AppConfig;
+use shared_lib::startup::{retry, retry_when};
 use sqlx::postgres::PgPoolOptions;
-use sqlx::{Pool, Postgres};
-use std::sync::Arc;
+use sqlx::{ConnectOptions, Connection, PgPool, Postgres, Transaction};
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
-use tracing::info;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// 只读副本连接失败后，这段时间内只读查询直接使用主库，之后再尝试只读副本
+const REPLICA_RETRY_INTERVAL: Duration = Duration::from_secs(10);
 
 /// 数据库连接池
-pub type DatabasePool = Pool<Postgres>;
+///
+/// 写操作使用主库；配置了只读副本（`database.replica_url`）时，只读查询使用只读副本。
+/// 只读副本有复制延迟，需要读到刚刚写入的数据时通过 [`ProjectRepositoryTrait::read_from_primary`] 读取主库。
+/// 只读副本连接失败时只读查询改用主库，参考 [`DatabasePool::begin_read`]。
+///
+/// [`ProjectRepositoryTrait::read_from_primary`]: crate::ProjectRepositoryTrait::read_from_primary
+#[derive(Debug, Clone)]
+pub struct DatabasePool {
+    primary: PgPool,
+    replica: Option<PgPool>,
+    /// 只读副本连接失败后，在这个时间之前只读查询使用主库
+    replica_down_until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl DatabasePool {
+    /// 只使用主库的连接池
+    pub fn new(primary: PgPool) -> Self {
+        Self {
+            primary,
+            replica: None,
+            replica_down_until: Arc::default(),
+        }
+    }
+
+    /// 只读查询使用指定的只读副本
+    pub fn with_replica(self, replica: PgPool) -> Self {
+        Self {
+            replica: Some(replica),
+            ..self
+        }
+    }
+
+    /// 主库的连接池，写操作和迁移使用
+    pub fn primary(&self) -> &PgPool {
+        &self.primary
+    }
+
+    /// 只读副本的连接池
+    pub fn replica(&self) -> Option<&PgPool> {
+        self.replica.as_ref()
+    }
+
+    /// 只读查询使用的连接池，没有配置只读副本或者只读副本暂时不可用时为主库
+    pub fn reader(&self) -> &PgPool {
+        self.available_replica().unwrap_or(&self.primary)
+    }
+
+    /// 只读副本是否可用，没有配置只读副本或者最近连接失败时为 `false`
+    pub fn replica_available(&self) -> bool {
+        self.available_replica().is_some()
+    }
+
+    fn available_replica(&self) -> Option<&PgPool> {
+        let down_until = self.replica_down_until.lock().unwrap_or_else(PoisonError::into_inner);
+        match *down_until {
+            Some(until) if Instant::now() < until => None,
+            _ => self.replica.as_ref(),
+        }
+    }
+
+    /// 标记只读副本暂时不可用，[`REPLICA_RETRY_INTERVAL`] 内只读查询使用主库
+    fn mark_replica_unavailable(&self) {
+        *self.replica_down_until.lock().unwrap_or_else(PoisonError::into_inner) = Some(Instant::now() + REPLICA_RETRY_INTERVAL);
+    }
+
+    /// 开始只读事务，配置了只读副本时优先使用只读副本
+    ///
+    /// 只读副本连接失败（连接错误、获取连接超时）时改用主库，主库正常时只读查询不会因为只读副本失败
+    pub async fn begin_read(&self) -> sqlx::Result<Transaction<'static, Postgres>> {
+        if let Some(replica) = self.available_replica() {
+            match replica.begin().await {
+                Err(err) if is_connection_error(&err) => {
+                    warn!("⚠️ 只读副本连接失败，{REPLICA_RETRY_INTERVAL:?} 内只读查询改用主库: {err}");
+                    self.mark_replica_unavailable();
+                }
+                result => return result,
+            }
+        }
+        self.primary.begin().await
+    }
+
+    /// 所有连接池，主库在前
+    fn pools(&self) -> impl Iterator<Item = &PgPool> {
+        std::iter::once(&self.primary).chain(&self.replica)
+    }
+
+    /// 正在使用（还没有归还）的连接数
+    pub fn connections_in_use(&self) -> usize {
+        self.pools().map(|pool| pool.size() as usize - pool.num_idle()).sum()
+    }
+
+    /// 关闭所有连接池，等待所有连接归还
+    pub async fn close(&self) {
+        for pool in self.pools() {
+            pool.close().await;
+        }
+    }
+}
 
 /// 创建数据库连接池并执行迁移（一站式函数）
+///
+/// 数据库暂时不可用时按 `startup` 配置重试，参考 [`shared_lib::startup`]
 pub async fn initialize_database(config: Arc<AppConfig>) -> DatabaseResult<DatabasePool> {
-    // 创建数据库连接池
-    // 使用默认配置，如果有调整需要可参考sqlx文档
+    let pool = connect_database(&config).await?;
+
+    // 执行数据库迁移
+    migrate_database(&pool, &config).await?;
+
+    Ok(pool)
+}
+
+/// 只创建数据库连接池，不执行迁移，用于命令行手动管理迁移等场景
+///
+/// 数据库暂时不可用时按 `startup` 配置重试
+pub async fn connect_database(config: &AppConfig) -> DatabaseResult<DatabasePool> {
+    let pool = connect_database_lazy(config)?;
+    wait_for_database(&pool, config).await?;
+
+    info!("🗄️ 数据库连接池创建成功");
+
+    Ok(pool)
+}
+
+/// 创建数据库连接池，但不建立连接，第一次使用时才连接数据库
+///
+/// 用于降级启动（`startup.degraded`），之后通过 [`prepare_database`] 等待数据库可用并执行迁移
+pub fn connect_database_lazy(config: &AppConfig) -> DatabaseResult<DatabasePool> {
+    let database = &config.database;
+    let pool = DatabasePool::new(
+        pool_options(config)
+            .connect_lazy(database.url.expose())
+            .map_err(|e| DatabaseError::connection(format!("数据库链接地址无效: {e}")))?,
+    );
+
+    match &database.replica_url {
+        Some(url) => {
+            let replica = pool_options(config)
+                .connect_lazy(url.expose())
+                .map_err(|e| DatabaseError::connection(format!("只读副本链接地址无效: {e}")))?;
+            Ok(pool.with_replica(replica))
+        }
+        None => Ok(pool),
+    }
+}
+
+/// 连接池参数，参考 `DatabaseConfig`，主库和只读副本使用相同的参数
+fn pool_options(config: &AppConfig) -> PgPoolOptions {
     // 注意：pool已经是一个智能指针了，所以可以使用.clone()安全跨线程使用
-    let pool = PgPoolOptions::new()
-        // 启动预留，加快获取速度
-        .min_connections(10)
-        // 生产环境配置30~40即可
-        .max_connections(40)
-        .acquire_timeout(Duration::from_secs(3))
-        // 1小时空闲则释放
-        .idle_timeout(Duration::from_secs(3600))
-        // 6小时强制释放，避免长时间链接导致数据库问题
-        .max_lifetime(Duration::from_secs(3600 * 6))
+    let database = &config.database;
+    PgPoolOptions::new()
+        .min_connections(database.min_connections)
+        .max_connections(database.max_connections)
+        .acquire_timeout(database.acquire_timeout)
+        .idle_timeout(database.idle_timeout)
+        .max_lifetime(database.max_lifetime)
         .test_before_acquire(true)
-        .connect(&config.postgresql_conn_str)
-        .await
-        .map_err(|e| DatabaseError::connection(format!("连接PostgreSQL数据库失败: {e}")))?;
+}
 
-    info!("🗄️ 数据库连接池创建成功");
+/// 等待数据库可用，然后执行迁移，与 [`connect_database_lazy`] 配合使用
+pub async fn prepare_database(pool: &DatabasePool, config: &AppConfig) -> DatabaseResult<()> {
+    wait_for_database(pool, config).await?;
+    migrate_database(pool, config).await
+}
 
-    // 执行数据库迁移
-    info!("🔄 开始执行数据库迁移...");
+/// 等待主库和只读副本可用，每次连接的超时时间为 `database.acquire_timeout_secs`
+///
+/// 只读副本是可选的，超时后仍然不可用时只记录日志，不影响启动，只读查询暂时使用主库
+async fn wait_for_database(pool: &DatabasePool, config: &AppConfig) -> DatabaseResult<()> {
+    wait_for_pool("连接PostgreSQL数据库", pool.primary(), config).await?;
+    if let Some(replica) = pool.replica()
+        && let Err(err) = wait_for_pool("连接PostgreSQL只读副本", replica, config).await
+    {
+        warn!("⚠️ {err}，只读查询暂时使用主库");
+        pool.mark_replica_unavailable();
+    }
+
+    Ok(())
+}
 
-    sqlx::migrate!("../../migrations")
-        .run(&pool)
+/// 连接失败、获取连接超时等连接相关的错误，重试或者换一个数据库可能成功
+pub(crate) fn is_connection_error(err: &sqlx::Error) -> bool {
+    matches!(err, sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut)
+}
+
+async fn wait_for_pool(what: &str, pool: &PgPool, config: &AppConfig) -> DatabaseResult<()> {
+    let timeout = config.database.acquire_timeout;
+    retry(what, &config.startup, || async {
+        // 直接建立连接，通过连接池获取连接失败时只会返回超时，日志中看不到具体原因
+        let conn = tokio::time::timeout(timeout, pool.connect_options().connect())
+            .await
+            .map_err(|_| sqlx::Error::Io(io::ErrorKind::TimedOut.into()))??;
+        conn.close().await
+    })
+    .await
+    .map_err(|e| DatabaseError::connection(format!("{what}失败: {e}")))
+}
+
+/// 执行数据库迁移，迁移期间数据库连接断开时按 `startup` 配置重试，SQL错误等其他错误直接返回
+async fn migrate_database(pool: &DatabasePool, config: &AppConfig) -> DatabaseResult<()> {
+    retry_when("执行数据库迁移", &config.startup, is_transient, || apply_migrations(pool))
         .await
-        .map_err(|e| DatabaseError::migration(format!("数据库迁移失败: {e}")))?;
+        .map_err(|e| DatabaseError::migration(format!("数据库迁移失败: {e}")))
+}
 
-    info!("✅ 数据库迁移完成");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(pool)
+    #[tokio::test]
+    async fn test_replica_fallback() {
+        // 主库：只接收连接、不响应的监听器，用来确认只读查询改用了主库
+        let primary = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let primary_addr = primary.local_addr().unwrap();
+        // 只读副本：没有进程监听的端口
+        let replica_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+
+        let connect = |addr| {
+            PgPoolOptions::new()
+                .acquire_timeout(Duration::from_millis(200))
+                .connect_lazy(&format!("postgres://app@{addr}/db"))
+                .unwrap()
+        };
+        let pool = DatabasePool::new(connect(primary_addr)).with_replica(connect(replica_addr));
+        assert!(pool.replica_available());
+
+        let accepted = tokio::spawn(async move { primary.accept().await.is_ok() });
+        assert!(pool.begin_read().await.is_err());
+        assert!(accepted.await.unwrap());
+        assert!(!pool.replica_available());
+    }
 }
@@ -14,6 +14,14 @@ pub enum DatabaseError {
     /// 迁移错误
     #[error("数据库迁移错误: {0}")]
     MigrationError(String),
+
+    /// 项目层级错误：新的父项目是项目自身或其子孙，移动后会形成环
+    #[error("项目层级错误: 项目 {id} 不能移动到自身或其子项目 {parent_id} 下")]
+    InvalidHierarchy { id: i32, parent_id: i32 },
+
+    /// 没有指定租户，属于程序错误，访问数据前需要先指定租户
+    #[error("没有指定租户")]
+    MissingTenant,
 }
 
 impl DatabaseError {
@@ -4,13 +4,19 @@
 
 pub mod connection;
 pub mod error;
+pub mod migration;
 pub mod models;
 pub mod repositories;
+pub mod search;
 
-pub use connection::{DatabasePool, initialize_database};
+pub use connection::{DatabasePool, connect_database, connect_database_lazy, initialize_database, prepare_database};
 pub use error::DatabaseError;
-pub use models::project::{ProjectCreate, ProjectInfo, ProjectSearchResult, ProjectUpdate};
-pub use repositories::{project::ProjectRepository, traits::ProjectRepositoryTrait};
+pub use models::attachment::{AttachmentCreate, AttachmentInfo};
+pub use models::project::{
+    ProjectCreate, ProjectFilter, ProjectInfo, ProjectRankedSearchResult, ProjectSearchHit, ProjectSearchResult, ProjectUpdate,
+};
+pub use models::tag::{TagCreate, TagInfo, TagUpdate};
+pub use repositories::{memory::MemoryProjectRepository, project::ProjectRepository, traits::ProjectRepositoryTrait};
 
 /// 数据库操作结果类型
 pub type DatabaseResult<T> = Result<T, DatabaseError>;
@@ -0,0 +1,93 @@
+//! 数据库迁移
+//!
+//! 迁移文件位于项目根目录的 `migrations` 目录，编译时嵌入到程序中，每个迁移都包含 `up` 和 `down` 两个文件。
+//! 服务启动时（[`initialize_database`](crate::initialize_database)）会自动执行未应用的迁移，
+//! 也可以通过命令行 `rust-backend migrate up/down/status` 手动管理。
+
+use crate::connection::is_connection_error;
+use crate::{DatabaseError, DatabasePool, DatabaseResult};
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use std::collections::HashSet;
+use tracing::info;
+
+/// 嵌入程序的所有迁移
+static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+
+/// 迁移的应用状态
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    /// 版本号，即迁移文件名的时间戳前缀
+    pub version: i64,
+    /// 迁移说明，即迁移文件名去掉版本号的部分
+    pub description: String,
+    /// 是否已经应用
+    pub applied: bool,
+}
+
+/// 执行所有未应用的迁移
+pub async fn run_migrations(pool: &DatabasePool) -> DatabaseResult<()> {
+    apply_migrations(pool)
+        .await
+        .map_err(|e| DatabaseError::migration(format!("数据库迁移失败: {e}")))
+}
+
+/// 执行所有未应用的迁移，返回原始错误，方便调用方区分是否可以重试，参考 [`is_transient`]
+pub(crate) async fn apply_migrations(pool: &DatabasePool) -> Result<(), MigrateError> {
+    info!("🔄 开始执行数据库迁移...");
+    MIGRATOR.run(pool.primary()).await?;
+    info!("✅ 数据库迁移完成");
+    Ok(())
+}
+
+/// 连接断开、获取连接超时等暂时性错误，重试可能成功
+///
+/// SQL错误、已应用的迁移被修改等错误重试也不会成功，需要直接返回
+pub(crate) fn is_transient(err: &MigrateError) -> bool {
+    match err {
+        MigrateError::Execute(err) | MigrateError::ExecuteMigration(err, _) => is_connection_error(err),
+        _ => false,
+    }
+}
+
+/// 回滚最后一个已应用的迁移，没有已应用的迁移时返回 `None`
+pub async fn revert_last_migration(pool: &DatabasePool) -> DatabaseResult<Option<MigrationStatus>> {
+    let applied: Vec<MigrationStatus> = migration_status(pool).await?.into_iter().filter(|m| m.applied).collect();
+    let Some((last, rest)) = applied.split_last() else {
+        return Ok(None);
+    };
+
+    // undo会回滚所有版本号大于target的迁移，target取上一个已应用的版本
+    let target = rest.last().map_or(0, |m| m.version);
+    MIGRATOR
+        .undo(pool.primary(), target)
+        .await
+        .map_err(|e| DatabaseError::migration(format!("回滚迁移 {} 失败: {e}", last.version)))?;
+
+    info!("↩️ 已回滚迁移 {} {}", last.version, last.description);
+    Ok(Some(last.clone()))
+}
+
+/// 所有迁移的应用状态，按版本号升序排列
+pub async fn migration_status(pool: &DatabasePool) -> DatabaseResult<Vec<MigrationStatus>> {
+    let mut conn = pool.primary().acquire().await?;
+    conn.ensure_migrations_table()
+        .await
+        .map_err(|e| DatabaseError::migration(format!("创建迁移记录表失败: {e}")))?;
+    let applied: HashSet<i64> = conn
+        .list_applied_migrations()
+        .await
+        .map_err(|e| DatabaseError::migration(format!("读取迁移记录失败: {e}")))?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            applied: applied.contains(&m.version),
+        })
+        .collect())
+}
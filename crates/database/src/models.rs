@@ -2,7 +2,13 @@
 //!
 //! 这里定义与数据库表对应的结构体和相关操作
 
+pub mod attachment;
 pub mod project;
+pub mod tag;
 
 // 重新导出具体的模型
-pub use project::{ProjectCreate, ProjectInfo, ProjectSearchResult, ProjectUpdate};
+pub use attachment::{AttachmentCreate, AttachmentInfo};
+pub use project::{
+    ProjectCreate, ProjectFilter, ProjectInfo, ProjectRankedSearchResult, ProjectSearchHit, ProjectSearchResult, ProjectUpdate,
+};
+pub use tag::{TagCreate, TagInfo, TagUpdate};
@@ -0,0 +1,34 @@
+//! 附件数据库模型
+//!
+//! 定义项目附件元数据相关的数据库模型结构体，附件内容保存在附件存储中
+
+use chrono::NaiveDateTime;
+
+/// 附件信息结构体
+#[derive(Debug, Clone)]
+pub struct AttachmentInfo {
+    pub id: i32,
+    pub project_id: i32,
+    /// 上传时的原始文件名
+    pub file_name: String,
+    pub content_type: String,
+    /// 内容大小（字节）
+    pub size: i64,
+    /// 内容的sha256（小写十六进制）
+    pub sha256: String,
+    /// 内容在附件存储中的路径
+    pub storage_key: String,
+    /// 创建时间（UTC）
+    pub created_at: NaiveDateTime,
+}
+
+/// 附件创建参数
+#[derive(Debug, Clone)]
+pub struct AttachmentCreate {
+    pub project_id: i32,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub sha256: String,
+    pub storage_key: String,
+}
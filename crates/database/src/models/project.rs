@@ -2,12 +2,35 @@
 //!
 //! 定义项目相关的数据库模型结构体
 
+use chrono::NaiveDateTime;
+
 /// 项目信息结构体
 #[derive(Debug, Clone)]
 pub struct ProjectInfo {
     pub id: i32,
     pub project_name: String,
     pub comment: String,
+    /// 父项目ID，顶层项目为 `None`
+    pub parent_id: Option<i32>,
+    /// 创建时间（UTC）
+    pub created_at: NaiveDateTime,
+    /// 最后更新时间（UTC）
+    pub updated_at: NaiveDateTime,
+    /// 项目标签名称，按名称排序
+    pub tags: Vec<String>,
+}
+
+/// 项目搜索条件
+///
+/// 所有条件同时满足时才会返回，未填写的条件不参与过滤
+#[derive(Debug, Clone, Default)]
+pub struct ProjectFilter {
+    /// 项目名称（模糊搜索）
+    pub project_name: Option<String>,
+    /// 至少包含其中一个标签
+    pub any_tags: Vec<String>,
+    /// 包含所有标签
+    pub all_tags: Vec<String>,
 }
 
 /// 项目搜索结果
@@ -17,11 +40,33 @@ pub struct ProjectSearchResult {
     pub total: u32,
 }
 
+/// 按相关度排序的搜索命中项
+#[derive(Debug, Clone)]
+pub struct ProjectSearchHit {
+    /// 命中的项目
+    pub project: ProjectInfo,
+    /// 综合相关度得分，取名称与说明得分中的较大值（0~1）
+    pub score: f32,
+    /// 项目名称的高亮片段，没有命中时为 `None`
+    pub name_highlight: Option<String>,
+    /// 项目说明的高亮片段，没有命中时为 `None`
+    pub comment_highlight: Option<String>,
+}
+
+/// 按相关度排序的搜索结果
+#[derive(Debug, Clone)]
+pub struct ProjectRankedSearchResult {
+    pub hits: Vec<ProjectSearchHit>,
+    pub total: u32,
+}
+
 /// 项目创建参数
 #[derive(Debug, Clone)]
 pub struct ProjectCreate {
     pub project_name: String,
     pub comment: String,
+    /// 父项目ID，为 `None` 时创建顶层项目
+    pub parent_id: Option<i32>,
 }
 
 /// 项目更新参数
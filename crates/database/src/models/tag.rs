@@ -0,0 +1,32 @@
+//! 标签数据库模型
+//!
+//! 定义标签相关的数据库模型结构体
+
+use chrono::NaiveDateTime;
+
+/// 标签信息结构体
+#[derive(Debug, Clone)]
+pub struct TagInfo {
+    pub id: i32,
+    /// 标签名称，在租户内唯一，例如 `team:payments`
+    pub name: String,
+    pub comment: String,
+    /// 创建时间（UTC）
+    pub created_at: NaiveDateTime,
+    /// 最后更新时间（UTC）
+    pub updated_at: NaiveDateTime,
+}
+
+/// 标签创建参数
+#[derive(Debug, Clone)]
+pub struct TagCreate {
+    pub name: String,
+    pub comment: String,
+}
+
+/// 标签更新参数
+#[derive(Debug, Clone)]
+pub struct TagUpdate {
+    pub name: Option<String>,
+    pub comment: Option<String>,
+}
@@ -0,0 +1,765 @@
+//! 内存项目仓库
+//!
+//! [`ProjectRepositoryTrait`] 的内存实现，不需要数据库，主要用于测试。
+//!
+//! 行为与 [`crate::ProjectRepository`] 保持一致：
+//! - 名称搜索使用 `LIKE '%name%'` 语义（区分大小写，`%`、`_` 为通配符）
+//! - 分页结果的总数来自当前页，页码超出范围时总数为0（与 `COUNT(*) OVER ()` 的行为相同）
+//! - 相关度搜索、名称提示使用与 `pg_trgm` 一致的相似度算法
+//! - 查询、更新、删除不存在的项目或标签时返回 [`sqlx::Error::RowNotFound`]
+//! - 标签名称重复时返回唯一约束错误（[`sqlx::error::ErrorKind::UniqueViolation`]）
+//! - 删除后项目ID、标签ID不会被复用（与 `serial` 一致）
+//! - 删除项目时子项目挂到被删除项目的父项目下，移动项目会形成环时返回 [`DatabaseError::InvalidHierarchy`]
+//! - 删除项目时同时删除附件元数据（与 `on delete cascade` 一致）
+//! - 每个租户的数据相互隔离（与行级安全策略一致），ID在所有租户之间分配，标签名称在租户内唯一
+//! - 没有指定租户时返回 [`DatabaseError::MissingTenant`]
+//! - 通过 [`MemoryProjectRepository::set_unavailable`] 模拟数据库不可用，所有操作返回 [`sqlx::Error::PoolTimedOut`]
+
+use crate::models::attachment::{AttachmentCreate, AttachmentInfo};
+use crate::models::project::{
+    ProjectCreate, ProjectFilter, ProjectInfo, ProjectRankedSearchResult, ProjectSearchHit, ProjectSearchResult, ProjectUpdate,
+};
+use crate::models::tag::{TagCreate, TagInfo, TagUpdate};
+use crate::repositories::traits::ProjectRepositoryTrait;
+use crate::search::{highlight_fragment, trigram_similarity, word_similarity};
+use crate::{DatabaseError, DatabaseResult};
+use chrono::Utc;
+use shared_lib::models::tenant::TenantId;
+use sqlx::error::ErrorKind;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// `pg_trgm.word_similarity_threshold` 的默认值，名称提示使用
+const DEFAULT_WORD_SIMILARITY_THRESHOLD: f32 = 0.6;
+
+/// 内存项目仓库
+///
+/// 内部使用 [`Arc`] 共享数据，`clone` 之后的实例（包括 [`ProjectRepositoryTrait::with_tenant`] 返回的实例）操作的是同一份数据
+#[derive(Debug, Clone, Default)]
+pub struct MemoryProjectRepository {
+    inner: Arc<Mutex<HashMap<TenantId, MemoryProjects>>>,
+    ids: Arc<MemoryIds>,
+    tenant: Option<TenantId>,
+    unavailable: Arc<AtomicBool>,
+}
+
+/// 最后分配的ID，所有租户共用（与 `serial` 一致）
+#[derive(Debug, Default)]
+struct MemoryIds {
+    project: AtomicI32,
+    tag: AtomicI32,
+    attachment: AtomicI32,
+}
+
+/// 单个租户的数据
+#[derive(Debug, Default)]
+struct MemoryProjects {
+    /// 按ID排序的项目列表，`tags` 字段在读取时根据 `project_tags` 填充
+    projects: Vec<ProjectInfo>,
+    /// 按ID排序的标签列表
+    tags: Vec<TagInfo>,
+    /// 项目与标签的关联关系 `(project_id, tag_id)`
+    project_tags: BTreeSet<(i32, i32)>,
+    /// 按ID排序的附件列表
+    attachments: Vec<AttachmentInfo>,
+}
+
+/// 当前租户数据的锁
+struct TenantGuard<'a> {
+    guard: MutexGuard<'a, HashMap<TenantId, MemoryProjects>>,
+    tenant: &'a TenantId,
+}
+
+impl Deref for TenantGuard<'_> {
+    type Target = MemoryProjects;
+
+    fn deref(&self) -> &MemoryProjects {
+        &self.guard[self.tenant]
+    }
+}
+
+impl DerefMut for TenantGuard<'_> {
+    fn deref_mut(&mut self) -> &mut MemoryProjects {
+        self.guard.get_mut(self.tenant).expect("tenant data is created when locking")
+    }
+}
+
+impl MemoryProjectRepository {
+    /// 创建空的内存仓库
+    ///
+    /// 访问数据前需要先通过 [`ProjectRepositoryTrait::with_tenant`] 指定租户
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前租户的所有项目，按ID排序
+    pub fn projects(&self) -> DatabaseResult<Vec<ProjectInfo>> {
+        let data = self.lock()?;
+        Ok(data.projects.iter().map(|p| data.with_tags(p)).collect())
+    }
+
+    /// 模拟数据库不可用，用于测试错误处理和就绪探针，对所有 `clone` 之后的实例生效
+    pub fn set_unavailable(&self, unavailable: bool) {
+        self.unavailable.store(unavailable, Ordering::Relaxed);
+    }
+
+    /// 数据库不可用时返回与连接池获取连接超时相同的错误
+    fn check_available(&self) -> DatabaseResult<()> {
+        if self.unavailable.load(Ordering::Relaxed) {
+            return Err(DatabaseError::SqlxError(sqlx::Error::PoolTimedOut));
+        }
+        Ok(())
+    }
+
+    /// 锁定当前租户的数据
+    fn lock(&self) -> DatabaseResult<TenantGuard<'_>> {
+        self.check_available()?;
+        let tenant = self.tenant.as_ref().ok_or(DatabaseError::MissingTenant)?;
+        let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        guard.entry(tenant.clone()).or_default();
+        Ok(TenantGuard { guard, tenant })
+    }
+
+    /// 分配下一个ID
+    fn next_id(counter: &AtomicI32) -> i32 {
+        counter.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+impl MemoryProjects {
+    fn position(&self, id: i32) -> DatabaseResult<usize> {
+        self.projects
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or(DatabaseError::SqlxError(sqlx::Error::RowNotFound))
+    }
+
+    fn attachment_position(&self, project_id: i32, id: i32) -> DatabaseResult<usize> {
+        self.attachments
+            .iter()
+            .position(|a| a.project_id == project_id && a.id == id)
+            .ok_or(DatabaseError::SqlxError(sqlx::Error::RowNotFound))
+    }
+
+    fn tag_position(&self, id: i32) -> DatabaseResult<usize> {
+        self.tags
+            .iter()
+            .position(|t| t.id == id)
+            .ok_or(DatabaseError::SqlxError(sqlx::Error::RowNotFound))
+    }
+
+    /// 检查标签名称是否已经被其他标签使用
+    fn check_tag_name(&self, name: &str, id: Option<i32>) -> DatabaseResult<()> {
+        if self.tags.iter().any(|t| t.name == name && Some(t.id) != id) {
+            return Err(DatabaseError::SqlxError(sqlx::Error::Database(Box::new(UniqueViolation {
+                constraint: "tags_tenant_id_name_key",
+            }))));
+        }
+        Ok(())
+    }
+
+    /// 项目的标签名称，按名称排序
+    fn tag_names(&self, project_id: i32) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .project_tags
+            .iter()
+            .filter(|(p, _)| *p == project_id)
+            .filter_map(|(_, tag_id)| self.tags.iter().find(|t| t.id == *tag_id))
+            .map(|t| t.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// 填充项目的标签
+    fn with_tags(&self, project: &ProjectInfo) -> ProjectInfo {
+        ProjectInfo {
+            tags: self.tag_names(project.id),
+            ..project.clone()
+        }
+    }
+
+    fn project_with_tags(&self, id: i32) -> DatabaseResult<ProjectInfo> {
+        let index = self.position(id)?;
+        Ok(self.with_tags(&self.projects[index]))
+    }
+}
+
+/// 模拟数据库的唯一约束错误
+#[derive(Debug)]
+struct UniqueViolation {
+    constraint: &'static str,
+}
+
+impl Display for UniqueViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "duplicate key value violates unique constraint \"{}\"", self.constraint)
+    }
+}
+
+impl std::error::Error for UniqueViolation {}
+
+impl sqlx::error::DatabaseError for UniqueViolation {
+    fn message(&self) -> &str {
+        "duplicate key value violates unique constraint"
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed("23505"))
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        Some(self.constraint)
+    }
+
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::UniqueViolation
+    }
+}
+
+#[async_trait::async_trait]
+impl ProjectRepositoryTrait for MemoryProjectRepository {
+    fn with_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            tenant: Some(tenant.clone()),
+            ..self.clone()
+        }
+    }
+
+    async fn find_projects(&self, filter: ProjectFilter, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult> {
+        let name = filter.project_name.unwrap_or_default();
+        let pattern = format!("%{name}%");
+
+        let data = self.lock()?;
+        let matched: Vec<ProjectInfo> = data
+            .projects
+            .iter()
+            .filter(|p| name.is_empty() || like(&p.project_name, &pattern))
+            .map(|p| data.with_tags(p))
+            .filter(|p| filter.any_tags.is_empty() || filter.any_tags.iter().any(|t| p.tags.contains(t)))
+            .filter(|p| filter.all_tags.iter().all(|t| p.tags.contains(t)))
+            .collect();
+
+        let total = matched.len() as u32;
+        let projects = paginate(matched, page_size, offset);
+
+        Ok(ProjectSearchResult {
+            total: if projects.is_empty() { 0 } else { total },
+            projects,
+        })
+    }
+
+    async fn search_projects_ranked(
+        &self,
+        keyword: String,
+        threshold: f32,
+        page_size: i64,
+        offset: i64,
+    ) -> DatabaseResult<ProjectRankedSearchResult> {
+        let score = |text: &str| trigram_similarity(text, &keyword).max(word_similarity(&keyword, text));
+
+        let data = self.lock()?;
+        let mut scored: Vec<(&ProjectInfo, f32)> = data
+            .projects
+            .iter()
+            .filter(|p| score(&p.project_name) >= threshold || score(&p.comment) >= threshold)
+            .map(|p| (p, score(&p.project_name).max(score(&p.comment))))
+            .collect();
+        scored.sort_by(|(a, a_score), (b, b_score)| b_score.total_cmp(a_score).then(a.id.cmp(&b.id)));
+
+        let total = scored.len() as u32;
+        let hits: Vec<ProjectSearchHit> = paginate(scored, page_size, offset)
+            .into_iter()
+            .map(|(project, score)| ProjectSearchHit {
+                name_highlight: highlight_fragment(&project.project_name, &keyword, threshold),
+                comment_highlight: highlight_fragment(&project.comment, &keyword, threshold),
+                score,
+                project: data.with_tags(project),
+            })
+            .collect();
+
+        Ok(ProjectRankedSearchResult {
+            total: if hits.is_empty() { 0 } else { total },
+            hits,
+        })
+    }
+
+    async fn suggest_project_names(&self, prefix: String, limit: i64) -> DatabaseResult<Vec<String>> {
+        let lowered_prefix = prefix.to_lowercase();
+
+        let data = self.lock()?;
+        let mut names: Vec<(bool, f32, String)> = data
+            .projects
+            .iter()
+            .map(|p| {
+                let prefix_hit = p.project_name.to_lowercase().starts_with(&lowered_prefix);
+                (prefix_hit, word_similarity(&prefix, &p.project_name), p.project_name.clone())
+            })
+            .filter(|(prefix_hit, similarity, _)| *prefix_hit || *similarity >= DEFAULT_WORD_SIMILARITY_THRESHOLD)
+            .collect();
+
+        names.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.total_cmp(&a.1)).then(a.2.cmp(&b.2)));
+        names.dedup_by(|a, b| a.2 == b.2);
+
+        Ok(names.into_iter().take(limit.max(0) as usize).map(|(_, _, name)| name).collect())
+    }
+
+    async fn create_project(&self, project: ProjectCreate) -> DatabaseResult<ProjectInfo> {
+        let mut data = self.lock()?;
+        if let Some(parent_id) = project.parent_id {
+            data.position(parent_id)?;
+        }
+
+        let now = Utc::now().naive_utc();
+        let project = ProjectInfo {
+            id: Self::next_id(&self.ids.project),
+            project_name: project.project_name,
+            comment: project.comment,
+            parent_id: project.parent_id,
+            created_at: now,
+            updated_at: now,
+            tags: Vec::new(),
+        };
+        data.projects.push(project.clone());
+
+        Ok(project)
+    }
+
+    async fn get_project_by_id(&self, id: i32) -> DatabaseResult<ProjectInfo> {
+        self.lock()?.project_with_tags(id)
+    }
+
+    async fn get_projects_by_ids(&self, ids: &[i32]) -> DatabaseResult<Vec<ProjectInfo>> {
+        let data = self.lock()?;
+        Ok(data
+            .projects
+            .iter()
+            .filter(|p| ids.contains(&p.id))
+            .map(|p| data.with_tags(p))
+            .collect())
+    }
+
+    async fn update_project(&self, id: i32, update: ProjectUpdate) -> DatabaseResult<ProjectInfo> {
+        let mut data = self.lock()?;
+        let index = data.position(id)?;
+
+        let project = &mut data.projects[index];
+        if let Some(project_name) = update.project_name {
+            project.project_name = project_name;
+        }
+        if let Some(comment) = update.comment {
+            project.comment = comment;
+        }
+        project.updated_at = Utc::now().naive_utc();
+
+        data.project_with_tags(id)
+    }
+
+    async fn delete_project(&self, id: i32) -> DatabaseResult<ProjectInfo> {
+        let mut data = self.lock()?;
+        let project = data.project_with_tags(id)?;
+
+        let now = Utc::now().naive_utc();
+        for child in data.projects.iter_mut().filter(|p| p.parent_id == Some(id)) {
+            child.parent_id = project.parent_id;
+            child.updated_at = now;
+        }
+        data.projects.retain(|p| p.id != id);
+        data.project_tags.retain(|(project_id, _)| *project_id != id);
+        data.attachments.retain(|a| a.project_id != id);
+
+        Ok(project)
+    }
+
+    async fn get_project_ancestors(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>> {
+        let data = self.lock()?;
+        let mut ancestors = Vec::new();
+        let mut parent_id = data.project_with_tags(id)?.parent_id;
+        while let Some(id) = parent_id {
+            let parent = data.project_with_tags(id)?;
+            parent_id = parent.parent_id;
+            ancestors.push(parent);
+        }
+        ancestors.reverse();
+
+        Ok(ancestors)
+    }
+
+    async fn get_project_subtree(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>> {
+        let data = self.lock()?;
+        let mut subtree = vec![data.project_with_tags(id)?];
+
+        // 按层级逐层查找，`projects` 按ID排序，因此同一层的项目也按ID排序
+        let mut level = vec![id];
+        while !level.is_empty() {
+            let children: Vec<ProjectInfo> = data
+                .projects
+                .iter()
+                .filter(|p| p.parent_id.is_some_and(|parent_id| level.contains(&parent_id)))
+                .map(|p| data.with_tags(p))
+                .collect();
+            level = children.iter().map(|p| p.id).collect();
+            subtree.extend(children);
+        }
+
+        Ok(subtree)
+    }
+
+    async fn move_project(&self, id: i32, parent_id: Option<i32>) -> DatabaseResult<ProjectInfo> {
+        let mut data = self.lock()?;
+        let index = data.position(id)?;
+
+        let mut ancestor_id = parent_id;
+        while let Some(current) = ancestor_id {
+            if current == id {
+                return Err(DatabaseError::InvalidHierarchy {
+                    id,
+                    parent_id: parent_id.unwrap_or_default(),
+                });
+            }
+            ancestor_id = data.projects[data.position(current)?].parent_id;
+        }
+
+        let project = &mut data.projects[index];
+        project.parent_id = parent_id;
+        project.updated_at = Utc::now().naive_utc();
+
+        data.project_with_tags(id)
+    }
+
+    async fn find_tags(&self) -> DatabaseResult<Vec<TagInfo>> {
+        let mut tags = self.lock()?.tags.clone();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    async fn create_tag(&self, tag: TagCreate) -> DatabaseResult<TagInfo> {
+        let mut data = self.lock()?;
+        data.check_tag_name(&tag.name, None)?;
+
+        let now = Utc::now().naive_utc();
+        let tag = TagInfo {
+            id: Self::next_id(&self.ids.tag),
+            name: tag.name,
+            comment: tag.comment,
+            created_at: now,
+            updated_at: now,
+        };
+        data.tags.push(tag.clone());
+
+        Ok(tag)
+    }
+
+    async fn get_tag_by_id(&self, id: i32) -> DatabaseResult<TagInfo> {
+        let data = self.lock()?;
+        let index = data.tag_position(id)?;
+        Ok(data.tags[index].clone())
+    }
+
+    async fn update_tag(&self, id: i32, update: TagUpdate) -> DatabaseResult<TagInfo> {
+        let mut data = self.lock()?;
+        let index = data.tag_position(id)?;
+        if let Some(name) = &update.name {
+            data.check_tag_name(name, Some(id))?;
+        }
+
+        let tag = &mut data.tags[index];
+        if let Some(name) = update.name {
+            tag.name = name;
+        }
+        if let Some(comment) = update.comment {
+            tag.comment = comment;
+        }
+        tag.updated_at = Utc::now().naive_utc();
+
+        Ok(tag.clone())
+    }
+
+    async fn delete_tag(&self, id: i32) -> DatabaseResult<TagInfo> {
+        let mut data = self.lock()?;
+        let index = data.tag_position(id)?;
+        data.project_tags.retain(|(_, tag_id)| *tag_id != id);
+        Ok(data.tags.remove(index))
+    }
+
+    async fn attach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo> {
+        let mut data = self.lock()?;
+        data.tag_position(tag_id)?;
+        data.position(project_id)?;
+
+        data.project_tags.insert((project_id, tag_id));
+        data.project_with_tags(project_id)
+    }
+
+    async fn detach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo> {
+        let mut data = self.lock()?;
+        data.position(project_id)?;
+
+        data.project_tags.remove(&(project_id, tag_id));
+        data.project_with_tags(project_id)
+    }
+
+    async fn find_attachments(&self, project_id: i32) -> DatabaseResult<Vec<AttachmentInfo>> {
+        let data = self.lock()?;
+        data.position(project_id)?;
+        Ok(data.attachments.iter().filter(|a| a.project_id == project_id).cloned().collect())
+    }
+
+    async fn create_attachment(&self, attachment: AttachmentCreate) -> DatabaseResult<AttachmentInfo> {
+        let mut data = self.lock()?;
+        data.position(attachment.project_id)?;
+
+        let attachment = AttachmentInfo {
+            id: Self::next_id(&self.ids.attachment),
+            project_id: attachment.project_id,
+            file_name: attachment.file_name,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            sha256: attachment.sha256,
+            storage_key: attachment.storage_key,
+            created_at: Utc::now().naive_utc(),
+        };
+        data.attachments.push(attachment.clone());
+
+        Ok(attachment)
+    }
+
+    async fn get_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo> {
+        let data = self.lock()?;
+        let index = data.attachment_position(project_id, id)?;
+        Ok(data.attachments[index].clone())
+    }
+
+    async fn delete_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo> {
+        let mut data = self.lock()?;
+        let index = data.attachment_position(project_id, id)?;
+        Ok(data.attachments.remove(index))
+    }
+
+    async fn ping(&self) -> DatabaseResult<()> {
+        self.check_available()
+    }
+}
+
+/// 按 `LIMIT page_size OFFSET offset` 截取
+fn paginate<T>(items: Vec<T>, page_size: i64, offset: i64) -> Vec<T> {
+    items
+        .into_iter()
+        .skip(offset.max(0) as usize)
+        .take(page_size.max(0) as usize)
+        .collect()
+}
+
+/// PostgreSQL `LIKE` 匹配：`%` 匹配任意长度字符，`_` 匹配单个字符，`\` 转义下一个字符
+fn like(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    like_at(&text, &pattern)
+}
+
+fn like_at(text: &[char], pattern: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('%', rest)) => (0..=text.len()).any(|skip| like_at(&text[skip..], rest)),
+        Some(('_', rest)) => !text.is_empty() && like_at(&text[1..], rest),
+        Some(('\\', rest)) if !rest.is_empty() => text.first() == Some(&rest[0]) && like_at(&text[1..], &rest[1..]),
+        Some((c, rest)) => text.first() == Some(c) && like_at(&text[1..], rest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tenant() -> TenantId {
+        "test".parse().unwrap()
+    }
+
+    fn create(name: &str) -> ProjectCreate {
+        ProjectCreate {
+            project_name: name.to_string(),
+            comment: String::new(),
+            parent_id: None,
+        }
+    }
+
+    fn create_child(name: &str, parent_id: i32) -> ProjectCreate {
+        ProjectCreate {
+            parent_id: Some(parent_id),
+            ..create(name)
+        }
+    }
+
+    fn name_filter(name: &str) -> ProjectFilter {
+        ProjectFilter {
+            project_name: Some(name.to_string()),
+            ..ProjectFilter::default()
+        }
+    }
+
+    #[test]
+    fn test_like() {
+        assert!(like("payments", "%pay%"));
+        assert!(!like("Payments", "%pay%"));
+        assert!(like("a_b", "%a\\_b%"));
+        assert!(!like("axb", "%a\\_b%"));
+        assert!(like("axb", "%a_b%"));
+    }
+
+    #[tokio::test]
+    async fn test_find_projects_paging() {
+        let repo = MemoryProjectRepository::new().with_tenant(&tenant());
+        for name in ["pay-1", "pay-2", "pay-3", "other"] {
+            repo.create_project(create(name)).await.unwrap();
+        }
+
+        let page = repo.find_projects(name_filter("pay"), 2, 2).await.unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.projects[0].project_name, "pay-3");
+
+        let out_of_range = repo.find_projects(name_filter("pay"), 2, 4).await.unwrap();
+        assert_eq!(out_of_range.total, 0);
+        assert!(out_of_range.projects.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_project() {
+        let repo = MemoryProjectRepository::new().with_tenant(&tenant());
+        let project = repo.create_project(create("pay")).await.unwrap();
+        repo.delete_project(project.id).await.unwrap();
+
+        let err = repo.get_project_by_id(project.id).await.unwrap_err();
+        assert!(matches!(err, DatabaseError::SqlxError(sqlx::Error::RowNotFound)));
+
+        // 删除后ID不会被复用
+        assert_eq!(repo.create_project(create("pay")).await.unwrap().id, project.id + 1);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_isolation() {
+        let repo = MemoryProjectRepository::new();
+        assert!(matches!(repo.find_tags().await, Err(DatabaseError::MissingTenant)));
+
+        let acme = repo.with_tenant(&tenant());
+        let other = repo.with_tenant(&"other".parse().unwrap());
+        let project = acme.create_project(create("pay")).await.unwrap();
+        acme.create_tag(TagCreate {
+            name: "rust".to_string(),
+            comment: String::new(),
+        })
+        .await
+        .unwrap();
+
+        assert!(other.get_project_by_id(project.id).await.is_err());
+        assert!(other.create_project(create_child("child", project.id)).await.is_err());
+        assert_eq!(other.find_projects(name_filter("pay"), 10, 0).await.unwrap().total, 0);
+
+        // 标签名称在租户内唯一，ID在所有租户之间分配
+        let tag = other
+            .create_tag(TagCreate {
+                name: "rust".to_string(),
+                comment: String::new(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(tag.id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_tag_filters() {
+        let repo = MemoryProjectRepository::new().with_tenant(&tenant());
+        let mut tag_ids = Vec::new();
+        for name in ["env:prod", "team:payments", "team:search"] {
+            let tag = TagCreate {
+                name: name.to_string(),
+                comment: String::new(),
+            };
+            tag_ids.push(repo.create_tag(tag).await.unwrap().id);
+        }
+        let pay = repo.create_project(create("pay")).await.unwrap();
+        let search = repo.create_project(create("search")).await.unwrap();
+        repo.attach_tag(pay.id, tag_ids[0]).await.unwrap();
+        repo.attach_tag(pay.id, tag_ids[1]).await.unwrap();
+        repo.attach_tag(search.id, tag_ids[2]).await.unwrap();
+
+        let find = |any_tags: &[&str], all_tags: &[&str]| {
+            let filter = ProjectFilter {
+                project_name: None,
+                any_tags: any_tags.iter().map(|t| t.to_string()).collect(),
+                all_tags: all_tags.iter().map(|t| t.to_string()).collect(),
+            };
+            let repo = repo.clone();
+            async move {
+                let result = repo.find_projects(filter, 10, 0).await.unwrap();
+                result.projects.into_iter().map(|p| p.project_name).collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(find(&["team:payments", "team:search"], &[]).await, ["pay", "search"]);
+        assert_eq!(find(&[], &["env:prod", "team:payments"]).await, ["pay"]);
+        assert!(find(&[], &["env:prod", "team:search"]).await.is_empty());
+
+        // 删除标签后自动从项目中移除
+        repo.delete_tag(tag_ids[0]).await.unwrap();
+        assert_eq!(repo.get_project_by_id(pay.id).await.unwrap().tags, ["team:payments"]);
+
+        let duplicated = TagCreate {
+            name: "team:search".to_string(),
+            comment: String::new(),
+        };
+        let err = repo.create_tag(duplicated).await.unwrap_err();
+        assert!(matches!(err, DatabaseError::SqlxError(sqlx::Error::Database(e)) if e.is_unique_violation()));
+    }
+
+    #[tokio::test]
+    async fn test_project_hierarchy() {
+        let repo = MemoryProjectRepository::new().with_tenant(&tenant());
+        let program = repo.create_project(create("program")).await.unwrap();
+        let project = repo.create_project(create_child("project", program.id)).await.unwrap();
+        let sub_a = repo.create_project(create_child("sub-a", project.id)).await.unwrap();
+        let sub_b = repo.create_project(create_child("sub-b", project.id)).await.unwrap();
+
+        let names = |projects: Vec<ProjectInfo>| projects.into_iter().map(|p| p.project_name).collect::<Vec<_>>();
+        assert_eq!(names(repo.get_project_ancestors(sub_a.id).await.unwrap()), ["program", "project"]);
+        assert!(repo.get_project_ancestors(program.id).await.unwrap().is_empty());
+        assert_eq!(
+            names(repo.get_project_subtree(program.id).await.unwrap()),
+            ["program", "project", "sub-a", "sub-b"]
+        );
+
+        // 不能移动到自身或子孙下
+        for parent_id in [program.id, sub_b.id] {
+            let err = repo.move_project(program.id, Some(parent_id)).await.unwrap_err();
+            assert!(matches!(err, DatabaseError::InvalidHierarchy { .. }));
+        }
+        let err = repo.move_project(sub_a.id, Some(999)).await.unwrap_err();
+        assert!(matches!(err, DatabaseError::SqlxError(sqlx::Error::RowNotFound)));
+
+        let moved = repo.move_project(sub_b.id, Some(sub_a.id)).await.unwrap();
+        assert_eq!(moved.parent_id, Some(sub_a.id));
+
+        // 删除项目后子项目挂到父项目下
+        repo.delete_project(project.id).await.unwrap();
+        assert_eq!(repo.get_project_by_id(sub_a.id).await.unwrap().parent_id, Some(program.id));
+        assert_eq!(
+            names(repo.get_project_subtree(program.id).await.unwrap()),
+            ["program", "sub-a", "sub-b"]
+        );
+    }
+}
@@ -2,33 +2,143 @@
 //!
 //! 负责项目相关的数据库操作
 
-use crate::DatabaseResult;
-use crate::models::project::{ProjectCreate, ProjectInfo, ProjectSearchResult, ProjectUpdate};
+use crate::models::attachment::{AttachmentCreate, AttachmentInfo};
+use crate::models::project::{
+    ProjectCreate, ProjectFilter, ProjectInfo, ProjectRankedSearchResult, ProjectSearchHit, ProjectSearchResult, ProjectUpdate,
+};
+use crate::models::tag::{TagCreate, TagInfo, TagUpdate};
 use crate::repositories::traits::ProjectRepositoryTrait;
-use sqlx::PgPool;
+use crate::search::highlight_fragment;
+use crate::{DatabaseError, DatabasePool, DatabaseResult};
+use shared_lib::models::tenant::TenantId;
+use sqlx::{PgExecutor, Postgres, Transaction};
 use tracing::debug;
 
+/// 查询时切换到的数据库角色，行级安全策略对该角色生效
+const TENANT_ROLE: &str = "hm_tenant";
+
 /// 项目仓库结构体
 #[derive(Debug, Clone)]
 pub struct ProjectRepository {
-    pool: PgPool,
+    pool: DatabasePool,
+    /// 当前租户，通过 [`ProjectRepositoryTrait::with_tenant`] 设置
+    tenant: Option<TenantId>,
+    /// 只读查询也使用主库，通过 [`ProjectRepositoryTrait::read_from_primary`] 设置
+    read_primary: bool,
 }
 
 impl ProjectRepository {
     /// 创建新的项目仓库实例
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    ///
+    /// 访问数据前需要先通过 [`ProjectRepositoryTrait::with_tenant`] 指定租户
+    pub fn new(pool: DatabasePool) -> Self {
+        Self {
+            pool,
+            tenant: None,
+            read_primary: false,
+        }
+    }
+
+    /// 在主库上开始当前租户的事务，写操作使用
+    async fn begin(&self) -> DatabaseResult<Transaction<'static, Postgres>> {
+        let tenant = self.tenant()?;
+        Self::enter_tenant(self.pool.primary().begin().await?, tenant).await
+    }
+
+    /// 开始当前租户的只读事务，配置了只读副本时使用只读副本，只读副本连接失败时改用主库
+    async fn begin_read(&self) -> DatabaseResult<Transaction<'static, Postgres>> {
+        let tenant = self.tenant()?;
+        let tx = match self.read_primary {
+            true => self.pool.primary().begin().await?,
+            false => self.pool.begin_read().await?,
+        };
+        Self::enter_tenant(tx, tenant).await
+    }
+
+    /// 当前租户，没有指定时返回错误
+    fn tenant(&self) -> DatabaseResult<&TenantId> {
+        self.tenant.as_ref().ok_or(DatabaseError::MissingTenant)
+    }
+
+    /// 在事务内切换到指定租户
+    ///
+    /// 使用 `set_config(..., true)`（等同于 `SET LOCAL`）在事务内切换到 [`TENANT_ROLE`] 并设置 `app.tenant_id`，
+    /// 事务结束后自动还原，连接归还连接池后不会影响其他租户的请求。
+    /// 数据库账号通常是表的所有者甚至超级用户，不切换角色时可能绕过行级安全策略。
+    async fn enter_tenant(mut tx: Transaction<'static, Postgres>, tenant: &TenantId) -> DatabaseResult<Transaction<'static, Postgres>> {
+        sqlx::query("SELECT set_config('role', $1, true), set_config('app.tenant_id', $2, true)")
+            .bind(TENANT_ROLE)
+            .bind(tenant.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+        Ok(tx)
+    }
+
+    /// 查询指定项目信息（包括标签）
+    ///
+    /// 可以在连接池或者事务中执行
+    async fn fetch_project<'e>(executor: impl PgExecutor<'e>, id: i32) -> DatabaseResult<ProjectInfo> {
+        let project = sqlx::query_as!(
+            ProjectInfo,
+            r#"
+            SELECT p.id,
+                   p.project_name,
+                   p.comment,
+                   p.parent_id,
+                   p.created_at,
+                   p.updated_at,
+                   ARRAY(SELECT t.name
+                         FROM hm.project_tags pt
+                                  JOIN hm.tags t ON t.id = pt.tag_id
+                         WHERE pt.project_id = p.id
+                         ORDER BY t.name) AS "tags!"
+            FROM hm.projects p
+            WHERE p.id = $1
+            LIMIT 1
+            "#,
+            id
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(project)
+    }
+
+    /// 获取层级变更锁
+    ///
+    /// 移动、删除项目时使用事务级的咨询锁串行化层级变更，事务结束时自动释放。
+    /// 否则两个并发的移动操作（例如A移到B下、B移到A下）各自检查都通过，提交后会形成环。
+    async fn lock_hierarchy<'e>(executor: impl PgExecutor<'e>) -> DatabaseResult<()> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('hm.projects.parent_id'))")
+            .execute(executor)
+            .await?;
+        Ok(())
     }
 }
 
 #[async_trait::async_trait]
 impl ProjectRepositoryTrait for ProjectRepository {
+    fn with_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            tenant: Some(tenant.clone()),
+            ..self.clone()
+        }
+    }
+
+    fn read_from_primary(&self) -> Self {
+        Self {
+            read_primary: true,
+            ..self.clone()
+        }
+    }
+
     /// 根据查询参数搜索项目
     ///
     /// 根据查询参数搜索符合要求的项目列表，支持分页。
     ///
     /// # 参数
-    /// - `project_name`: 项目名称（模糊搜索）
+    /// - `filter`: 搜索条件，包括项目名称（模糊搜索）和标签
     /// - `page_size`: 页面大小
     /// - `offset`: 偏移量
     ///
@@ -42,20 +152,25 @@ impl ProjectRepositoryTrait for ProjectRepository {
     /// 2. 使用 `COUNT(*) OVER ()` 窗口函数获取总记录数
     /// 3. 使用 `COALESCE` 函数处理可选的搜索参数
     /// 4. 支持项目名称的模糊搜索（LIKE 操作）
+    /// 5. 标签过滤使用关联子查询，走 `hm.project_tags` 的主键索引：
+    ///    - 任意标签（any-of）：`EXISTS` 命中其中一个标签即可
+    ///    - 全部标签（all-of）：命中的标签个数等于条件中的标签个数（条件中的标签已去重）
+    /// 6. 只为当前页的项目聚合标签名称
     ///
     /// # 错误处理
     ///
     /// 如果数据库操作失败，会返回 [`crate::DatabaseError`]
-    async fn find_projects(&self, project_name: Option<String>, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult> {
-        debug!(
-            "🔍 搜索项目 - 名称: {:?}, 页面大小: {}, 偏移量: {}",
-            project_name, page_size, offset
-        );
+    async fn find_projects(&self, filter: ProjectFilter, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult> {
+        debug!("🔍 搜索项目 - 条件: {:?}, 页面大小: {}, 偏移量: {}", filter, page_size, offset);
+
+        let mut tx = self.begin_read().await?;
 
         // 准备搜索参数
         // 这里name需要clone一次，因为后面会使用两次name，导致重复消费
-        let name_param = project_name.clone().unwrap_or_default();
-        let like_param = project_name.map(|n| format!("%{n}%")).unwrap_or_default();
+        let name_param = filter.project_name.clone().unwrap_or_default();
+        let like_param = filter.project_name.map(|n| format!("%{n}%")).unwrap_or_default();
+        let any_tags = dedup_tags(filter.any_tags);
+        let all_tags = dedup_tags(filter.all_tags);
 
         // 具体sqlx的好处：
         // 1. 编译时SQL验证 - 确保SQL语法正确
@@ -65,27 +180,57 @@ impl ProjectRepositoryTrait for ProjectRepository {
         let rows = sqlx::query!(
             r#"
             WITH filtered_projects AS (
-                SELECT id,
-                       project_name,
-                       comment,
+                SELECT p.id,
+                       p.project_name,
+                       p.comment,
+                       p.parent_id,
+                       p.created_at,
+                       p.updated_at,
                        COUNT(*) OVER () as total_count
-                FROM hm.projects
-                WHERE (COALESCE($1, '') = '' OR project_name LIKE $2)
+                FROM hm.projects p
+                WHERE (COALESCE($1, '') = '' OR p.project_name LIKE $2)
+                  AND (cardinality($5::text[]) = 0 OR EXISTS (
+                      SELECT 1
+                      FROM hm.project_tags pt
+                               JOIN hm.tags t ON t.id = pt.tag_id
+                      WHERE pt.project_id = p.id
+                        AND t.name = ANY($5)
+                  ))
+                  AND (cardinality($6::text[]) = 0 OR (
+                      SELECT COUNT(*)
+                      FROM hm.project_tags pt
+                               JOIN hm.tags t ON t.id = pt.tag_id
+                      WHERE pt.project_id = p.id
+                        AND t.name = ANY($6)
+                  ) = cardinality($6::text[]))
+                ORDER BY p.id
                 LIMIT $3 OFFSET $4
             )
-            SELECT id,
-                   project_name,
-                   comment,
-                   total_count
-            FROM filtered_projects;
+            SELECT f.id,
+                   f.project_name,
+                   f.comment,
+                   f.parent_id,
+                   f.created_at,
+                   f.updated_at,
+                   f.total_count,
+                   ARRAY(SELECT t.name
+                         FROM hm.project_tags pt
+                                  JOIN hm.tags t ON t.id = pt.tag_id
+                         WHERE pt.project_id = f.id
+                         ORDER BY t.name) AS "tags!"
+            FROM filtered_projects f
+            ORDER BY f.id;
             "#,
             name_param,
             like_param,
             page_size,
             offset,
+            &any_tags,
+            &all_tags,
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         // 获取总数
         let total = rows.first().and_then(|r| r.total_count).unwrap_or(0) as u32;
@@ -97,6 +242,10 @@ impl ProjectRepositoryTrait for ProjectRepository {
                 id: r.id,
                 project_name: r.project_name,
                 comment: r.comment,
+                parent_id: r.parent_id,
+                created_at: r.created_at,
+                updated_at: r.updated_at,
+                tags: r.tags,
             })
             .collect();
 
@@ -105,10 +254,176 @@ impl ProjectRepositoryTrait for ProjectRepository {
         Ok(ProjectSearchResult { projects, total })
     }
 
+    /// 按相关度搜索项目
+    ///
+    /// 同时在项目名称和项目说明中进行模糊搜索，结果按相关度从高到低排序，支持分页。
+    ///
+    /// # 参数
+    /// - `keyword`: 搜索关键字
+    /// - `threshold`: 相似度阈值（0~1）
+    /// - `page_size`: 页面大小
+    /// - `offset`: 偏移量
+    ///
+    /// # SQL 查询说明
+    ///
+    /// 1. 使用 `set_config(..., true)` 在当前事务内设置 `pg_trgm` 的阈值（等同于 `SET LOCAL`），
+    ///    事务结束后自动还原，不会影响连接池中的其他查询
+    /// 2. 使用 `%`（整体相似）和 `<%`（单词相似）运算符过滤，二者都可以命中 `gin_trgm_ops` 索引
+    /// 3. 分别计算名称和说明的得分：`GREATEST(similarity, word_similarity)`，取较大者作为综合得分
+    /// 4. 使用 `COUNT(*) OVER ()` 窗口函数获取总记录数
+    ///
+    /// 高亮片段在查询完成后由 [`highlight_fragment`] 生成。
+    async fn search_projects_ranked(
+        &self,
+        keyword: String,
+        threshold: f32,
+        page_size: i64,
+        offset: i64,
+    ) -> DatabaseResult<ProjectRankedSearchResult> {
+        debug!(
+            "🔍 相关度搜索项目 - 关键字: {}, 阈值: {}, 页面大小: {}, 偏移量: {}",
+            keyword, threshold, page_size, offset
+        );
+
+        let mut tx = self.begin_read().await?;
+
+        sqlx::query!(
+            r#"
+            SELECT set_config('pg_trgm.similarity_threshold', $1, true) AS similarity_threshold,
+                   set_config('pg_trgm.word_similarity_threshold', $1, true) AS word_similarity_threshold;
+            "#,
+            threshold.to_string(),
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let rows = sqlx::query!(
+            r#"
+            WITH scored_projects AS (
+                SELECT id,
+                       project_name,
+                       comment,
+                       parent_id,
+                       created_at,
+                       updated_at,
+                       GREATEST(similarity(project_name, $1), word_similarity($1, project_name)) AS name_score,
+                       GREATEST(similarity(comment, $1), word_similarity($1, comment)) AS comment_score
+                FROM hm.projects
+                WHERE project_name % $1
+                   OR $1 <% project_name
+                   OR comment % $1
+                   OR $1 <% comment
+            ),
+            ranked_projects AS (
+                SELECT id,
+                       project_name,
+                       comment,
+                       parent_id,
+                       created_at,
+                       updated_at,
+                       GREATEST(name_score, comment_score) AS score,
+                       COUNT(*) OVER () AS total_count
+                FROM scored_projects
+            )
+            SELECT r.id,
+                   r.project_name,
+                   r.comment,
+                   r.parent_id,
+                   r.created_at,
+                   r.updated_at,
+                   r.score AS "score!",
+                   r.total_count AS "total_count!",
+                   ARRAY(SELECT t.name
+                         FROM hm.project_tags pt
+                                  JOIN hm.tags t ON t.id = pt.tag_id
+                         WHERE pt.project_id = r.id
+                         ORDER BY t.name) AS "tags!"
+            FROM ranked_projects r
+            ORDER BY r.score DESC, r.id
+            LIMIT $2 OFFSET $3;
+            "#,
+            keyword,
+            page_size,
+            offset,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let total = rows.first().map(|r| r.total_count).unwrap_or(0) as u32;
+
+        let hits: Vec<ProjectSearchHit> = rows
+            .into_iter()
+            .map(|r| ProjectSearchHit {
+                name_highlight: highlight_fragment(&r.project_name, &keyword, threshold),
+                comment_highlight: highlight_fragment(&r.comment, &keyword, threshold),
+                score: r.score,
+                project: ProjectInfo {
+                    id: r.id,
+                    project_name: r.project_name,
+                    comment: r.comment,
+                    parent_id: r.parent_id,
+                    created_at: r.created_at,
+                    updated_at: r.updated_at,
+                    tags: r.tags,
+                },
+            })
+            .collect();
+
+        debug!("✅ 相关度搜索完成 - 找到 {} 个项目，总计 {} 个", hits.len(), total);
+
+        Ok(ProjectRankedSearchResult { hits, total })
+    }
+
+    /// 项目名称输入提示
+    ///
+    /// 用于输入框的联想提示（typeahead），返回去重后的项目名称。
+    ///
+    /// # SQL 查询说明
+    ///
+    /// 1. 前缀匹配使用 `ILIKE`，用户输入中的 `%`、`_`、`\` 会被转义，避免被当作通配符
+    /// 2. 同时使用 `<%` 单词相似度运算符兼容拼写错误，阈值为 `pg_trgm.word_similarity_threshold` 的默认值
+    /// 3. 排序规则：前缀命中优先，其次按单词相似度从高到低，最后按名称排序
+    async fn suggest_project_names(&self, prefix: String, limit: i64) -> DatabaseResult<Vec<String>> {
+        debug!("🔍 项目名称提示 - 输入: {}, 数量: {}", prefix, limit);
+
+        let mut tx = self.begin_read().await?;
+
+        let prefix_pattern = format!("{}%", escape_like(&prefix));
+
+        let names = sqlx::query_scalar!(
+            r#"
+            SELECT project_name
+            FROM hm.projects
+            WHERE project_name ILIKE $2
+               OR $1 <% project_name
+            GROUP BY project_name
+            ORDER BY project_name ILIKE $2 DESC,
+                     word_similarity($1, project_name) DESC,
+                     project_name
+            LIMIT $3;
+            "#,
+            prefix,
+            prefix_pattern,
+            limit,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        debug!("✅ 项目名称提示完成 - 返回 {} 个名称", names.len());
+
+        Ok(names)
+    }
+
     /// 创建新项目
     ///
     /// 根据用户输入参数创建项目信息
     ///
+    /// 指定了父项目时，使用 `INSERT ... SELECT ... WHERE EXISTS` 检查父项目是否存在，
+    /// 父项目不存在时不会插入任何数据，返回 [`sqlx::Error::RowNotFound`]
+    ///
     /// # 参数
     /// - `project`: 项目创建信息
     ///
@@ -117,18 +432,24 @@ impl ProjectRepositoryTrait for ProjectRepository {
     async fn create_project(&self, project: ProjectCreate) -> DatabaseResult<ProjectInfo> {
         debug!("📝 创建项目: {:#?}", project);
 
+        let mut tx = self.begin().await?;
+
         let project_info = sqlx::query_as!(
             ProjectInfo,
             r#"
-            INSERT INTO hm.projects (project_name, comment, created_at, updated_at)
-            VALUES ($1, $2, now(), now())
-            RETURNING id, project_name, comment;
+            INSERT INTO hm.projects (project_name, comment, parent_id, created_at, updated_at)
+            SELECT $1, $2, $3, now(), now()
+            WHERE $3::integer IS NULL
+               OR EXISTS (SELECT 1 FROM hm.projects WHERE id = $3)
+            RETURNING id, project_name, comment, parent_id, created_at, updated_at, ARRAY[]::varchar[] AS "tags!";
             "#,
             project.project_name,
-            project.comment
+            project.comment,
+            project.parent_id,
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         debug!("✅ 项目创建成功: {:#?}", project_info);
         Ok(project_info)
@@ -146,21 +467,55 @@ impl ProjectRepositoryTrait for ProjectRepository {
     async fn get_project_by_id(&self, id: i32) -> DatabaseResult<ProjectInfo> {
         debug!("🔍 根据 ID 获取项目: {}", id);
 
-        let project = sqlx::query_as!(
+        let mut tx = self.begin_read().await?;
+
+        let project = Self::fetch_project(&mut *tx, id).await?;
+        tx.commit().await?;
+
+        debug!("✅ 项目获取成功: {:#?}", project);
+        Ok(project)
+    }
+
+    /// 根据多个 ID 批量获取项目信息
+    ///
+    /// 使用 `id = ANY($1)` 一次查询所有项目，避免逐个查询导致的N+1问题
+    ///
+    /// # 参数
+    /// - `ids`: 项目 ID 列表
+    ///
+    /// # 返回值
+    /// 返回按 ID 排序的项目列表，不存在的 ID 会被忽略
+    async fn get_projects_by_ids(&self, ids: &[i32]) -> DatabaseResult<Vec<ProjectInfo>> {
+        debug!("🔍 根据 ID 批量获取项目: {:?}", ids);
+
+        let mut tx = self.begin_read().await?;
+
+        let projects = sqlx::query_as!(
             ProjectInfo,
             r#"
-            SELECT id, project_name, comment
-            FROM hm.projects
-            WHERE id = $1
-            LIMIT 1
+            SELECT p.id,
+                   p.project_name,
+                   p.comment,
+                   p.parent_id,
+                   p.created_at,
+                   p.updated_at,
+                   ARRAY(SELECT t.name
+                         FROM hm.project_tags pt
+                                  JOIN hm.tags t ON t.id = pt.tag_id
+                         WHERE pt.project_id = p.id
+                         ORDER BY t.name) AS "tags!"
+            FROM hm.projects p
+            WHERE p.id = ANY($1)
+            ORDER BY p.id
             "#,
-            id
+            ids
         )
-        .fetch_one(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
+        tx.commit().await?;
 
-        debug!("✅ 项目获取成功: {:#?}", project);
-        Ok(project)
+        debug!("✅ 批量获取项目成功，共 {} 个", projects.len());
+        Ok(projects)
     }
 
     /// 更新项目信息
@@ -185,22 +540,35 @@ impl ProjectRepositoryTrait for ProjectRepository {
     async fn update_project(&self, id: i32, update: ProjectUpdate) -> DatabaseResult<ProjectInfo> {
         debug!("🔄 更新项目 {} 信息: {:#?}", id, update);
 
+        let mut tx = self.begin().await?;
+
         let project = sqlx::query_as!(
             ProjectInfo,
             r#"
-            UPDATE hm.projects
+            UPDATE hm.projects p
             SET project_name = coalesce($2, project_name),
                 comment = coalesce($3, comment),
                 updated_at = now()
-            WHERE id = $1
-            RETURNING id, project_name, comment;
+            WHERE p.id = $1
+            RETURNING p.id,
+                      p.project_name,
+                      p.comment,
+                      p.parent_id,
+                      p.created_at,
+                      p.updated_at,
+                      ARRAY(SELECT t.name
+                            FROM hm.project_tags pt
+                                     JOIN hm.tags t ON t.id = pt.tag_id
+                            WHERE pt.project_id = p.id
+                            ORDER BY t.name) AS "tags!";
             "#,
             id,
             update.project_name,
             update.comment,
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         debug!("✅ 项目更新成功: {:#?}", project);
         Ok(project)
@@ -208,7 +576,13 @@ impl ProjectRepositoryTrait for ProjectRepository {
 
     /// 删除项目
     ///
-    /// 删除指定的项目
+    /// 删除指定的项目，项目的标签关联会被级联删除。
+    /// `RETURNING` 中的子查询使用语句开始时的快照，因此仍然可以返回删除前的标签。
+    ///
+    /// 在同一个事务中完成：
+    /// 1. 获取层级变更锁（参考 [`Self::lock_hierarchy`]）
+    /// 2. 将子项目挂到被删除项目的父项目下，顶层项目的子项目变为顶层项目
+    /// 3. 删除项目
     ///
     /// # 参数
     /// - `id`: 项目 ID
@@ -218,19 +592,539 @@ impl ProjectRepositoryTrait for ProjectRepository {
     async fn delete_project(&self, id: i32) -> DatabaseResult<ProjectInfo> {
         debug!("🗑️ 删除项目: {}", id);
 
+        let mut tx = self.begin().await?;
+        Self::lock_hierarchy(&mut *tx).await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE hm.projects c
+            SET parent_id = p.parent_id,
+                updated_at = now()
+            FROM hm.projects p
+            WHERE p.id = $1
+              AND c.parent_id = p.id;
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+
         let project = sqlx::query_as!(
             ProjectInfo,
             r#"
-            DELETE FROM hm.projects
-            WHERE id = $1
-            RETURNING id, project_name, comment;
+            DELETE FROM hm.projects p
+            WHERE p.id = $1
+            RETURNING p.id,
+                      p.project_name,
+                      p.comment,
+                      p.parent_id,
+                      p.created_at,
+                      p.updated_at,
+                      ARRAY(SELECT t.name
+                            FROM hm.project_tags pt
+                                     JOIN hm.tags t ON t.id = pt.tag_id
+                            WHERE pt.project_id = p.id
+                            ORDER BY t.name) AS "tags!";
             "#,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         debug!("✅ 项目删除成功: {:#?}", project);
         Ok(project)
     }
+
+    /// 查询项目的所有祖先
+    ///
+    /// # SQL 查询说明
+    ///
+    /// 使用递归 CTE 从项目自身开始沿 `parent_id` 向上查找，`depth` 为距离项目自身的层数。
+    /// 结果包含项目自身（`depth = 0`），用于区分项目不存在和顶层项目，返回前移除。
+    async fn get_project_ancestors(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>> {
+        debug!("🔍 查询项目祖先: {}", id);
+
+        let mut tx = self.begin_read().await?;
+
+        let mut projects = sqlx::query_as!(
+            ProjectInfo,
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT id, parent_id, 0 AS depth
+                FROM hm.projects
+                WHERE id = $1
+                UNION ALL
+                SELECT p.id, p.parent_id, a.depth + 1
+                FROM hm.projects p
+                         JOIN ancestors a ON p.id = a.parent_id
+            )
+            SELECT p.id AS "id!",
+                   p.project_name AS "project_name!",
+                   p.comment AS "comment!",
+                   p.parent_id,
+                   p.created_at AS "created_at!",
+                   p.updated_at AS "updated_at!",
+                   ARRAY(SELECT t.name
+                         FROM hm.project_tags pt
+                                  JOIN hm.tags t ON t.id = pt.tag_id
+                         WHERE pt.project_id = p.id
+                         ORDER BY t.name) AS "tags!"
+            FROM ancestors a
+                     JOIN hm.projects p ON p.id = a.id
+            ORDER BY a.depth DESC
+            "#,
+            id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        // 最后一个元素是项目自身
+        if projects.pop().is_none() {
+            return Err(sqlx::Error::RowNotFound.into());
+        }
+
+        debug!("✅ 查询项目祖先完成，共 {} 个", projects.len());
+        Ok(projects)
+    }
+
+    /// 查询以项目为根的子树
+    ///
+    /// # SQL 查询说明
+    ///
+    /// 使用递归 CTE 从项目自身开始沿 `parent_id` 向下查找，子项目查询走 `idx_projects_parent` 索引。
+    /// 层级变更时会检查环（参考 [`Self::move_project`]），因此递归一定会结束。
+    async fn get_project_subtree(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>> {
+        debug!("🔍 查询项目子树: {}", id);
+
+        let mut tx = self.begin_read().await?;
+
+        let projects = sqlx::query_as!(
+            ProjectInfo,
+            r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id, 0 AS depth
+                FROM hm.projects
+                WHERE id = $1
+                UNION ALL
+                SELECT p.id, s.depth + 1
+                FROM hm.projects p
+                         JOIN subtree s ON p.parent_id = s.id
+            )
+            SELECT p.id AS "id!",
+                   p.project_name AS "project_name!",
+                   p.comment AS "comment!",
+                   p.parent_id,
+                   p.created_at AS "created_at!",
+                   p.updated_at AS "updated_at!",
+                   ARRAY(SELECT t.name
+                         FROM hm.project_tags pt
+                                  JOIN hm.tags t ON t.id = pt.tag_id
+                         WHERE pt.project_id = p.id
+                         ORDER BY t.name) AS "tags!"
+            FROM subtree s
+                     JOIN hm.projects p ON p.id = s.id
+            ORDER BY s.depth, p.id
+            "#,
+            id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        if projects.is_empty() {
+            return Err(sqlx::Error::RowNotFound.into());
+        }
+
+        debug!("✅ 查询项目子树完成，共 {} 个", projects.len());
+        Ok(projects)
+    }
+
+    /// 移动项目到新的父项目下
+    ///
+    /// 在同一个事务中完成：
+    /// 1. 获取层级变更锁，保证检查和更新之间层级不会被其他事务修改
+    /// 2. 从新的父项目开始向上查找祖先：父项目不存在时返回 [`sqlx::Error::RowNotFound`]，
+    ///    祖先中包含项目自身时（即新的父项目是项目自身或其子孙）返回 [`DatabaseError::InvalidHierarchy`]
+    /// 3. 更新 `parent_id`，项目不存在时返回 [`sqlx::Error::RowNotFound`]
+    async fn move_project(&self, id: i32, parent_id: Option<i32>) -> DatabaseResult<ProjectInfo> {
+        debug!("🌳 移动项目 {} 到 {:?} 下", id, parent_id);
+
+        let mut tx = self.begin().await?;
+        Self::lock_hierarchy(&mut *tx).await?;
+
+        if let Some(parent_id) = parent_id {
+            let check = sqlx::query!(
+                r#"
+                WITH RECURSIVE ancestors AS (
+                    SELECT id, parent_id
+                    FROM hm.projects
+                    WHERE id = $1
+                    UNION ALL
+                    SELECT p.id, p.parent_id
+                    FROM hm.projects p
+                             JOIN ancestors a ON p.id = a.parent_id
+                )
+                SELECT COUNT(*) AS "found!",
+                       COALESCE(bool_or(id = $2), false) AS "creates_cycle!"
+                FROM ancestors
+                "#,
+                parent_id,
+                id,
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if check.found == 0 {
+                return Err(sqlx::Error::RowNotFound.into());
+            }
+            if check.creates_cycle {
+                return Err(DatabaseError::InvalidHierarchy { id, parent_id });
+            }
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE hm.projects
+            SET parent_id = $2,
+                updated_at = now()
+            WHERE id = $1
+            RETURNING id;
+            "#,
+            id,
+            parent_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let project = Self::fetch_project(&mut *tx, id).await?;
+        tx.commit().await?;
+
+        debug!("✅ 项目移动成功: {:#?}", project);
+        Ok(project)
+    }
+
+    /// 查询所有标签
+    async fn find_tags(&self) -> DatabaseResult<Vec<TagInfo>> {
+        debug!("🔍 查询所有标签");
+
+        let mut tx = self.begin_read().await?;
+
+        let tags = sqlx::query_as!(
+            TagInfo,
+            r#"
+            SELECT id, name, comment, created_at, updated_at
+            FROM hm.tags
+            ORDER BY name
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        debug!("✅ 查询标签完成，共 {} 个", tags.len());
+        Ok(tags)
+    }
+
+    /// 创建新标签
+    ///
+    /// 标签名称有唯一约束，名称重复时返回数据库的唯一约束错误
+    async fn create_tag(&self, tag: TagCreate) -> DatabaseResult<TagInfo> {
+        debug!("📝 创建标签: {:#?}", tag);
+
+        let mut tx = self.begin().await?;
+
+        let tag = sqlx::query_as!(
+            TagInfo,
+            r#"
+            INSERT INTO hm.tags (name, comment, created_at, updated_at)
+            VALUES ($1, $2, now(), now())
+            RETURNING id, name, comment, created_at, updated_at;
+            "#,
+            tag.name,
+            tag.comment
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        debug!("✅ 标签创建成功: {:#?}", tag);
+        Ok(tag)
+    }
+
+    /// 根据 ID 获取标签信息
+    async fn get_tag_by_id(&self, id: i32) -> DatabaseResult<TagInfo> {
+        debug!("🔍 根据 ID 获取标签: {}", id);
+
+        let mut tx = self.begin_read().await?;
+
+        let tag = sqlx::query_as!(
+            TagInfo,
+            r#"
+            SELECT id, name, comment, created_at, updated_at
+            FROM hm.tags
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(tag)
+    }
+
+    /// 更新标签信息
+    ///
+    /// 与 [`Self::update_project`] 一样使用 `coalesce` 保留未填写的字段
+    async fn update_tag(&self, id: i32, update: TagUpdate) -> DatabaseResult<TagInfo> {
+        debug!("🔄 更新标签 {} 信息: {:#?}", id, update);
+
+        let mut tx = self.begin().await?;
+
+        let tag = sqlx::query_as!(
+            TagInfo,
+            r#"
+            UPDATE hm.tags
+            SET name = coalesce($2, name),
+                comment = coalesce($3, comment),
+                updated_at = now()
+            WHERE id = $1
+            RETURNING id, name, comment, created_at, updated_at;
+            "#,
+            id,
+            update.name,
+            update.comment,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        debug!("✅ 标签更新成功: {:#?}", tag);
+        Ok(tag)
+    }
+
+    /// 删除标签
+    ///
+    /// 项目与标签的关联会被级联删除
+    async fn delete_tag(&self, id: i32) -> DatabaseResult<TagInfo> {
+        debug!("🗑️ 删除标签: {}", id);
+
+        let mut tx = self.begin().await?;
+
+        let tag = sqlx::query_as!(
+            TagInfo,
+            r#"
+            DELETE FROM hm.tags
+            WHERE id = $1
+            RETURNING id, name, comment, created_at, updated_at;
+            "#,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        debug!("✅ 标签删除成功: {:#?}", tag);
+        Ok(tag)
+    }
+
+    /// 为项目添加标签
+    ///
+    /// 在同一个事务中完成：
+    /// 1. 检查标签是否存在，不存在时返回 [`sqlx::Error::RowNotFound`]
+    /// 2. 插入关联关系，已经存在时忽略（`ON CONFLICT DO NOTHING`）
+    /// 3. 查询最新的项目信息，项目不存在时返回 [`sqlx::Error::RowNotFound`]
+    async fn attach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo> {
+        debug!("🏷️ 为项目 {} 添加标签 {}", project_id, tag_id);
+
+        let mut tx = self.begin().await?;
+
+        sqlx::query!("SELECT id FROM hm.tags WHERE id = $1", tag_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO hm.project_tags (project_id, tag_id, created_at)
+            SELECT id, $2, now()
+            FROM hm.projects
+            WHERE id = $1
+            ON CONFLICT DO NOTHING;
+            "#,
+            project_id,
+            tag_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let project = Self::fetch_project(&mut *tx, project_id).await?;
+        tx.commit().await?;
+
+        debug!("✅ 标签添加成功: {:?}", project.tags);
+        Ok(project)
+    }
+
+    /// 从项目中移除标签
+    ///
+    /// 项目不存在时返回 [`sqlx::Error::RowNotFound`]
+    async fn detach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo> {
+        debug!("🏷️ 从项目 {} 移除标签 {}", project_id, tag_id);
+
+        let mut tx = self.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM hm.project_tags WHERE project_id = $1 AND tag_id = $2",
+            project_id,
+            tag_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let project = Self::fetch_project(&mut *tx, project_id).await?;
+        tx.commit().await?;
+
+        debug!("✅ 标签移除成功: {:?}", project.tags);
+        Ok(project)
+    }
+
+    /// 查询项目的所有附件
+    ///
+    /// 先检查项目是否存在，区分项目不存在和项目没有附件
+    async fn find_attachments(&self, project_id: i32) -> DatabaseResult<Vec<AttachmentInfo>> {
+        debug!("🔍 查询项目 {} 的附件", project_id);
+
+        let mut tx = self.begin_read().await?;
+
+        sqlx::query!("SELECT id FROM hm.projects WHERE id = $1", project_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let attachments = sqlx::query_as!(
+            AttachmentInfo,
+            r#"
+            SELECT id, project_id, file_name, content_type, size, sha256, storage_key, created_at
+            FROM hm.project_attachments
+            WHERE project_id = $1
+            ORDER BY id
+            "#,
+            project_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        debug!("✅ 查询附件完成，共 {} 个", attachments.len());
+        Ok(attachments)
+    }
+
+    /// 保存附件元数据
+    ///
+    /// 与 [`Self::create_project`] 一样使用 `INSERT ... SELECT`，项目不存在时不插入任何数据，返回 [`sqlx::Error::RowNotFound`]
+    async fn create_attachment(&self, attachment: AttachmentCreate) -> DatabaseResult<AttachmentInfo> {
+        debug!("📝 保存附件: {:#?}", attachment);
+
+        let mut tx = self.begin().await?;
+
+        let attachment = sqlx::query_as!(
+            AttachmentInfo,
+            r#"
+            INSERT INTO hm.project_attachments (project_id, file_name, content_type, size, sha256, storage_key, created_at)
+            SELECT id, $2, $3, $4, $5, $6, now()
+            FROM hm.projects
+            WHERE id = $1
+            RETURNING id, project_id, file_name, content_type, size, sha256, storage_key, created_at;
+            "#,
+            attachment.project_id,
+            attachment.file_name,
+            attachment.content_type,
+            attachment.size,
+            attachment.sha256,
+            attachment.storage_key,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        debug!("✅ 附件保存成功: {:#?}", attachment);
+        Ok(attachment)
+    }
+
+    /// 获取项目的附件信息
+    async fn get_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo> {
+        debug!("🔍 获取项目 {} 的附件 {}", project_id, id);
+
+        let mut tx = self.begin_read().await?;
+
+        let attachment = sqlx::query_as!(
+            AttachmentInfo,
+            r#"
+            SELECT id, project_id, file_name, content_type, size, sha256, storage_key, created_at
+            FROM hm.project_attachments
+            WHERE project_id = $1 AND id = $2
+            "#,
+            project_id,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(attachment)
+    }
+
+    /// 删除附件元数据
+    async fn delete_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo> {
+        debug!("🗑️ 删除项目 {} 的附件 {}", project_id, id);
+
+        let mut tx = self.begin().await?;
+
+        let attachment = sqlx::query_as!(
+            AttachmentInfo,
+            r#"
+            DELETE FROM hm.project_attachments
+            WHERE project_id = $1 AND id = $2
+            RETURNING id, project_id, file_name, content_type, size, sha256, storage_key, created_at;
+            "#,
+            project_id,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        debug!("✅ 附件删除成功: {:#?}", attachment);
+        Ok(attachment)
+    }
+
+    async fn ping(&self) -> DatabaseResult<()> {
+        sqlx::query!("SELECT 1 AS ping").fetch_one(self.pool.primary()).await?;
+        Ok(())
+    }
+
+    async fn ping_replica(&self) -> Option<DatabaseResult<()>> {
+        let replica = self.pool.replica()?;
+        let result = sqlx::query!("SELECT 1 AS ping").fetch_one(replica).await;
+        Some(result.map(|_| ()).map_err(Into::into))
+    }
+}
+
+/// 标签条件去重，保证按个数匹配全部标签时结果正确
+fn dedup_tags(mut tags: Vec<String>) -> Vec<String> {
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// 转义 `LIKE`/`ILIKE` 中的通配符，使用户输入按字面匹配
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
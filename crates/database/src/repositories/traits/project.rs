@@ -3,28 +3,79 @@
 //! 定义项目数据库操作的抽象接口
 
 use crate::DatabaseResult;
-use crate::models::project::{ProjectCreate, ProjectInfo, ProjectSearchResult, ProjectUpdate};
+use crate::models::attachment::{AttachmentCreate, AttachmentInfo};
+use crate::models::project::{ProjectCreate, ProjectFilter, ProjectInfo, ProjectRankedSearchResult, ProjectSearchResult, ProjectUpdate};
+use crate::models::tag::{TagCreate, TagInfo, TagUpdate};
+use shared_lib::models::tenant::TenantId;
 
 /// 项目仓库trait定义
 ///
 /// 定义了项目相关的数据库操作接口，支持：
 /// - 项目搜索（分页）
+/// - 项目相关度搜索（分页）
+/// - 项目名称输入提示
 /// - 项目创建
 /// - 项目查询
 /// - 项目更新
 /// - 项目删除
+/// - 项目层级：查询祖先、子树，移动项目
+/// - 标签管理，以及为项目添加、移除标签
+/// - 项目附件元数据管理
+/// - 连通性检查
+///
+/// 除连通性检查外，所有操作都只能访问当前租户的数据，需要先调用 [`Self::with_tenant`] 指定租户，
+/// 否则返回 [`crate::DatabaseError::MissingTenant`]
 #[async_trait::async_trait]
 pub trait ProjectRepositoryTrait: Send + Sync + Clone + 'static {
+    /// 返回访问指定租户数据的仓库实例，与当前实例共享连接池等资源
+    fn with_tenant(&self, tenant: &TenantId) -> Self;
+
+    /// 返回只读查询也使用主库的仓库实例
+    ///
+    /// 配置了只读副本时，只读查询默认发送到只读副本，副本有复制延迟，
+    /// 需要读到刚刚写入的数据（例如写操作之前的检查）时使用。没有只读副本的实现直接返回当前实例
+    fn read_from_primary(&self) -> Self {
+        self.clone()
+    }
+
     /// 根据查询参数搜索项目
     ///
     /// # 参数
-    /// - `project_name`: 项目名称（模糊搜索）
+    /// - `filter`: 搜索条件，包括项目名称（模糊搜索）和标签
     /// - `page_size`: 页面大小
     /// - `offset`: 偏移量
     ///
     /// # 返回值
     /// 返回包含项目列表和总数的结果 [`ProjectSearchResult`]
-    async fn find_projects(&self, project_name: Option<String>, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult>;
+    async fn find_projects(&self, filter: ProjectFilter, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult>;
+
+    /// 按相关度搜索项目
+    ///
+    /// # 参数
+    /// - `keyword`: 搜索关键字，同时匹配项目名称和项目说明
+    /// - `threshold`: 相似度阈值（0~1），低于该值的记录不会返回
+    /// - `page_size`: 页面大小
+    /// - `offset`: 偏移量
+    ///
+    /// # 返回值
+    /// 返回按相关度从高到低排序的命中列表和总数 [`ProjectRankedSearchResult`]
+    async fn search_projects_ranked(
+        &self,
+        keyword: String,
+        threshold: f32,
+        page_size: i64,
+        offset: i64,
+    ) -> DatabaseResult<ProjectRankedSearchResult>;
+
+    /// 项目名称输入提示
+    ///
+    /// # 参数
+    /// - `prefix`: 用户已经输入的内容
+    /// - `limit`: 最多返回的名称个数
+    ///
+    /// # 返回值
+    /// 返回去重后的项目名称，前缀命中的排在前面
+    async fn suggest_project_names(&self, prefix: String, limit: i64) -> DatabaseResult<Vec<String>>;
 
     /// 创建新项目
     ///
@@ -32,7 +83,7 @@ pub trait ProjectRepositoryTrait: Send + Sync + Clone + 'static {
     /// - `project`: 项目创建信息
     ///
     /// # 返回值
-    /// 返回创建的项目信息
+    /// 返回创建的项目信息，指定的父项目不存在时返回 [`sqlx::Error::RowNotFound`]
     async fn create_project(&self, project: ProjectCreate) -> DatabaseResult<ProjectInfo>;
 
     /// 根据 ID 获取项目信息
@@ -44,6 +95,15 @@ pub trait ProjectRepositoryTrait: Send + Sync + Clone + 'static {
     /// 返回项目信息
     async fn get_project_by_id(&self, id: i32) -> DatabaseResult<ProjectInfo>;
 
+    /// 根据多个 ID 批量获取项目信息
+    ///
+    /// # 参数
+    /// - `ids`: 项目 ID 列表
+    ///
+    /// # 返回值
+    /// 返回按 ID 排序的项目列表，不存在的 ID 会被忽略
+    async fn get_projects_by_ids(&self, ids: &[i32]) -> DatabaseResult<Vec<ProjectInfo>>;
+
     /// 更新项目信息
     ///
     /// # 参数
@@ -56,10 +116,153 @@ pub trait ProjectRepositoryTrait: Send + Sync + Clone + 'static {
 
     /// 删除项目
     ///
+    /// 被删除项目的子项目会挂到被删除项目的父项目下（顶层项目的子项目变为顶层项目），不会级联删除
+    ///
     /// # 参数
     /// - `id`: 项目 ID
     ///
     /// # 返回值
     /// 返回被删除的项目信息
     async fn delete_project(&self, id: i32) -> DatabaseResult<ProjectInfo>;
+
+    /// 查询项目的所有祖先
+    ///
+    /// # 参数
+    /// - `id`: 项目 ID
+    ///
+    /// # 返回值
+    /// 返回从顶层项目到直接父项目的列表，不包含项目自身；顶层项目返回空列表
+    async fn get_project_ancestors(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>>;
+
+    /// 查询以项目为根的子树
+    ///
+    /// # 参数
+    /// - `id`: 项目 ID
+    ///
+    /// # 返回值
+    /// 返回包含项目自身在内的所有子孙项目，按层级深度、ID排序，第一个元素为项目自身
+    async fn get_project_subtree(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>>;
+
+    /// 移动项目到新的父项目下
+    ///
+    /// # 参数
+    /// - `id`: 项目 ID
+    /// - `parent_id`: 新的父项目 ID，为 `None` 时移动为顶层项目
+    ///
+    /// # 返回值
+    /// 返回移动后的项目信息。新的父项目是项目自身或其子孙时返回 [`crate::DatabaseError::InvalidHierarchy`]
+    async fn move_project(&self, id: i32, parent_id: Option<i32>) -> DatabaseResult<ProjectInfo>;
+
+    /// 查询所有标签
+    ///
+    /// # 返回值
+    /// 返回按名称排序的标签列表
+    async fn find_tags(&self) -> DatabaseResult<Vec<TagInfo>>;
+
+    /// 创建新标签
+    ///
+    /// # 参数
+    /// - `tag`: 标签创建信息
+    ///
+    /// # 返回值
+    /// 返回创建的标签信息，名称重复时返回唯一约束错误
+    async fn create_tag(&self, tag: TagCreate) -> DatabaseResult<TagInfo>;
+
+    /// 根据 ID 获取标签信息
+    ///
+    /// # 参数
+    /// - `id`: 标签 ID
+    ///
+    /// # 返回值
+    /// 返回标签信息
+    async fn get_tag_by_id(&self, id: i32) -> DatabaseResult<TagInfo>;
+
+    /// 更新标签信息
+    ///
+    /// # 参数
+    /// - `id`: 标签 ID
+    /// - `update`: 更新信息
+    ///
+    /// # 返回值
+    /// 返回更新后的标签信息
+    async fn update_tag(&self, id: i32, update: TagUpdate) -> DatabaseResult<TagInfo>;
+
+    /// 删除标签，同时从所有项目中移除该标签
+    ///
+    /// # 参数
+    /// - `id`: 标签 ID
+    ///
+    /// # 返回值
+    /// 返回被删除的标签信息
+    async fn delete_tag(&self, id: i32) -> DatabaseResult<TagInfo>;
+
+    /// 为项目添加标签，标签已经存在时不做任何操作
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `tag_id`: 标签 ID
+    ///
+    /// # 返回值
+    /// 返回添加标签后的项目信息
+    async fn attach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo>;
+
+    /// 从项目中移除标签，项目没有该标签时不做任何操作
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `tag_id`: 标签 ID
+    ///
+    /// # 返回值
+    /// 返回移除标签后的项目信息
+    async fn detach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo>;
+
+    /// 查询项目的所有附件
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    ///
+    /// # 返回值
+    /// 返回按 ID 排序的附件列表，项目不存在时返回 [`sqlx::Error::RowNotFound`]
+    async fn find_attachments(&self, project_id: i32) -> DatabaseResult<Vec<AttachmentInfo>>;
+
+    /// 保存附件元数据，附件内容需要提前写入附件存储
+    ///
+    /// # 参数
+    /// - `attachment`: 附件创建信息
+    ///
+    /// # 返回值
+    /// 返回创建的附件信息，项目不存在时返回 [`sqlx::Error::RowNotFound`]
+    async fn create_attachment(&self, attachment: AttachmentCreate) -> DatabaseResult<AttachmentInfo>;
+
+    /// 获取项目的附件信息
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `id`: 附件 ID
+    ///
+    /// # 返回值
+    /// 返回附件信息，附件不存在或者不属于该项目时返回 [`sqlx::Error::RowNotFound`]
+    async fn get_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo>;
+
+    /// 删除附件元数据，附件存储中的内容由调用方删除
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `id`: 附件 ID
+    ///
+    /// # 返回值
+    /// 返回被删除的附件信息
+    async fn delete_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo>;
+
+    /// 检查数据库（主库）是否可用
+    ///
+    /// 执行一条最简单的查询，用于就绪探针等场景
+    async fn ping(&self) -> DatabaseResult<()>;
+
+    /// 检查只读副本是否可用，没有配置只读副本时返回 `None`
+    ///
+    /// 只读副本是可选的，不可用时不影响就绪探针，只在探针结果中报告
+    async fn ping_replica(&self) -> Option<DatabaseResult<()>> {
+        None
+    }
 }
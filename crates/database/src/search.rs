@@ -0,0 +1,269 @@
+//! 模糊搜索辅助工具
+//!
+//! 配合 `pg_trgm` 扩展使用，提供：
+//! - 默认的相似度阈值
+//! - 与 `pg_trgm` 算法一致的 trigram 相似度和单词相似度计算
+//! - 命中片段高亮
+
+use std::collections::HashSet;
+
+/// 默认相似度阈值，可以通过 `search.similarity_threshold` 修改
+pub use shared_lib::models::config::DEFAULT_SIMILARITY_THRESHOLD;
+
+/// 高亮片段的开始标签
+pub const HIGHLIGHT_PRE_TAG: &str = "<em>";
+
+/// 高亮片段的结束标签
+pub const HIGHLIGHT_POST_TAG: &str = "</em>";
+
+/// 高亮片段在命中位置前后保留的字符数
+const FRAGMENT_CONTEXT_CHARS: usize = 20;
+
+/// 计算两个字符串的 trigram 相似度
+///
+/// 算法与 `pg_trgm` 的 `similarity()` 保持一致：
+/// 1. 转换为小写，按非字母数字字符切分单词
+/// 2. 每个单词前面补两个空格、后面补一个空格，再拆分为三字符组
+/// 3. 相似度 = 交集大小 / 并集大小
+pub fn trigram_similarity(a: &str, b: &str) -> f32 {
+    let left = trigrams(a);
+    let right = trigrams(b);
+
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+
+    let shared = left.intersection(&right).count();
+    let total = left.union(&right).count();
+
+    shared as f32 / total as f32
+}
+
+/// 计算 `needle` 与 `haystack` 的单词相似度
+///
+/// 与 `pg_trgm` 的 `word_similarity()` 定义一致：`needle` 的 trigram 集合与 `haystack`
+/// 有序 trigram 序列中任意连续片段的最大相似度，用于 `<%` 运算符。
+pub fn word_similarity(needle: &str, haystack: &str) -> f32 {
+    let needle = trigrams(needle);
+    let ordered = ordered_trigrams(haystack);
+
+    if needle.is_empty() || ordered.is_empty() {
+        return 0.0;
+    }
+
+    let mut best = 0.0f32;
+    for start in 0..ordered.len() {
+        // 以不相同的 trigram 开头的片段，去掉开头后相似度只会更高
+        if !needle.contains(&ordered[start]) {
+            continue;
+        }
+
+        let mut extent = HashSet::new();
+        let mut shared = 0;
+        for trigram in &ordered[start..] {
+            if extent.insert(*trigram) && needle.contains(trigram) {
+                shared += 1;
+            }
+            let total = needle.len() + extent.len() - shared;
+            best = best.max(shared as f32 / total as f32);
+        }
+    }
+
+    best
+}
+
+/// 生成字符串的 trigram 集合
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    ordered_trigrams(text).into_iter().collect()
+}
+
+/// 按出现顺序生成字符串的 trigram 序列
+fn ordered_trigrams(text: &str) -> Vec<[char; 3]> {
+    let mut ordered = Vec::new();
+
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        let padded: Vec<char> = "  "
+            .chars()
+            .chain(word.chars().flat_map(char::to_lowercase))
+            .chain(" ".chars())
+            .collect();
+        for window in padded.windows(3) {
+            ordered.push([window[0], window[1], window[2]]);
+        }
+    }
+
+    ordered
+}
+
+/// 生成命中片段的高亮文本
+///
+/// 处理规则：
+/// - 关键字按空白拆分为多个词，忽略大小写逐个查找字面命中
+/// - 没有字面命中时，退化为把 trigram 相似度不低于 `threshold` 的单词作为命中（拼写错误等场景）
+/// - 以第一个命中为中心，前后各保留 [`FRAGMENT_CONTEXT_CHARS`] 个字符，被截断的部分使用 `…` 表示
+/// - 原文会进行HTML转义，命中部分使用 [`HIGHLIGHT_PRE_TAG`] / [`HIGHLIGHT_POST_TAG`] 包裹
+///
+/// 完全没有命中时返回 `None`
+pub fn highlight_fragment(text: &str, keyword: &str, threshold: f32) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let lowered: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+
+    let mut ranges = literal_matches(&lowered, keyword);
+    if ranges.is_empty() {
+        ranges = fuzzy_word_matches(&chars, keyword, threshold);
+    }
+    if ranges.is_empty() {
+        return None;
+    }
+
+    ranges.sort_unstable();
+    let ranges = merge_ranges(ranges);
+
+    let (first_start, first_end) = ranges[0];
+    let window_start = first_start.saturating_sub(FRAGMENT_CONTEXT_CHARS);
+    let window_end = (first_end + FRAGMENT_CONTEXT_CHARS).min(chars.len());
+
+    let mut fragment = String::new();
+    if window_start > 0 {
+        fragment.push('…');
+    }
+
+    let mut cursor = window_start;
+    for &(start, end) in ranges.iter().filter(|(start, end)| *start >= window_start && *end <= window_end) {
+        push_escaped(&mut fragment, &chars[cursor..start]);
+        fragment.push_str(HIGHLIGHT_PRE_TAG);
+        push_escaped(&mut fragment, &chars[start..end]);
+        fragment.push_str(HIGHLIGHT_POST_TAG);
+        cursor = end;
+    }
+    push_escaped(&mut fragment, &chars[cursor..window_end]);
+
+    if window_end < chars.len() {
+        fragment.push('…');
+    }
+
+    Some(fragment)
+}
+
+/// 查找关键字中每个词在原文中的字面命中位置（按字符下标）
+fn literal_matches(lowered: &[char], keyword: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+
+    for term in keyword.split_whitespace() {
+        let term: Vec<char> = term.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect();
+        if term.len() > lowered.len() {
+            continue;
+        }
+
+        let mut index = 0;
+        while index + term.len() <= lowered.len() {
+            if lowered[index..index + term.len()] == term[..] {
+                ranges.push((index, index + term.len()));
+                index += term.len();
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    ranges
+}
+
+/// 查找与关键字中任意词 trigram 相似度不低于阈值的单词位置
+fn fuzzy_word_matches(chars: &[char], keyword: &str, threshold: f32) -> Vec<(usize, usize)> {
+    let terms: Vec<&str> = keyword.split_whitespace().collect();
+    let mut ranges = Vec::new();
+
+    let mut index = 0;
+    while index < chars.len() {
+        if !chars[index].is_alphanumeric() {
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+        while index < chars.len() && chars[index].is_alphanumeric() {
+            index += 1;
+        }
+
+        let word: String = chars[start..index].iter().collect();
+        if terms.iter().any(|term| trigram_similarity(&word, term) >= threshold) {
+            ranges.push((start, index));
+        }
+    }
+
+    ranges
+}
+
+/// 合并重叠或相邻的命中区间（输入需要已排序）
+fn merge_ranges(ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+/// 追加HTML转义后的文本，避免前端直接渲染高亮片段时产生XSS
+fn push_escaped(out: &mut String, chars: &[char]) {
+    for &c in chars {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigram_similarity_matches_pg_trgm() {
+        // SELECT similarity('word', 'two words') => 0.36363637
+        assert!((trigram_similarity("word", "two words") - 0.363_636_37).abs() < 1e-6);
+        assert_eq!(trigram_similarity("same", "SAME"), 1.0);
+        assert_eq!(trigram_similarity("", "anything"), 0.0);
+    }
+
+    #[test]
+    fn test_word_similarity_matches_pg_trgm() {
+        // SELECT word_similarity('word', 'two words') => 0.8
+        assert!((word_similarity("word", "two words") - 0.8).abs() < 1e-6);
+        assert_eq!(word_similarity("pay", "new pay gateway"), 1.0);
+        assert_eq!(word_similarity("anything", ""), 0.0);
+    }
+
+    #[test]
+    fn test_highlight_literal_match() {
+        let fragment = highlight_fragment("Payments Gateway", "payment", DEFAULT_SIMILARITY_THRESHOLD);
+        assert_eq!(fragment.as_deref(), Some("<em>Payment</em>s Gateway"));
+    }
+
+    #[test]
+    fn test_highlight_truncates_and_escapes() {
+        let text = format!("{}<b>payments</b>{}", "a".repeat(30), "z".repeat(30));
+        let fragment = highlight_fragment(&text, "payments", DEFAULT_SIMILARITY_THRESHOLD).unwrap();
+
+        assert!(fragment.starts_with('…'));
+        assert!(fragment.ends_with('…'));
+        assert!(fragment.contains("&lt;b&gt;<em>payments</em>&lt;/b&gt;"));
+    }
+
+    #[test]
+    fn test_highlight_fuzzy_fallback() {
+        let fragment = highlight_fragment("new payments gateway", "paymnts", DEFAULT_SIMILARITY_THRESHOLD);
+        assert_eq!(fragment.as_deref(), Some("new <em>payments</em> gateway"));
+
+        assert_eq!(highlight_fragment("unrelated", "payments", DEFAULT_SIMILARITY_THRESHOLD), None);
+    }
+}
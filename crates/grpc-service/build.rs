@@ -0,0 +1,27 @@
+//! 编译 `proto/` 目录下的协议文件
+//!
+//! 同时生成文件描述符集合，供gRPC反射服务使用
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+
+    let mut config = prost_build::Config::new();
+    config.protoc_executable(protoc_bin_vendored::protoc_bin_path()?);
+
+    let well_known_include = protoc_bin_vendored::include_path()?;
+
+    tonic_prost_build::configure()
+        .build_client(true)
+        .build_server(true)
+        .file_descriptor_set_path(out_dir.join("project_descriptor.bin"))
+        .compile_with_config(
+            config,
+            &[PathBuf::from("proto/project.proto")],
+            &[PathBuf::from("proto"), well_known_include],
+        )?;
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! 错误转换
+//!
+//! 将仓库层和参数校验错误转换为gRPC状态码，与REST接口的http状态码含义保持一致：
+//!
+//! | 错误 | gRPC状态码 | http状态码 |
+//! | --- | --- | --- |
+//! | 参数校验失败 | `INVALID_ARGUMENT` | 400 |
+//! | 没有携带访问令牌或者令牌无效 | `UNAUTHENTICATED` | 401 |
+//! | 记录不存在 | `NOT_FOUND` | 404 |
+//! | 唯一约束冲突 | `ALREADY_EXISTS` | 409 |
+//! | 项目层级错误（移动后形成环） | `FAILED_PRECONDITION` | 409 |
+//! | 连接池超时、连接失败 | `UNAVAILABLE` | 500 |
+//! | 其他数据库错误 | `INTERNAL` | 500 |
+//!
+//! 错误信息与REST接口使用同一份文案（[`web_service::i18n`]），gRPC请求不协商语言，固定使用默认语言。
+//! `UNAVAILABLE`、`INTERNAL` 只返回通用信息，原始错误（SQL错误、约束名称、连接信息等）只记录到日志中。
+
+use database::DatabaseError;
+use shared_lib::models::locale::Locale;
+use tonic::{Code, Status};
+use validator::ValidationErrors;
+use web_service::models::err::AppError;
+
+/// 将仓库层错误转换为gRPC状态码
+pub fn database_status(err: DatabaseError) -> Status {
+    let code = match &err {
+        DatabaseError::SqlxError(sqlx::Error::RowNotFound) => Code::NotFound,
+        DatabaseError::SqlxError(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => Code::AlreadyExists,
+        DatabaseError::SqlxError(sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_)) => Code::Unavailable,
+        DatabaseError::ConnectionError(_) => Code::Unavailable,
+        DatabaseError::InvalidHierarchy { .. } => Code::FailedPrecondition,
+        DatabaseError::SqlxError(_) | DatabaseError::MigrationError(_) | DatabaseError::MissingTenant => Code::Internal,
+    };
+
+    // 与REST接口相同：5xx错误记录原始错误，只返回通用信息
+    let err = AppError::RepositoryError(err);
+    err.log();
+    Status::new(code, err.message_in(Locale::default()))
+}
+
+/// 将参数校验错误转换为gRPC状态码
+pub fn validation_status(err: ValidationErrors) -> Status {
+    Status::invalid_argument(format!("Validate failed: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_status() {
+        assert_eq!(database_status(sqlx::Error::RowNotFound.into()).code(), Code::NotFound);
+        assert_eq!(database_status(sqlx::Error::PoolTimedOut.into()).code(), Code::Unavailable);
+        assert_eq!(database_status(DatabaseError::connection("refused")).code(), Code::Unavailable);
+        assert_eq!(
+            database_status(sqlx::Error::ColumnNotFound("id".into()).into()).code(),
+            Code::Internal
+        );
+    }
+
+    #[test]
+    fn test_database_status_hides_raw_errors() {
+        let status = database_status(sqlx::Error::ColumnNotFound("secret_column".into()).into());
+        assert_eq!(status.code(), Code::Internal);
+        assert!(!status.message().contains("secret_column"), "{}", status.message());
+        assert_eq!(status.message(), "Repository error");
+
+        let status = database_status(DatabaseError::connection("password authentication failed for user app"));
+        assert_eq!(status.code(), Code::Unavailable);
+        assert!(!status.message().contains("password"), "{}", status.message());
+
+        // 4xx错误返回具体信息
+        assert_eq!(database_status(sqlx::Error::RowNotFound.into()).message(), "Record not found");
+        let status = database_status(DatabaseError::InvalidHierarchy { id: 1, parent_id: 2 });
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert!(status.message().contains("descendant 2"), "{}", status.message());
+    }
+}
@@ -0,0 +1,62 @@
+//! gRPC服务模块
+//!
+//! 基于 [`tonic`] 实现，供内部服务调用。
+//! 与Web服务共用同一个 [`ProjectServiceTrait`](web_service::services::ProjectServiceTrait)，只负责协议的转换。
+//!
+//! - `rust_backend.project.v1.ProjectService`: 项目服务，参考 `proto/project.proto`
+//! - `grpc.reflection.v1.ServerReflection`: 反射服务，`grpcurl` 等工具不需要proto文件即可调用
+
+use color_eyre::Result;
+use database::DatabasePool;
+use shared_lib::models::config::AppConfig;
+use shared_lib::shutdown::wait_for_shutdown;
+use std::sync::Arc;
+use tokio::sync::watch::Receiver;
+use tonic::transport::Server;
+use tracing::info;
+use web_service::services::ProjectService;
+
+pub mod error;
+pub mod project;
+
+pub use project::ProjectGrpcService;
+
+/// 由 `proto/project.proto` 生成的代码
+pub mod pb {
+    tonic::include_proto!("rust_backend.project.v1");
+
+    /// 文件描述符集合，供反射服务使用
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("project_descriptor");
+}
+
+/// 启动 gRPC 服务
+///
+/// 收到关闭信号后停止接收新请求，等待处理中的请求完成后退出
+///
+/// 监听地址参考 [`shared_lib::models::config::GrpcConfig`]
+pub async fn start_grpc_service(app_config: Arc<AppConfig>, pool: DatabasePool, mut shutdown_rx: Receiver<bool>) -> Result<()> {
+    let project_repository = database::ProjectRepository::new(pool);
+    // 删除项目时需要同时删除附件内容，与Web服务使用相同的附件存储
+    let storage = storage::from_config(&app_config.storage)?;
+    let project_service = Arc::new(ProjectService::new(project_repository, storage));
+
+    let reflection_service = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(pb::FILE_DESCRIPTOR_SET)
+        .build_v1()?;
+
+    let listen_addr = app_config.grpc.listen_addr;
+    info!("🚀 启动 gRPC 在 {}", listen_addr);
+
+    Server::builder()
+        .add_service(reflection_service)
+        .add_service(pb::project_service_server::ProjectServiceServer::new(ProjectGrpcService::new(
+            project_service,
+            Arc::new(app_config.auth.clone()),
+        )))
+        .serve_with_shutdown(listen_addr, async move { wait_for_shutdown(&mut shutdown_rx).await })
+        .await?;
+
+    info!("🛑 gRPC 已关闭");
+
+    Ok(())
+}
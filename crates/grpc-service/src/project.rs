@@ -0,0 +1,254 @@
+//! 项目服务的gRPC实现
+//!
+//! 业务逻辑与Web服务共用同一个 [`ProjectServiceTrait`]，这里只负责参数和返回值的转换。
+//!
+//! 与REST接口一样，调用方需要在metadata中携带 `authorization: Bearer <token>`，
+//! 服务根据令牌确定租户，所有操作只能访问该租户的数据。
+
+use crate::error::{database_status, validation_status};
+use crate::pb;
+use chrono::NaiveDateTime;
+use prost_types::Timestamp;
+use shared_lib::models::config::AuthConfig;
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::debug;
+use validator::Validate;
+use web_service::models::common::PageQuery;
+use web_service::models::projects::ProjectSearch;
+use web_service::services::ProjectServiceTrait;
+
+/// 项目服务的gRPC实现
+#[derive(Debug)]
+pub struct ProjectGrpcService<PS: ProjectServiceTrait> {
+    project_service: Arc<PS>,
+
+    /// 访问令牌与租户的映射
+    auth: Arc<AuthConfig>,
+}
+
+impl<PS: ProjectServiceTrait> ProjectGrpcService<PS> {
+    pub fn new(project_service: Arc<PS>, auth: Arc<AuthConfig>) -> Self {
+        Self { project_service, auth }
+    }
+
+    /// 根据metadata中的 `authorization` 确定租户，返回处理该租户请求的服务实例
+    fn tenant_service<T>(&self, request: &Request<T>) -> Result<PS, Status> {
+        let authorization = match request.metadata().get("authorization") {
+            Some(value) => Some(value.to_str().map_err(|_| unauthenticated())?),
+            None => None,
+        };
+        let tenant = self.auth.resolve_tenant(authorization).ok_or_else(unauthenticated)?;
+
+        Ok(self.project_service.with_tenant(&tenant))
+    }
+}
+
+fn unauthenticated() -> Status {
+    Status::unauthenticated("Missing or invalid access token")
+}
+
+/// 从数据库层的 ProjectInfo 转换为 gRPC 的 Project
+impl From<database::models::ProjectInfo> for pb::Project {
+    fn from(project: database::models::ProjectInfo) -> Self {
+        Self {
+            id: project.id,
+            project_name: project.project_name,
+            comment: project.comment,
+            created_at: Some(to_timestamp(project.created_at)),
+            updated_at: Some(to_timestamp(project.updated_at)),
+            tags: project.tags,
+            parent_id: project.parent_id,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl<PS: ProjectServiceTrait> pb::project_service_server::ProjectService for ProjectGrpcService<PS> {
+    async fn search(&self, request: Request<pb::SearchProjectsRequest>) -> Result<Response<pb::SearchProjectsResponse>, Status> {
+        let project_service = self.tenant_service(&request)?;
+        let request = request.into_inner();
+        debug!("🔍 搜索项目(gRPC) {:?}", request);
+
+        // 复用REST接口的参数校验规则
+        let search = ProjectSearch {
+            project_name: request.project_name,
+            any_tags: request.any_tags,
+            all_tags: request.all_tags,
+            page_query: PageQuery {
+                page_index: request.page_index,
+                page_size: request.page_size,
+            },
+        };
+        search.validate().map_err(validation_status)?;
+
+        let PageQuery { page_index, page_size } = search.page_query;
+        let offset = (page_index.saturating_sub(1)) * page_size;
+        let result = project_service
+            .find_projects(search.filter(), page_size as i64, offset as i64)
+            .await
+            .map_err(database_status)?;
+
+        Ok(Response::new(pb::SearchProjectsResponse {
+            projects: result.projects.into_iter().map(Into::into).collect(),
+            total: result.total,
+            page_index,
+            page_size,
+        }))
+    }
+
+    async fn get(&self, request: Request<pb::GetProjectRequest>) -> Result<Response<pb::Project>, Status> {
+        let project_service = self.tenant_service(&request)?;
+        let id = request.into_inner().id;
+        debug!("Getting project(gRPC) {}", id);
+
+        let project = project_service.get_project_by_id(id).await.map_err(database_status)?;
+
+        Ok(Response::new(project.into()))
+    }
+
+    async fn create(&self, request: Request<pb::CreateProjectRequest>) -> Result<Response<pb::Project>, Status> {
+        let project_service = self.tenant_service(&request)?;
+        let request = request.into_inner();
+        debug!("Creating project(gRPC) {:?}", request);
+
+        let project = database::models::ProjectCreate {
+            project_name: request.project_name,
+            comment: request.comment,
+            parent_id: request.parent_id,
+        };
+        let project = project_service.create_project(project).await.map_err(database_status)?;
+
+        Ok(Response::new(project.into()))
+    }
+
+    async fn update(&self, request: Request<pb::UpdateProjectRequest>) -> Result<Response<pb::Project>, Status> {
+        let project_service = self.tenant_service(&request)?;
+        let request = request.into_inner();
+        debug!("Updating project(gRPC) {:?}", request);
+
+        let update = database::models::ProjectUpdate {
+            project_name: request.project_name,
+            comment: request.comment,
+        };
+        let project = project_service.update_project(request.id, update).await.map_err(database_status)?;
+
+        Ok(Response::new(project.into()))
+    }
+
+    async fn delete(&self, request: Request<pb::DeleteProjectRequest>) -> Result<Response<pb::Project>, Status> {
+        let project_service = self.tenant_service(&request)?;
+        let id = request.into_inner().id;
+        debug!("Deleting project(gRPC) {}", id);
+
+        let project = project_service.delete_project(id).await.map_err(database_status)?;
+
+        Ok(Response::new(project.into()))
+    }
+
+    async fn r#move(&self, request: Request<pb::MoveProjectRequest>) -> Result<Response<pb::Project>, Status> {
+        let project_service = self.tenant_service(&request)?;
+        let request = request.into_inner();
+        debug!("Moving project(gRPC) {:?}", request);
+
+        let project = project_service
+            .move_project(request.id, request.parent_id)
+            .await
+            .map_err(database_status)?;
+
+        Ok(Response::new(project.into()))
+    }
+}
+
+/// 将数据库中的UTC时间转换为protobuf时间戳
+fn to_timestamp(time: NaiveDateTime) -> Timestamp {
+    let time = time.and_utc();
+    Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pb::project_service_server::ProjectService as _;
+    use database::MemoryProjectRepository;
+    use std::collections::HashMap;
+    use storage::MemoryStorage;
+    use tonic::Code;
+    use web_service::services::ProjectService;
+
+    fn service() -> ProjectGrpcService<ProjectService<MemoryProjectRepository>> {
+        let auth = AuthConfig {
+            tokens: HashMap::from([("test-token".to_string(), "test".parse().unwrap())]),
+            default_tenant: None,
+        };
+        ProjectGrpcService::new(
+            Arc::new(ProjectService::new(MemoryProjectRepository::new(), Arc::new(MemoryStorage::new()))),
+            Arc::new(auth),
+        )
+    }
+
+    /// 携带测试令牌的请求
+    fn request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.metadata_mut().insert("authorization", "Bearer test-token".parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_project_crud() {
+        let service = service();
+
+        let created = service
+            .create(request(pb::CreateProjectRequest {
+                project_name: "pay".to_string(),
+                comment: "comment".to_string(),
+                parent_id: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(created.created_at.is_some());
+
+        let page = service
+            .search(request(pb::SearchProjectsRequest {
+                project_name: Some("pa".to_string()),
+                any_tags: Vec::new(),
+                all_tags: Vec::new(),
+                page_index: 1,
+                page_size: 10,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.projects[0].id, created.id);
+
+        service.delete(request(pb::DeleteProjectRequest { id: created.id })).await.unwrap();
+        let status = service.get(request(pb::GetProjectRequest { id: created.id })).await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_page() {
+        let status = service()
+            .search(request(pb::SearchProjectsRequest {
+                project_name: None,
+                any_tags: Vec::new(),
+                all_tags: Vec::new(),
+                page_index: 0,
+                page_size: 10,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_unauthenticated() {
+        let status = service().get(Request::new(pb::GetProjectRequest { id: 1 })).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+    }
+}
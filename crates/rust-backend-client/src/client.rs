@@ -0,0 +1,191 @@
+//! `/api/v1` 接口客户端
+
+use crate::error::{ClientError, ClientResult};
+use crate::pagination::{PageExt, first_page};
+use api_models::common::{Reply, ReplyList};
+use api_models::projects::{
+    ProjectCreate, ProjectInfo, ProjectRankedSearch, ProjectSearch, ProjectSearchHit, ProjectSuggestQuery, ProjectUpdate,
+};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, RequestBuilder, Response, Url};
+use serde::de::DeserializeOwned;
+
+/// API路由前缀
+const API_PREFIX: &str = "/api/v1";
+
+/// rust-backend `/api/v1` 接口的客户端
+///
+/// 内部的 [`reqwest::Client`] 自带连接池，客户端可以放心 `clone` 后在多个任务中使用。
+///
+/// ```no_run
+/// use rust_backend_client::RustBackendClient;
+///
+/// # async fn run() -> rust_backend_client::ClientResult<()> {
+/// let client = RustBackendClient::new("http://localhost:8080")?.with_bearer_token("token")?;
+/// let project = client.get_project(1).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RustBackendClient {
+    /// 服务地址，不包含结尾的 `/`
+    base_url: String,
+    http: reqwest::Client,
+    /// 每个请求都会带上的请求头，例如认证信息
+    headers: HeaderMap,
+}
+
+impl RustBackendClient {
+    /// 创建客户端
+    ///
+    /// `base_url` 为服务的根地址，例如 `http://localhost:8080`，不需要包含 `/api/v1`
+    pub fn new(base_url: &str) -> ClientResult<Self> {
+        Url::parse(base_url).map_err(|_| ClientError::InvalidBaseUrl(base_url.to_string()))?;
+
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+            headers: HeaderMap::new(),
+        })
+    }
+
+    /// 使用自定义的 [`reqwest::Client`]，例如需要设置超时时间、代理或者TLS证书
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// 使用 `Authorization: Bearer <token>` 认证
+    pub fn with_bearer_token(self, token: &str) -> ClientResult<Self> {
+        self.with_header(AUTHORIZATION, &format!("Bearer {token}"))
+    }
+
+    /// 为每个请求添加请求头，例如 `x-api-key`
+    ///
+    /// 相同名称的请求头会被覆盖
+    pub fn with_header(mut self, name: HeaderName, value: &str) -> ClientResult<Self> {
+        let mut value = HeaderValue::from_str(value).map_err(|_| ClientError::InvalidHeader(name.to_string()))?;
+        // 避免认证信息出现在日志中
+        value.set_sensitive(name == AUTHORIZATION);
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// 根据查询参数搜索项目
+    pub async fn find_projects(&self, search: &ProjectSearch) -> ClientResult<ReplyList<ProjectInfo>> {
+        self.send(self.request(Method::POST, "/search-projects").json(search)).await
+    }
+
+    /// 遍历所有分页，返回符合条件的全部项目
+    pub async fn find_all_projects(&self, project_name: Option<String>, page_size: u32) -> ClientResult<Vec<ProjectInfo>> {
+        let mut projects = Vec::new();
+        let mut search = ProjectSearch {
+            project_name,
+            any_tags: Vec::new(),
+            all_tags: Vec::new(),
+            page_query: first_page(page_size),
+        };
+
+        loop {
+            let page = self.find_projects(&search).await?;
+            let next_page = page.next_page();
+            let is_empty = page.data.is_empty();
+            projects.extend(page.data);
+
+            match next_page {
+                // 数据在分页过程中被删除时，可能会出现空页，此时直接结束
+                Some(page_query) if !is_empty => search.page_query = page_query,
+                _ => return Ok(projects),
+            }
+        }
+    }
+
+    /// 按相关度搜索项目
+    pub async fn rank_search_projects(&self, search: &ProjectRankedSearch) -> ClientResult<ReplyList<ProjectSearchHit>> {
+        self.send(self.request(Method::POST, "/search-projects/ranked").json(search)).await
+    }
+
+    /// 项目名称输入提示
+    pub async fn suggest_projects(&self, query: &ProjectSuggestQuery) -> ClientResult<Vec<String>> {
+        let reply: Reply<Vec<String>> = self.send(self.request(Method::GET, "/projects/suggestions").query(query)).await?;
+        Ok(reply.data)
+    }
+
+    /// 创建项目
+    pub async fn create_project(&self, project: &ProjectCreate) -> ClientResult<ProjectInfo> {
+        let reply: Reply<ProjectInfo> = self.send(self.request(Method::POST, "/projects").json(project)).await?;
+        Ok(reply.data)
+    }
+
+    /// 查询指定项目信息
+    pub async fn get_project(&self, id: i32) -> ClientResult<ProjectInfo> {
+        self.send(self.request(Method::GET, &format!("/projects/{id}"))).await
+    }
+
+    /// 更新项目信息
+    pub async fn update_project(&self, id: i32, update: &ProjectUpdate) -> ClientResult<ProjectInfo> {
+        self.send(self.request(Method::PATCH, &format!("/projects/{id}")).json(update))
+            .await
+    }
+
+    /// 删除指定的项目
+    pub async fn delete_project(&self, id: i32) -> ClientResult<ProjectInfo> {
+        self.send(self.request(Method::DELETE, &format!("/projects/{id}"))).await
+    }
+
+    /// 搜索用户
+    pub async fn find_users(&self) -> ClientResult<()> {
+        self.send_empty(self.request(Method::POST, "/search-users")).await
+    }
+
+    /// 查询指定用户信息
+    pub async fn get_user(&self, id: i32) -> ClientResult<()> {
+        self.send_empty(self.request(Method::GET, &format!("/users/{id}"))).await
+    }
+
+    /// 创建用户
+    pub async fn create_user(&self) -> ClientResult<()> {
+        self.send_empty(self.request(Method::POST, "/users")).await
+    }
+
+    /// 更新用户信息
+    pub async fn update_user(&self, id: i32) -> ClientResult<()> {
+        self.send_empty(self.request(Method::PATCH, &format!("/users/{id}"))).await
+    }
+
+    /// 删除指定的用户
+    pub async fn delete_user(&self, id: i32) -> ClientResult<()> {
+        self.send_empty(self.request(Method::DELETE, &format!("/users/{id}"))).await
+    }
+
+    /// 创建请求，`path` 为 `/api/v1` 之后的路径
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{API_PREFIX}{path}", self.base_url))
+            .headers(self.headers.clone())
+    }
+
+    /// 发送请求并反序列化返回值
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> ClientResult<T> {
+        let response = check_status(request.send().await?).await?;
+        Ok(response.json().await?)
+    }
+
+    /// 发送请求并忽略返回值
+    async fn send_empty(&self, request: RequestBuilder) -> ClientResult<()> {
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+}
+
+/// 将错误响应转换为 [`ClientError`]
+async fn check_status(response: Response) -> ClientResult<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    // 服务端的错误信息是纯文本，读取失败时不影响错误类型
+    let message = response.text().await.unwrap_or_default();
+    Err(ClientError::from_response(status, message))
+}
@@ -0,0 +1,73 @@
+//! 客户端错误类型
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+/// 客户端调用结果
+pub type ClientResult<T> = Result<T, ClientError>;
+
+/// 客户端错误
+///
+/// 服务端的错误响应会根据状态码转换为对应的错误类型，参考 `web_service::models::err::AppError`
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// 请求参数不正确（400/422），例如参数校验失败
+    #[error("Bad request ({status}): {message}")]
+    BadRequest { status: StatusCode, message: String },
+
+    /// 认证失败或者没有权限（401/403）
+    #[error("Unauthorized ({status}): {message}")]
+    Unauthorized { status: StatusCode, message: String },
+
+    /// 请求的资源不存在（404）
+    #[error("Not found: {message}")]
+    NotFound { message: String },
+
+    /// 请求体超过服务端限制（413）
+    #[error("Payload too large: {message}")]
+    PayloadTooLarge { message: String },
+
+    /// 服务端过载，可以稍后重试（503）
+    #[error("Service unavailable: {message}")]
+    Unavailable { message: String },
+
+    /// 服务端处理超时（504）
+    #[error("Server timed out: {message}")]
+    Timeout { message: String },
+
+    /// 其他错误响应
+    #[error("Unexpected response ({status}): {message}")]
+    Api { status: StatusCode, message: String },
+
+    /// 网络错误、返回值反序列化失败等
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    /// 服务地址不正确
+    #[error("Invalid base url `{0}`")]
+    InvalidBaseUrl(String),
+
+    /// 认证信息不是合法的请求头
+    #[error("Invalid header value for `{0}`")]
+    InvalidHeader(String),
+}
+
+impl ClientError {
+    /// 根据错误响应的状态码和内容创建错误
+    pub fn from_response(status: StatusCode, message: String) -> Self {
+        match status {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => ClientError::BadRequest { status, message },
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ClientError::Unauthorized { status, message },
+            StatusCode::NOT_FOUND => ClientError::NotFound { message },
+            StatusCode::PAYLOAD_TOO_LARGE => ClientError::PayloadTooLarge { message },
+            StatusCode::SERVICE_UNAVAILABLE => ClientError::Unavailable { message },
+            StatusCode::GATEWAY_TIMEOUT => ClientError::Timeout { message },
+            _ => ClientError::Api { status, message },
+        }
+    }
+
+    /// 是否可以重试（服务端过载或者超时）
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ClientError::Unavailable { .. } | ClientError::Timeout { .. })
+    }
+}
@@ -0,0 +1,21 @@
+//! rust-backend 的Rust客户端
+//!
+//! 为 `/api/v1` 的所有接口提供类型安全的异步调用：
+//! - 请求和返回值类型与服务端共用 `api-models` 中的定义，服务端修改后编译期即可发现不兼容，不需要编译服务端的依赖
+//! - 错误响应根据状态码转换为 [`ClientError`]
+//! - 支持Bearer Token等认证请求头
+//! - 提供分页辅助方法，参考 [`PageExt`]
+
+pub mod client;
+pub mod error;
+pub mod pagination;
+
+pub use client::RustBackendClient;
+pub use error::{ClientError, ClientResult};
+pub use pagination::{PageExt, first_page};
+
+// 重新导出请求和返回值类型，调用方不需要直接依赖 api-models
+pub use api_models::common::{PageQuery, Reply, ReplyList};
+pub use api_models::projects::{
+    ProjectCreate, ProjectHighlight, ProjectInfo, ProjectRankedSearch, ProjectSearch, ProjectSearchHit, ProjectSuggestQuery, ProjectUpdate,
+};
@@ -0,0 +1,53 @@
+//! 分页辅助工具
+
+use api_models::common::{PageQuery, ReplyList};
+
+/// 第一页的分页参数
+pub fn first_page(page_size: u32) -> PageQuery {
+    PageQuery { page_index: 1, page_size }
+}
+
+/// 分页结果的辅助方法
+pub trait PageExt {
+    /// 是否还有下一页
+    fn has_next_page(&self) -> bool;
+
+    /// 下一页的分页参数，没有下一页时返回 `None`
+    fn next_page(&self) -> Option<PageQuery>;
+}
+
+impl<T> PageExt for ReplyList<T> {
+    fn has_next_page(&self) -> bool {
+        u64::from(self.page_index) * u64::from(self.page_size) < u64::from(self.total)
+    }
+
+    fn next_page(&self) -> Option<PageQuery> {
+        self.has_next_page().then(|| PageQuery {
+            page_index: self.page_index + 1,
+            page_size: self.page_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(page_index: u32, page_size: u32, total: u32) -> ReplyList<()> {
+        ReplyList {
+            data: Vec::new(),
+            total,
+            page_size,
+            page_index,
+        }
+    }
+
+    #[test]
+    fn test_next_page() {
+        assert_eq!(page(1, 20, 45).next_page().map(|p| p.page_index), Some(2));
+        assert_eq!(page(2, 20, 45).next_page().map(|p| p.page_index), Some(3));
+        assert!(page(3, 20, 45).next_page().is_none());
+        assert!(page(2, 20, 40).next_page().is_none());
+        assert!(page(1, 20, 0).next_page().is_none());
+    }
+}
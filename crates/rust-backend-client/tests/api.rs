@@ -0,0 +1,135 @@
+//! 通过客户端调用真实的路由
+//!
+//! 仓库层使用 [`MemoryProjectRepository`]，不需要数据库
+
+use database::MemoryProjectRepository;
+use rust_backend_client::{
+    ClientError, PageExt, ProjectCreate, ProjectRankedSearch, ProjectSearch, ProjectSuggestQuery, ProjectUpdate, RustBackendClient,
+    first_page,
+};
+use shared_lib::models::config::MiddlewareConfig;
+use tokio::net::TcpListener;
+use web_service::routes::create_app_routers;
+use web_service::test_support::memory_app_state;
+
+/// 在随机端口上启动业务路由，返回连接到该端口的客户端
+async fn spawn_server() -> RustBackendClient {
+    let middleware_config = MiddlewareConfig {
+        max_body_size: 64 * 1024,
+        ..MiddlewareConfig::default()
+    };
+    let routers = create_app_routers(memory_app_state(MemoryProjectRepository::new()), &middleware_config).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, routers.public).await });
+
+    RustBackendClient::new(&format!("http://{addr}"))
+        .unwrap()
+        .with_bearer_token("test-token")
+        .unwrap()
+}
+
+fn new_project(name: &str) -> ProjectCreate {
+    ProjectCreate {
+        project_name: name.to_string(),
+        comment: format!("{name} comment"),
+        parent_id: None,
+    }
+}
+
+#[tokio::test]
+async fn test_project_crud() {
+    let client = spawn_server().await;
+
+    let created = client.create_project(&new_project("payments")).await.unwrap();
+    assert_eq!(created.project_name, "payments");
+
+    let fetched = client.get_project(created.id).await.unwrap();
+    assert_eq!(fetched.comment, "payments comment");
+
+    let update = ProjectUpdate {
+        project_name: None,
+        comment: Some("updated".to_string()),
+    };
+    let updated = client.update_project(created.id, &update).await.unwrap();
+    assert_eq!(updated.project_name, "payments");
+    assert_eq!(updated.comment, "updated");
+
+    let deleted = client.delete_project(created.id).await.unwrap();
+    assert_eq!(deleted.id, created.id);
+
+    let err = client.get_project(created.id).await.unwrap_err();
+    assert!(matches!(err, ClientError::NotFound { .. }), "{err:?}");
+}
+
+#[tokio::test]
+async fn test_search_and_pagination() {
+    let client = spawn_server().await;
+
+    for i in 0..45 {
+        client.create_project(&new_project(&format!("project-{i:02}"))).await.unwrap();
+    }
+    client.create_project(&new_project("other")).await.unwrap();
+
+    let search = ProjectSearch {
+        project_name: Some("project-".to_string()),
+        any_tags: Vec::new(),
+        all_tags: Vec::new(),
+        page_query: first_page(20),
+    };
+    let page = client.find_projects(&search).await.unwrap();
+    assert_eq!(page.total, 45);
+    assert_eq!(page.data.len(), 20);
+    assert_eq!(page.next_page().map(|p| p.page_index), Some(2));
+
+    let all = client.find_all_projects(Some("project-".to_string()), 20).await.unwrap();
+    assert_eq!(all.len(), 45);
+
+    let ranked = ProjectRankedSearch {
+        keyword: "other".to_string(),
+        threshold: None,
+        page_query: first_page(10),
+    };
+    let hits = client.rank_search_projects(&ranked).await.unwrap();
+    assert_eq!(hits.total, 1);
+    assert_eq!(hits.data[0].highlight.project_name.as_deref(), Some("<em>other</em>"));
+
+    let query = ProjectSuggestQuery {
+        q: "project-0".to_string(),
+        limit: Some(3),
+    };
+    let names = client.suggest_projects(&query).await.unwrap();
+    assert_eq!(names, ["project-00", "project-01", "project-02"]);
+}
+
+#[tokio::test]
+async fn test_error_responses() {
+    let client = spawn_server().await;
+
+    let search = ProjectSearch {
+        project_name: None,
+        any_tags: Vec::new(),
+        all_tags: Vec::new(),
+        page_query: first_page(0),
+    };
+    let err = client.find_projects(&search).await.unwrap_err();
+    assert!(matches!(err, ClientError::BadRequest { .. }), "{err:?}");
+
+    let err = client.create_project(&new_project(&"x".repeat(128 * 1024))).await.unwrap_err();
+    assert!(matches!(err, ClientError::PayloadTooLarge { .. }), "{err:?}");
+
+    let err = RustBackendClient::new("not a url").unwrap_err();
+    assert!(matches!(err, ClientError::InvalidBaseUrl(_)), "{err:?}");
+}
+
+#[tokio::test]
+async fn test_user_routes() {
+    let client = spawn_server().await;
+
+    client.find_users().await.unwrap();
+    client.get_user(1).await.unwrap();
+    client.create_user().await.unwrap();
+    client.update_user(1).await.unwrap();
+    client.delete_user(1).await.unwrap();
+}
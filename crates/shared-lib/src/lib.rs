@@ -4,9 +4,17 @@
 //! - 公共数据结构
 //! - Redis 模型和常量
 //! - 分布式锁工具
+//! - 日志初始化
+//! - 子系统运行状态
+//! - 关闭信号
+//! - 启动时等待依赖服务（重试连接数据库、Redis）
 
 pub mod distributed_lock;
+pub mod logging;
 pub mod models;
+pub mod shutdown;
+pub mod startup;
+pub mod subsystems;
 
 // 重新导出常用类型
 pub use models::{
@@ -18,10 +26,16 @@ pub use models::{
     HEARTBEAT_INTERVAL_SECONDS,
     HEARTBEAT_TIMEOUT_SECONDS,
     LOCK_TTL_SECONDS,
+    ListenAddr,
+    Locale,
+    MiddlewareConfig,
     REBALANCE_LOCK_KEY,
     RedisConfig,
     RedisConsumerHeartBeat,
     TaskInfo,
+    TenantId,
+    TlsConfig,
+    WebConfig,
 };
 
 // 重新导出分布式锁功能
@@ -0,0 +1,217 @@
+//! 📝 日志
+//!
+//! 启动服务时根据 [`LogConfig`] 初始化tracing：
+//! - 过滤规则使用 [`EnvFilter`] 的语法，例如 `info,database=debug,sqlx=warn`，修改配置后可以热加载，
+//!   也可以通过 [`LogFilter`] 临时修改（例如运维接口临时打开某个模块的debug日志）
+//! - 输出格式：`full`（单行文本）、`pretty`（多行文本）、`json`（每行一个JSON对象）
+//! - 日志始终输出到stdout，配置了 `log.file.directory` 时同时写入按天滚动的日志文件
+//! - 所有输出都使用非阻塞的写入器，由后台线程完成写入，避免阻塞异步任务。
+//!   程序退出前需要一直持有 [`LogGuard`]，销毁时会写完缓冲区中剩余的日志
+
+use crate::models::config::{LogConfig, LogFileConfig, LogFormat};
+use color_eyre::Result;
+use color_eyre::eyre::{Context, eyre};
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::Subscriber;
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry, fmt, reload};
+
+/// 日志文件的扩展名
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// 非阻塞写入器的后台线程，销毁时写完剩余的日志
+#[must_use = "日志写入线程会在销毁时停止，需要持有到程序退出"]
+pub struct LogGuard {
+    _guards: Vec<WorkerGuard>,
+}
+
+/// 初始化全局的日志记录器，返回可以在运行时修改过滤规则的 [`LogFilter`]
+pub fn init(config: &LogConfig) -> Result<(Arc<LogFilter>, LogGuard)> {
+    let (filter, filter_layer) = LogFilter::new(&config.filter)?;
+
+    let mut guards = Vec::new();
+    let (stdout, guard) = tracing_appender::non_blocking(std::io::stdout());
+    guards.push(guard);
+    let mut layers = vec![format_layer(config.format, stdout, true)];
+
+    if let Some(file) = &config.file {
+        let (writer, guard) = tracing_appender::non_blocking(rolling_file(file)?);
+        guards.push(guard);
+        layers.push(format_layer(config.format, writer, false));
+    }
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(layers)
+        .try_init()
+        .context("Failed to initialize logging")?;
+
+    Ok((Arc::new(filter), LogGuard { _guards: guards }))
+}
+
+/// 按天滚动的日志文件
+fn rolling_file(config: &LogFileConfig) -> Result<RollingFileAppender> {
+    // 清理旧文件时需要读取目录，提前创建
+    std::fs::create_dir_all(&config.directory).with_context(|| format!("Can not create log directory {}", config.directory.display()))?;
+
+    let mut builder = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(&config.prefix)
+        .filename_suffix(LOG_FILE_SUFFIX);
+    if let Some(max_files) = config.max_files {
+        builder = builder.max_log_files(max_files);
+    }
+
+    builder
+        .build(&config.directory)
+        .with_context(|| format!("Can not create log file in {}", config.directory.display()))
+}
+
+/// 指定格式的输出层，写入文件时不输出颜色
+fn format_layer<S>(format: LogFormat, writer: NonBlocking, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let layer = fmt::layer().with_writer(writer).with_ansi(ansi);
+    match format {
+        LogFormat::Full => layer.boxed(),
+        LogFormat::Pretty => layer.pretty().boxed(),
+        LogFormat::Json => layer.json().boxed(),
+    }
+}
+
+/// 当前的过滤规则
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterDirectives {
+    /// 配置项 `log.filter` 中的过滤规则
+    pub configured: String,
+
+    /// 临时设置的过滤规则，设置后优先于配置中的规则，直到被清除或者重启
+    pub temporary: Option<String>,
+}
+
+/// 可以在运行时修改的日志过滤规则
+///
+/// 配置中的过滤规则修改后（热加载）调用 [`LogFilter::set_configured`]；
+/// 需要临时调整时调用 [`LogFilter::set_temporary`]，例如临时打开某个模块的debug日志，排查完问题后调用 [`LogFilter::clear_temporary`] 恢复
+pub struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+
+    directives: Mutex<FilterDirectives>,
+
+    /// 没有安装到全局的日志记录器时（测试中）保持过滤层存活，否则无法修改过滤规则
+    _detached: Option<reload::Layer<EnvFilter, Registry>>,
+}
+
+impl LogFilter {
+    /// 创建过滤层和对应的 [`LogFilter`]
+    fn new(directives: &str) -> Result<(Self, reload::Layer<EnvFilter, Registry>)> {
+        let (layer, handle) = reload::Layer::new(parse_filter(directives)?);
+        let filter = LogFilter {
+            handle,
+            directives: Mutex::new(FilterDirectives {
+                configured: directives.to_string(),
+                temporary: None,
+            }),
+            _detached: None,
+        };
+        Ok((filter, layer))
+    }
+
+    /// 创建不影响全局日志记录器的 [`LogFilter`]，用于测试
+    pub fn detached(directives: &str) -> Result<Self> {
+        let (mut filter, layer) = LogFilter::new(directives)?;
+        filter._detached = Some(layer);
+        Ok(filter)
+    }
+
+    /// 当前的过滤规则
+    pub fn directives(&self) -> FilterDirectives {
+        self.lock().clone()
+    }
+
+    /// 配置中的过滤规则发生变化，没有临时规则时立即生效
+    pub fn set_configured(&self, directives: &str) -> Result<()> {
+        let mut current = self.lock();
+        if current.configured == directives {
+            return Ok(());
+        }
+
+        if current.temporary.is_none() {
+            self.reload(directives)?;
+        }
+        current.configured = directives.to_string();
+        Ok(())
+    }
+
+    /// 临时设置过滤规则，规则无效时返回错误
+    pub fn set_temporary(&self, directives: &str) -> Result<()> {
+        let mut current = self.lock();
+        self.reload(directives)?;
+        current.temporary = Some(directives.to_string());
+        Ok(())
+    }
+
+    /// 清除临时的过滤规则，恢复使用配置中的规则
+    pub fn clear_temporary(&self) -> Result<()> {
+        let mut current = self.lock();
+        if current.temporary.take().is_some() {
+            self.reload(&current.configured)?;
+        }
+        Ok(())
+    }
+
+    fn reload(&self, directives: &str) -> Result<()> {
+        let filter = parse_filter(directives)?;
+        self.handle
+            .reload(filter)
+            .map_err(|err| eyre!("Failed to reload log filter: {err}"))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, FilterDirectives> {
+        self.directives.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl std::fmt::Debug for LogFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogFilter").field("directives", &*self.lock()).finish()
+    }
+}
+
+/// 解析过滤规则
+fn parse_filter(directives: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(directives).with_context(|| format!("Invalid log filter `{directives}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_filter() {
+        let filter = LogFilter::detached("info").unwrap();
+
+        filter.set_temporary("info,database=trace").unwrap();
+        // 临时规则生效期间，配置中的规则变化只会被记录下来
+        filter.set_configured("warn").unwrap();
+        assert_eq!(
+            filter.directives(),
+            FilterDirectives {
+                configured: "warn".to_string(),
+                temporary: Some("info,database=trace".to_string()),
+            }
+        );
+
+        assert!(filter.set_temporary("info,database=loud").is_err());
+        assert_eq!(filter.directives().temporary.as_deref(), Some("info,database=trace"));
+
+        filter.clear_temporary().unwrap();
+        assert_eq!(filter.directives().temporary, None);
+        assert_eq!(filter.handle.with_current(|current| current.to_string()).unwrap(), "warn");
+    }
+}
@@ -1,66 +1,1349 @@
-use color_eyre::eyre::Context;
-use color_eyre::{Help, Result};
+//! 程序配置
+//!
+//! 配置可以来自TOML配置文件、环境变量和命令行参数，合并规则参考 [`config_source`](crate::models::config_source)。
+//! 下面每个字段的文档中都注明了对应的配置项，例如 `redis.max_pool_size`，以及兼容的环境变量名。
+
+use crate::models::config_source::{ConfigOverrides, ConfigSource};
+use crate::models::locale::Locale;
+use crate::models::redis_constants::{
+    BATCH_SIZE, CONSUMER_GROUP_NAME, CONSUMER_HEARTBEAT_KEY, HEARTBEAT_INTERVAL_SECONDS, HEARTBEAT_TIMEOUT_SECONDS, LOCK_TTL_SECONDS,
+    REBALANCE_LOCK_KEY,
+};
+use crate::models::secret::Secret;
+use crate::models::tenant::TenantId;
+use chrono::NaiveDate;
+use color_eyre::eyre::{Context, eyre};
+use color_eyre::{Help, Report, Result};
+use croner::Cron;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
 use validator::Validate;
 
+/// PostgreSQL连接池配置
+#[derive(Validate, Debug)]
+pub struct DatabaseConfig {
+    /// 数据库链接字符串（仅支持postgresql），必须设置
+    ///
+    /// 配置项 `database.url`，环境变量 `DATABASE_URL`（或 `DATABASE_URL_FILE`）
+    pub url: Secret,
+
+    /// 只读副本的链接字符串，可选
+    ///
+    /// 设置后只读查询（例如搜索项目、查询项目详情）发送到只读副本，写操作仍然发送到主库；
+    /// 两个连接池使用相同的连接池参数
+    ///
+    /// 配置项 `database.replica_url`，环境变量 `DATABASE_REPLICA_URL`（或 `DATABASE_REPLICA_URL_FILE`）
+    pub replica_url: Option<Secret>,
+
+    /// 连接池中保持的最少连接数，启动时预留，加快获取速度，默认10
+    ///
+    /// 配置项 `database.min_connections`
+    pub min_connections: u32,
+
+    /// 连接池的最大连接数，生产环境配置30~40即可，默认40
+    ///
+    /// 配置项 `database.max_connections`
+    #[validate(range(min = 1))]
+    pub max_connections: u32,
+
+    /// 获取连接的超时时间，默认3秒
+    ///
+    /// 配置项 `database.acquire_timeout_secs`
+    pub acquire_timeout: Duration,
+
+    /// 空闲连接的释放时间，默认1小时
+    ///
+    /// 配置项 `database.idle_timeout_secs`
+    pub idle_timeout: Duration,
+
+    /// 连接的最长使用时间，超过后强制释放，避免长时间链接导致数据库问题，默认6小时
+    ///
+    /// 配置项 `database.max_lifetime_secs`
+    pub max_lifetime: Duration,
+}
+
+impl DatabaseConfig {
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let config = DatabaseConfig {
+            url: source.required("database.url")?.into(),
+            replica_url: source.optional::<String>("database.replica_url")?.map(Secret::from),
+            min_connections: source.parse("database.min_connections", 10)?,
+            max_connections: source.parse("database.max_connections", 40)?,
+            acquire_timeout: Duration::from_secs(source.parse("database.acquire_timeout_secs", 3)?),
+            idle_timeout: Duration::from_secs(source.parse("database.idle_timeout_secs", 3600)?),
+            max_lifetime: Duration::from_secs(source.parse("database.max_lifetime_secs", 3600 * 6)?),
+        };
+
+        if config.min_connections > config.max_connections {
+            return Err(eyre!(
+                "database.min_connections ({}) must not be greater than database.max_connections ({})",
+                config.min_connections,
+                config.max_connections
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
 #[derive(Validate, Debug)]
 pub struct RedisConfig {
-    /// redis链接字符串
-    pub redis_conn_str: String,
+    /// redis链接字符串，必须设置
+    ///
+    /// 配置项 `redis.url`，环境变量 `REDIS_URL`（或 `REDIS_URL_FILE`）
+    pub redis_conn_str: Secret,
 
-    /// redis pool的大小
+    /// redis pool的大小，默认16
     /// 需要根据下面的max_consumer_count来配置
-    /// 可通过环境变量 `MAX_REDIS_POOL_SIZE` 来调整
+    ///
+    /// 配置项 `redis.max_pool_size`，环境变量 `MAX_REDIS_POOL_SIZE`
     #[validate(range(min = 10, max = 100))]
     pub max_redis_pool_size: usize,
 
-    /// 每个类型的任务最多启动的consumer个数
+    /// 每个类型的任务最多启动的consumer个数，默认5
     ///
     /// 例如：当前有A/B两种类型的consumer，如果这个值设置为5，那么最多启动5个A类型的消费者和5个B类型的消费者
     /// 最终需要的pool_size > 10
     ///
-    /// 可通过环境变量 `MAX_CONSUMER_COUNT` 来调整
+    /// 配置项 `redis.max_consumer_count`，环境变量 `MAX_CONSUMER_COUNT`
     #[validate(range(min = 1, max = 30))]
     pub max_consumer_count: usize,
+
+    /// 每次连接Redis的超时时间，默认5秒；连接失败后按 [`StartupConfig`] 重试
+    ///
+    /// 配置项 `redis.connect_timeout_secs`
+    pub connect_timeout: Duration,
+}
+
+impl RedisConfig {
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        Ok(RedisConfig {
+            redis_conn_str: source.required("redis.url")?.into(),
+            max_redis_pool_size: source.parse("redis.max_pool_size", 16)?,
+            max_consumer_count: source.parse("redis.max_consumer_count", 5)?,
+            connect_timeout: Duration::from_secs(source.parse("redis.connect_timeout_secs", 5)?),
+        })
+    }
+}
+
+/// Redis任务消费者和消息重平衡的配置
+///
+/// 默认值定义在 [`redis_constants`](crate::models::redis_constants) 中，
+/// 消费者和重平衡定时任务必须使用相同的消费者组、心跳键名，分开部署时需要保持一致
+#[derive(Validate, Debug, Clone)]
+pub struct ConsumerConfig {
+    /// 消费者组名称
+    ///
+    /// 配置项 `consumer.group_name`
+    pub group_name: String,
+
+    /// 消费者心跳的Hash键名
+    ///
+    /// 配置项 `consumer.heartbeat_key`
+    pub heartbeat_key: String,
+
+    /// 心跳发送间隔，默认5秒
+    ///
+    /// 配置项 `consumer.heartbeat_interval_secs`
+    pub heartbeat_interval: Duration,
+
+    /// 心跳超时时间，超过此时间没有心跳的消费者将被视为失效，默认60秒，必须大于心跳发送间隔
+    ///
+    /// 配置项 `consumer.heartbeat_timeout_secs`
+    pub heartbeat_timeout: Duration,
+
+    /// 每个消费者同时处理的消息数，默认5
+    ///
+    /// 配置项 `consumer.handler_concurrency`
+    #[validate(range(min = 1))]
+    pub handler_concurrency: usize,
+
+    /// 每次从流中读取的最大消息数，默认10
+    ///
+    /// 配置项 `consumer.read_count`
+    #[validate(range(min = 1))]
+    pub read_count: usize,
+
+    /// 没有消息时阻塞等待的时间，默认1秒
+    ///
+    /// 配置项 `consumer.read_block_ms`
+    pub read_block: Duration,
+
+    /// 重平衡分布式锁键名
+    ///
+    /// 配置项 `consumer.rebalance_lock_key`
+    pub rebalance_lock_key: String,
+
+    /// 重平衡分布式锁的超时时间，默认30秒
+    ///
+    /// 配置项 `consumer.rebalance_lock_ttl_secs`
+    pub rebalance_lock_ttl: Duration,
+
+    /// 重平衡时每次转移给同一个消费者的消息数，默认10
+    ///
+    /// 配置项 `consumer.rebalance_batch_size`
+    #[validate(range(min = 1))]
+    pub rebalance_batch_size: usize,
+}
+
+impl Default for ConsumerConfig {
+    fn default() -> Self {
+        ConsumerConfig {
+            group_name: CONSUMER_GROUP_NAME.to_string(),
+            heartbeat_key: CONSUMER_HEARTBEAT_KEY.to_string(),
+            heartbeat_interval: Duration::from_secs(HEARTBEAT_INTERVAL_SECONDS),
+            heartbeat_timeout: Duration::from_secs(HEARTBEAT_TIMEOUT_SECONDS as u64),
+            handler_concurrency: 5,
+            read_count: 10,
+            read_block: Duration::from_millis(1000),
+            rebalance_lock_key: REBALANCE_LOCK_KEY.to_string(),
+            rebalance_lock_ttl: Duration::from_secs(LOCK_TTL_SECONDS),
+            rebalance_batch_size: BATCH_SIZE,
+        }
+    }
+}
+
+impl ConsumerConfig {
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let default = ConsumerConfig::default();
+
+        let config = ConsumerConfig {
+            group_name: source.string("consumer.group_name", &default.group_name),
+            heartbeat_key: source.string("consumer.heartbeat_key", &default.heartbeat_key),
+            heartbeat_interval: Duration::from_secs(
+                source.parse("consumer.heartbeat_interval_secs", default.heartbeat_interval.as_secs())?,
+            ),
+            heartbeat_timeout: Duration::from_secs(source.parse("consumer.heartbeat_timeout_secs", default.heartbeat_timeout.as_secs())?),
+            handler_concurrency: source.parse("consumer.handler_concurrency", default.handler_concurrency)?,
+            read_count: source.parse("consumer.read_count", default.read_count)?,
+            read_block: Duration::from_millis(source.parse("consumer.read_block_ms", default.read_block.as_millis() as u64)?),
+            rebalance_lock_key: source.string("consumer.rebalance_lock_key", &default.rebalance_lock_key),
+            rebalance_lock_ttl: Duration::from_secs(
+                source.parse("consumer.rebalance_lock_ttl_secs", default.rebalance_lock_ttl.as_secs())?,
+            ),
+            rebalance_batch_size: source.parse("consumer.rebalance_batch_size", default.rebalance_batch_size)?,
+        };
+
+        if config.heartbeat_timeout <= config.heartbeat_interval {
+            return Err(eyre!(
+                "consumer.heartbeat_timeout_secs ({:?}) must be greater than consumer.heartbeat_interval_secs ({:?})",
+                config.heartbeat_timeout,
+                config.heartbeat_interval
+            ))
+            .suggestion("心跳超时时间一般设置为发送间隔的10倍以上，避免网络抖动时误判消费者失效");
+        }
+
+        Ok(config)
+    }
+}
+
+/// 定时任务配置
+#[derive(Debug)]
+pub struct CronConfig {
+    /// Redis消息重平衡的执行计划（6位cron表达式，包含秒），默认每10秒执行一次
+    ///
+    /// 加载配置时校验表达式，无效时启动失败，热加载时拒绝修改
+    ///
+    /// 配置项 `cron.rebalance_schedule`
+    pub rebalance_schedule: String,
+}
+
+impl Default for CronConfig {
+    fn default() -> Self {
+        CronConfig {
+            rebalance_schedule: "0/10 * * * * *".to_string(),
+        }
+    }
+}
+
+impl CronConfig {
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let rebalance_schedule = source.string("cron.rebalance_schedule", &CronConfig::default().rebalance_schedule);
+
+        // 与 tokio-cron-scheduler 的解析参数保持一致：必须包含秒，同时指定日期和星期时两个条件都要满足
+        Cron::new(&rebalance_schedule)
+            .with_seconds_required()
+            .with_dom_and_dow()
+            .parse()
+            .map_err(|err| eyre!("Invalid cron.rebalance_schedule `{rebalance_schedule}`: {err}"))
+            .suggestion("使用包含秒的6位cron表达式，例如 `0/10 * * * * *`")?;
+
+        Ok(CronConfig { rebalance_schedule })
+    }
+}
+
+/// 优雅退出配置
+///
+/// 收到退出信号后按阶段依次关闭各个服务，每个阶段都有自己的超时时间，超时后放弃未完成的工作并强制退出。
+/// 所有参数都支持热加载，开始退出时读取当前的配置
+#[derive(Debug)]
+pub struct ShutdownConfig {
+    /// 就绪探针返回503之后、停止接收请求之前等待的时间，让负载均衡有时间摘除流量，默认0秒
+    ///
+    /// 配置项 `shutdown.readiness_delay_secs`，部署在Kubernetes中时一般设置为就绪探针间隔的2倍左右
+    pub readiness_delay: Duration,
+
+    /// 等待Web和gRPC服务处理完请求的超时时间，默认30秒
+    ///
+    /// 配置项 `shutdown.web_timeout_secs`
+    pub web_timeout: Duration,
+
+    /// 等待Redis消费者处理完已经读取的消息并确认的超时时间，默认30秒
+    ///
+    /// 配置项 `shutdown.consumer_timeout_secs`
+    pub consumer_timeout: Duration,
+
+    /// 等待定时任务调度器停止的超时时间，默认10秒
+    ///
+    /// 配置项 `shutdown.cron_timeout_secs`
+    pub cron_timeout: Duration,
+
+    /// 等待数据库连接池关闭（所有连接归还）的超时时间，默认5秒
+    ///
+    /// 配置项 `shutdown.pool_timeout_secs`
+    pub pool_timeout: Duration,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        ShutdownConfig {
+            readiness_delay: Duration::ZERO,
+            web_timeout: Duration::from_secs(30),
+            consumer_timeout: Duration::from_secs(30),
+            cron_timeout: Duration::from_secs(10),
+            pool_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ShutdownConfig {
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let default = ShutdownConfig::default();
+        let secs = |key: &str, default: Duration| Ok::<_, Report>(Duration::from_secs(source.parse(key, default.as_secs())?));
+
+        Ok(ShutdownConfig {
+            readiness_delay: secs("shutdown.readiness_delay_secs", default.readiness_delay)?,
+            web_timeout: secs("shutdown.web_timeout_secs", default.web_timeout)?,
+            consumer_timeout: secs("shutdown.consumer_timeout_secs", default.consumer_timeout)?,
+            cron_timeout: secs("shutdown.cron_timeout_secs", default.cron_timeout)?,
+            pool_timeout: secs("shutdown.pool_timeout_secs", default.pool_timeout)?,
+        })
+    }
+}
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// 单行文本，默认格式
+    #[default]
+    Full,
+    /// 多行文本，适合本地开发时阅读
+    Pretty,
+    /// 每行一个JSON对象，适合日志采集
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "full" => Ok(LogFormat::Full),
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            other => Err(eyre!("Unknown log format `{other}`, expected full, pretty or json")),
+        }
+    }
+}
+
+/// 日志文件配置，日志文件按天滚动
+#[derive(Debug, Clone)]
+pub struct LogFileConfig {
+    /// 日志文件所在的目录，不存在时自动创建
+    ///
+    /// 配置项 `log.file.directory`，环境变量 `LOG_FILE_DIRECTORY`
+    pub directory: PathBuf,
+
+    /// 文件名前缀，默认 `rust-backend`，实际的文件名为 `rust-backend.2026-10-18.log`
+    ///
+    /// 配置项 `log.file.prefix`
+    pub prefix: String,
+
+    /// 最多保留的日志文件个数，超出时删除最早的文件，默认全部保留
+    ///
+    /// 配置项 `log.file.max_files`
+    pub max_files: Option<usize>,
+}
+
+/// 日志配置
+///
+/// 只对启动服务生效，运维子命令固定把 `info` 级别的日志输出到stderr
+#[derive(Debug)]
+pub struct LogConfig {
+    /// 过滤规则，使用 [`tracing_subscriber::EnvFilter`] 的语法，默认 `info`，例如 `info,database=debug,sqlx=warn`
+    ///
+    /// 配置项 `log.filter`，环境变量 `RUST_LOG`
+    pub filter: String,
+
+    /// 输出格式，默认 `full`，可选 `pretty`、`json`
+    ///
+    /// 配置项 `log.format`，环境变量 `LOG_FORMAT`
+    pub format: LogFormat,
+
+    /// 日志文件，设置了 `log.file.directory` 时同时输出到stdout和日志文件
+    pub file: Option<LogFileConfig>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            filter: "info".to_string(),
+            format: LogFormat::default(),
+            file: None,
+        }
+    }
+}
+
+impl LogConfig {
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let default = LogConfig::default();
+
+        // 只用来检查过滤规则是否有效，初始化日志时再重新解析
+        source.optional::<EnvFilter>("log.filter")?;
+
+        let file = source
+            .optional::<PathBuf>("log.file.directory")?
+            .map(|directory| {
+                Ok::<_, Report>(LogFileConfig {
+                    directory,
+                    prefix: source.string("log.file.prefix", "rust-backend"),
+                    max_files: source.optional("log.file.max_files")?,
+                })
+            })
+            .transpose()?;
+
+        Ok(LogConfig {
+            filter: source.string("log.filter", &default.filter),
+            format: source.parse("log.format", default.format)?,
+            file,
+        })
+    }
+}
+
+/// 程序角色，每个角色对应一个子系统
+///
+/// 同一个程序可以只启动部分子系统，例如消费者单独部署更多副本、定时任务只部署一份
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    /// Web服务（REST、GraphQL、运维接口），需要PostgreSQL
+    Web,
+    /// gRPC服务，需要PostgreSQL
+    Grpc,
+    /// Redis任务消费者，需要Redis
+    Consumer,
+    /// 定时任务（Redis消息重平衡），需要Redis
+    Cron,
+}
+
+impl Role {
+    /// 所有角色
+    pub const ALL: [Role; 4] = [Role::Web, Role::Grpc, Role::Consumer, Role::Cron];
+
+    /// 角色名称，与配置中使用的名称一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Web => "web",
+            Role::Grpc => "grpc",
+            Role::Consumer => "consumer",
+            Role::Cron => "cron",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| eyre!("Unknown role `{s}`, expected one of web, grpc, consumer, cron"))
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 启用的角色，配置格式为逗号分隔的角色名称，例如 `web,consumer`，`all` 表示所有角色
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Roles(Vec<Role>);
+
+impl Roles {
+    /// 启用所有角色
+    pub fn all() -> Self {
+        Roles(Role::ALL.to_vec())
+    }
+
+    /// 是否启用了指定角色
+    pub fn contains(&self, role: Role) -> bool {
+        self.0.contains(&role)
+    }
+
+    /// 启用的角色
+    pub fn iter(&self) -> impl Iterator<Item = Role> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// 只保留满足条件的角色
+    pub fn retain(&mut self, f: impl FnMut(&Role) -> bool) {
+        self.0.retain(f);
+    }
+
+    /// 是否没有启用任何角色
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 是否需要连接PostgreSQL
+    pub fn needs_database(&self) -> bool {
+        self.contains(Role::Web) || self.contains(Role::Grpc)
+    }
+}
+
+impl Default for Roles {
+    fn default() -> Self {
+        Roles::all()
+    }
+}
+
+impl FromStr for Roles {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut roles = Vec::new();
+        for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if name.eq_ignore_ascii_case("all") {
+                return Ok(Roles::all());
+            }
+            roles.push(name.parse::<Role>()?);
+        }
+        if roles.is_empty() {
+            return Err(eyre!("No role is selected in `{s}`"));
+        }
+
+        roles.sort_unstable();
+        roles.dedup();
+        Ok(Roles(roles))
+    }
+}
+
+impl Display for Roles {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = self.0.iter().map(Role::as_str).collect();
+        f.write_str(&names.join(","))
+    }
+}
+
+/// 子系统异常退出（返回错误或者panic）后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// 不重启，记录错误，其他子系统继续运行
+    Never,
+    /// 按指数退避重启
+    OnFailure,
+    /// 关闭所有子系统后退出程序
+    Escalate,
+}
+
+impl RestartPolicy {
+    /// 策略名称，与配置中使用的名称一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestartPolicy::Never => "never",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::Escalate => "escalate",
+        }
+    }
+}
+
+impl FromStr for RestartPolicy {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "never" => Ok(RestartPolicy::Never),
+            "on-failure" => Ok(RestartPolicy::OnFailure),
+            "escalate" => Ok(RestartPolicy::Escalate),
+            _ => Err(eyre!("Unknown restart policy `{s}`, expected never, on-failure or escalate")),
+        }
+    }
+}
+
+impl Display for RestartPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 启动时等待依赖服务的配置
+///
+/// 使用docker-compose、Kubernetes部署时，数据库和Redis可能比程序晚启动，
+/// 连接失败后按退避时间重试，超过 `timeout` 后仍然失败时启动失败，参考 [`crate::startup`]
+#[derive(Debug)]
+pub struct StartupConfig {
+    /// 等待数据库、Redis可用以及执行数据库迁移的最长时间，默认60秒，设置为0时不重试
+    ///
+    /// 配置项 `startup.timeout_secs`
+    pub timeout: Duration,
+
+    /// 第一次重试前等待的时间，之后每次翻倍，默认1秒
+    ///
+    /// 配置项 `startup.initial_backoff_secs`
+    pub initial_backoff: Duration,
+
+    /// 重试前最多等待的时间，默认10秒
+    ///
+    /// 配置项 `startup.max_backoff_secs`
+    pub max_backoff: Duration,
+
+    /// 降级启动，默认false
+    ///
+    /// 开启后Web服务不等待数据库，先以未就绪状态启动（就绪探针返回503），数据库可用并完成迁移后才开始接收流量
+    ///
+    /// 配置项 `startup.degraded`
+    pub degraded: bool,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        StartupConfig {
+            timeout: Duration::from_secs(60),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            degraded: false,
+        }
+    }
+}
+
+impl StartupConfig {
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let default = StartupConfig::default();
+
+        let config = StartupConfig {
+            timeout: Duration::from_secs(source.parse("startup.timeout_secs", default.timeout.as_secs())?),
+            initial_backoff: Duration::from_secs(source.parse("startup.initial_backoff_secs", default.initial_backoff.as_secs())?),
+            max_backoff: Duration::from_secs(source.parse("startup.max_backoff_secs", default.max_backoff.as_secs())?),
+            degraded: source.bool("startup.degraded", default.degraded)?,
+        };
+
+        if config.initial_backoff.is_zero() || config.max_backoff < config.initial_backoff {
+            return Err(eyre!(
+                "startup.initial_backoff_secs ({:?}) must be greater than 0 and not greater than startup.max_backoff_secs ({:?})",
+                config.initial_backoff,
+                config.max_backoff
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+/// 子系统监督配置
+///
+/// 每个角色对应的子系统可以设置不同的重启策略，例如启动时Redis暂时不可用导致定时任务失败，
+/// 定时任务按退避时间重启，不影响正常运行的Web服务
+#[derive(Debug)]
+pub struct SupervisorConfig {
+    /// Web服务的重启策略，默认 `escalate`（监听地址被占用等错误重启后也无法恢复）
+    ///
+    /// 配置项 `supervisor.web`
+    pub web: RestartPolicy,
+
+    /// gRPC服务的重启策略，默认 `escalate`
+    ///
+    /// 配置项 `supervisor.grpc`
+    pub grpc: RestartPolicy,
+
+    /// Redis消费者的重启策略，默认 `on-failure`
+    ///
+    /// 配置项 `supervisor.consumer`
+    pub consumer: RestartPolicy,
+
+    /// 定时任务的重启策略，默认 `on-failure`
+    ///
+    /// 配置项 `supervisor.cron`
+    pub cron: RestartPolicy,
+
+    /// 第一次重启前等待的时间，连续失败时每次翻倍，默认1秒
+    ///
+    /// 配置项 `supervisor.initial_backoff_secs`
+    pub initial_backoff: Duration,
+
+    /// 重启前最多等待的时间，默认60秒；子系统正常运行超过这个时间后再失败时，重新从 `initial_backoff` 开始
+    ///
+    /// 配置项 `supervisor.max_backoff_secs`
+    pub max_backoff: Duration,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        SupervisorConfig {
+            web: RestartPolicy::Escalate,
+            grpc: RestartPolicy::Escalate,
+            consumer: RestartPolicy::OnFailure,
+            cron: RestartPolicy::OnFailure,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl SupervisorConfig {
+    /// 角色对应子系统的重启策略
+    pub fn restart_policy(&self, role: Role) -> RestartPolicy {
+        match role {
+            Role::Web => self.web,
+            Role::Grpc => self.grpc,
+            Role::Consumer => self.consumer,
+            Role::Cron => self.cron,
+        }
+    }
+
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let default = SupervisorConfig::default();
+
+        let config = SupervisorConfig {
+            web: source.parse("supervisor.web", default.web)?,
+            grpc: source.parse("supervisor.grpc", default.grpc)?,
+            consumer: source.parse("supervisor.consumer", default.consumer)?,
+            cron: source.parse("supervisor.cron", default.cron)?,
+            initial_backoff: Duration::from_secs(source.parse("supervisor.initial_backoff_secs", default.initial_backoff.as_secs())?),
+            max_backoff: Duration::from_secs(source.parse("supervisor.max_backoff_secs", default.max_backoff.as_secs())?),
+        };
+
+        if config.initial_backoff.is_zero() || config.max_backoff < config.initial_backoff {
+            return Err(eyre!(
+                "supervisor.initial_backoff_secs ({:?}) must be greater than 0 and not greater than supervisor.max_backoff_secs ({:?})",
+                config.initial_backoff,
+                config.max_backoff
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+/// Web服务监听地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    /// TCP地址，格式为 `host:port`，例如 `0.0.0.0:8080`、`localhost:8080`、`[::1]:8080`
+    Tcp(String),
+
+    /// Unix domain socket路径，配置格式为 `unix:/path/to/app.sock`
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddr {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err(eyre!("Unix socket path is empty in listen address `{s}`"));
+            }
+            return Ok(ListenAddr::Unix(PathBuf::from(path)));
+        }
+
+        // 只做格式校验，主机名在绑定时由系统解析
+        match s.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => Ok(ListenAddr::Tcp(s.to_string())),
+            _ => Err(eyre!(
+                "Invalid listen address `{s}`, expected `host:port` or `unix:/path/to/socket`"
+            )),
+        }
+    }
+}
+
+impl Display for ListenAddr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenAddr::Tcp(addr) => write!(f, "{addr}"),
+            ListenAddr::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// TLS证书配置
+///
+/// 证书和私钥都使用PEM格式，文件内容变化后会自动重新加载，不需要重启服务
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// 证书链文件路径
+    ///
+    /// 配置项 `web.tls.cert_file`，环境变量 `WEB_TLS_CERT_FILE`
+    pub cert_file: PathBuf,
+
+    /// 私钥文件路径
+    ///
+    /// 配置项 `web.tls.key_file`，环境变量 `WEB_TLS_KEY_FILE`
+    pub key_file: PathBuf,
+}
+
+/// 业务接口的中间件配置
+#[derive(Validate, Debug)]
+pub struct MiddlewareConfig {
+    /// 允许跨域访问的来源列表，例如 `https://app.example.com`，`*` 表示允许所有来源
+    ///
+    /// 为空时不返回任何CORS响应头，浏览器会拒绝跨域请求。
+    ///
+    /// 配置项 `web.middleware.cors_allowed_origins`，环境变量 `WEB_CORS_ALLOWED_ORIGINS`，多个来源使用逗号分隔
+    pub cors_allowed_origins: Vec<String>,
+
+    /// 是否启用响应压缩（gzip/br/zstd）和请求解压，默认开启
+    ///
+    /// 配置项 `web.middleware.compression`，环境变量 `WEB_COMPRESSION`
+    pub compression: bool,
+
+    /// 请求体的最大字节数（解压后），默认2MiB
+    ///
+    /// 配置项 `web.middleware.max_body_size`，环境变量 `WEB_MAX_BODY_SIZE`
+    #[validate(range(min = 1024))]
+    pub max_body_size: usize,
+
+    /// 单个请求的处理超时时间，默认30秒，超时返回504
+    ///
+    /// 配置项 `web.middleware.request_timeout_secs`，环境变量 `WEB_REQUEST_TIMEOUT_SECS`
+    pub request_timeout: Duration,
+
+    /// 附件上传请求的处理超时时间，默认30分钟，上传接口使用这个超时时间代替 `request_timeout_secs`
+    ///
+    /// 上传期间一直在接收请求体，慢速客户端需要更长的时间
+    ///
+    /// 配置项 `web.middleware.upload_timeout_secs`，环境变量 `WEB_UPLOAD_TIMEOUT_SECS`
+    pub upload_timeout: Duration,
+
+    /// 同时处理的最大请求数，默认1024，超出时直接返回503
+    ///
+    /// 配置项 `web.middleware.max_concurrent_requests`，环境变量 `WEB_MAX_CONCURRENT_REQUESTS`
+    #[validate(range(min = 1))]
+    pub max_concurrent_requests: usize,
+
+    /// 错误信息的默认语言，默认 `en-US`，请求的 `Accept-Language` 中没有支持的语言时使用
+    ///
+    /// 配置项 `web.middleware.default_locale`，环境变量 `WEB_DEFAULT_LOCALE`，支持 `en-US` 和 `zh-CN`
+    pub default_locale: Locale,
+}
+
+impl Default for MiddlewareConfig {
+    fn default() -> Self {
+        MiddlewareConfig {
+            cors_allowed_origins: Vec::new(),
+            compression: true,
+            max_body_size: 2 * 1024 * 1024,
+            request_timeout: Duration::from_secs(30),
+            upload_timeout: Duration::from_secs(30 * 60),
+            max_concurrent_requests: 1024,
+            default_locale: Locale::default(),
+        }
+    }
+}
+
+impl MiddlewareConfig {
+    /// 读取中间件配置，未设置的使用默认值
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let default = MiddlewareConfig::default();
+
+        Ok(MiddlewareConfig {
+            cors_allowed_origins: source.list("web.middleware.cors_allowed_origins"),
+            compression: source.bool("web.middleware.compression", default.compression)?,
+            max_body_size: source.parse("web.middleware.max_body_size", default.max_body_size)?,
+            request_timeout: Duration::from_secs(source.parse("web.middleware.request_timeout_secs", default.request_timeout.as_secs())?),
+            upload_timeout: Duration::from_secs(source.parse("web.middleware.upload_timeout_secs", default.upload_timeout.as_secs())?),
+            max_concurrent_requests: source.parse("web.middleware.max_concurrent_requests", default.max_concurrent_requests)?,
+            default_locale: source.parse("web.middleware.default_locale", default.default_locale)?,
+        })
+    }
+}
+
+/// Web服务配置
+#[derive(Validate, Debug)]
+pub struct WebConfig {
+    /// 监听地址，默认 `0.0.0.0:8080`
+    ///
+    /// 配置项 `web.listen_addr`，环境变量 `WEB_LISTEN_ADDR`
+    pub listen_addr: ListenAddr,
+
+    /// 运维接口的监听地址，默认 `127.0.0.1:8081`
+    ///
+    /// 文档、健康检查、监控指标和管理接口只在这个地址上提供，不对外暴露。
+    /// 该监听器固定使用明文HTTP/1.1，不受 `tls` 和 `http2` 配置影响。
+    ///
+    /// 配置项 `web.admin_listen_addr`，环境变量 `WEB_ADMIN_LISTEN_ADDR`
+    pub admin_listen_addr: ListenAddr,
+
+    /// TLS配置，为 `None` 时使用明文HTTP
+    pub tls: Option<TlsConfig>,
+
+    /// 是否启用HTTP/2，默认关闭
+    ///
+    /// - 启用TLS时通过ALPN协商 `h2`
+    /// - 明文时支持 prior knowledge 方式的 `h2c`
+    ///
+    /// 配置项 `web.http2`，环境变量 `WEB_HTTP2`
+    pub http2: bool,
+
+    /// 业务接口的中间件配置
+    #[validate(nested)]
+    pub middleware: MiddlewareConfig,
+
+    /// v1中已经有v2替代的接口的弃用日期，通过 `Deprecation` 响应头返回，默认 [`DEFAULT_V1_DEPRECATED_AT`]
+    ///
+    /// 配置项 `web.v1_deprecated_at`，环境变量 `WEB_V1_DEPRECATED_AT`，格式为 `YYYY-MM-DD`（UTC）
+    pub v1_deprecated_at: NaiveDate,
+
+    /// v1中已经有v2替代的接口的计划下线日期，通过 `Sunset` 响应头返回，默认 [`DEFAULT_V1_SUNSET`]
+    ///
+    /// 推迟下线时修改这个配置即可，不需要修改代码。
+    ///
+    /// 配置项 `web.v1_sunset`，环境变量 `WEB_V1_SUNSET`，格式为 `YYYY-MM-DD`（UTC），需要晚于 `web.v1_deprecated_at`
+    pub v1_sunset: NaiveDate,
+}
+
+/// v1接口默认的弃用日期，即v2发布的日期
+pub const DEFAULT_V1_DEPRECATED_AT: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 18).expect("valid date");
+
+/// v1接口默认的计划下线日期，弃用后6个月，由API负责人通过 `web.v1_sunset` 调整
+pub const DEFAULT_V1_SUNSET: NaiveDate = NaiveDate::from_ymd_opt(2027, 4, 18).expect("valid date");
+
+impl WebConfig {
+    /// 读取Web服务配置
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let listen_addr = source.parse("web.listen_addr", ListenAddr::Tcp("0.0.0.0:8080".to_string()))?;
+        let admin_listen_addr = source.parse("web.admin_listen_addr", ListenAddr::Tcp("127.0.0.1:8081".to_string()))?;
+
+        if admin_listen_addr == listen_addr {
+            return Err(eyre!(
+                "web.admin_listen_addr must be different from web.listen_addr ({listen_addr})"
+            ))
+            .suggestion("为 web.admin_listen_addr（WEB_ADMIN_LISTEN_ADDR）设置一个单独的端口或socket路径");
+        }
+
+        let tls = match (source.get("web.tls.cert_file"), source.get("web.tls.key_file")) {
+            (Some(cert_file), Some(key_file)) => Some(TlsConfig {
+                cert_file: cert_file.into(),
+                key_file: key_file.into(),
+            }),
+            (None, None) => None,
+            _ => {
+                return Err(eyre!("web.tls.cert_file and web.tls.key_file must be set together"))
+                    .suggestion("同时设置或同时移除 WEB_TLS_CERT_FILE 和 WEB_TLS_KEY_FILE");
+            }
+        };
+
+        let v1_deprecated_at = source.parse("web.v1_deprecated_at", DEFAULT_V1_DEPRECATED_AT)?;
+        let v1_sunset = source.parse("web.v1_sunset", DEFAULT_V1_SUNSET)?;
+        if v1_sunset <= v1_deprecated_at {
+            return Err(eyre!(
+                "web.v1_sunset ({v1_sunset}) must be later than web.v1_deprecated_at ({v1_deprecated_at})"
+            ));
+        }
+
+        Ok(WebConfig {
+            listen_addr,
+            admin_listen_addr,
+            tls,
+            http2: source.bool("web.http2", false)?,
+            middleware: MiddlewareConfig::from_source(source)?,
+            v1_deprecated_at,
+            v1_sunset,
+        })
+    }
+}
+
+/// gRPC服务配置
+#[derive(Debug)]
+pub struct GrpcConfig {
+    /// 监听地址，默认 `0.0.0.0:50051`
+    ///
+    /// 配置项 `grpc.listen_addr`，环境变量 `GRPC_LISTEN_ADDR`
+    pub listen_addr: SocketAddr,
+}
+
+impl GrpcConfig {
+    /// 读取gRPC服务配置
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        Ok(GrpcConfig {
+            listen_addr: source.parse("grpc.listen_addr", SocketAddr::from(([0, 0, 0, 0], 50051)))?,
+        })
+    }
+}
+
+/// 搜索配置
+#[derive(Validate, Debug)]
+pub struct SearchConfig {
+    /// 按相关度搜索时的默认相似度阈值（0~1），请求中没有指定 `threshold` 时使用，默认 [`DEFAULT_SIMILARITY_THRESHOLD`]
+    ///
+    /// 值越大结果越精确，数据量较大、拼写错误较少时可以适当调高
+    ///
+    /// 配置项 `search.similarity_threshold`，环境变量 `SEARCH_SIMILARITY_THRESHOLD`
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub similarity_threshold: f32,
+}
+
+/// 默认相似度阈值，与 `pg_trgm.similarity_threshold` 的默认值保持一致
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.3;
+
+impl SearchConfig {
+    /// 读取搜索配置
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        Ok(SearchConfig {
+            similarity_threshold: source.parse("search.similarity_threshold", DEFAULT_SIMILARITY_THRESHOLD)?,
+        })
+    }
+}
+
+/// 附件存储后端
+#[derive(Clone, Debug)]
+pub enum StorageBackend {
+    /// 本地文件系统，附件保存在指定目录下
+    Local {
+        /// 附件根目录
+        ///
+        /// 配置项 `storage.local_root`，环境变量 `STORAGE_LOCAL_ROOT`，默认 `./data/attachments`
+        root: PathBuf,
+    },
+
+    /// S3兼容的对象存储，例如 AWS S3、MinIO
+    S3(S3Config),
+}
+
+/// S3兼容对象存储的配置
+#[derive(Clone, Debug)]
+pub struct S3Config {
+    /// 服务地址，例如 `https://s3.us-east-1.amazonaws.com`、`http://localhost:9000`
+    ///
+    /// 配置项 `storage.s3.endpoint`，环境变量 `S3_ENDPOINT`
+    pub endpoint: String,
+
+    /// 区域，默认 `us-east-1`（MinIO默认使用该区域）
+    ///
+    /// 配置项 `storage.s3.region`，环境变量 `S3_REGION`
+    pub region: String,
+
+    /// 存储桶名称，需要提前创建
+    ///
+    /// 配置项 `storage.s3.bucket`，环境变量 `S3_BUCKET`
+    pub bucket: String,
+
+    /// 访问密钥ID
+    ///
+    /// 配置项 `storage.s3.access_key_id`，环境变量 `S3_ACCESS_KEY_ID`（或 `S3_ACCESS_KEY_ID_FILE`）
+    pub access_key_id: Secret,
+
+    /// 访问密钥
+    ///
+    /// 配置项 `storage.s3.secret_access_key`，环境变量 `S3_SECRET_ACCESS_KEY`（或 `S3_SECRET_ACCESS_KEY_FILE`）
+    pub secret_access_key: Secret,
+
+    /// 是否使用路径风格的地址（`<endpoint>/<bucket>/<key>`），默认开启，MinIO需要开启
+    ///
+    /// 关闭时使用虚拟主机风格的地址（`<bucket>.<endpoint>/<key>`）
+    ///
+    /// 配置项 `storage.s3.path_style`，环境变量 `S3_PATH_STYLE`
+    pub path_style: bool,
+}
+
+/// 附件存储配置
+#[derive(Debug)]
+pub struct StorageConfig {
+    /// 存储后端
+    ///
+    /// 配置项 `storage.backend`，环境变量 `STORAGE_BACKEND`，支持 `local`（默认）和 `s3`
+    pub backend: StorageBackend,
+
+    /// 单个附件上传请求的最大字节数（包括multipart编码），默认100MiB
+    ///
+    /// 附件上传接口不受 `WEB_MAX_BODY_SIZE` 限制，超时时间为 `WEB_UPLOAD_TIMEOUT_SECS`
+    ///
+    /// 配置项 `storage.max_upload_size`，环境变量 `STORAGE_MAX_UPLOAD_SIZE`
+    pub max_upload_size: usize,
+}
+
+impl StorageConfig {
+    /// 读取附件存储配置
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let backend = match source.get("storage.backend").unwrap_or("local") {
+            "local" => StorageBackend::Local {
+                root: source.string("storage.local_root", "./data/attachments").into(),
+            },
+            "s3" => StorageBackend::S3(S3Config {
+                endpoint: source.required("storage.s3.endpoint")?,
+                region: source.string("storage.s3.region", "us-east-1"),
+                bucket: source.required("storage.s3.bucket")?,
+                access_key_id: source.required("storage.s3.access_key_id")?.into(),
+                secret_access_key: source.required("storage.s3.secret_access_key")?.into(),
+                path_style: source.bool("storage.s3.path_style", true)?,
+            }),
+            other => {
+                return Err(eyre!("Unknown storage.backend `{other}`")).suggestion("storage.backend（STORAGE_BACKEND）只支持 local 和 s3");
+            }
+        };
+
+        Ok(StorageConfig {
+            backend,
+            max_upload_size: source.parse("storage.max_upload_size", 100 * 1024 * 1024)?,
+        })
+    }
+}
+
+/// 接口认证配置
+///
+/// 调用方在请求头中携带 `Authorization: Bearer <token>`，服务根据令牌确定所属租户，
+/// 数据库通过行级安全策略只返回该租户的数据
+#[derive(Clone, Default)]
+pub struct AuthConfig {
+    /// 访问令牌到租户的映射
+    ///
+    /// 配置项 `auth.tokens`，环境变量 `AUTH_TOKENS`（或 `AUTH_TOKENS_FILE`），格式为 `tenant:token`，多个令牌使用逗号分隔，
+    /// 同一个租户可以配置多个令牌
+    pub tokens: HashMap<String, TenantId>,
+
+    /// 未携带令牌时使用的租户，默认不设置，未携带令牌的请求返回401
+    ///
+    /// 只建议在单租户部署或本地开发时使用，配置项 `auth.default_tenant`，环境变量 `AUTH_DEFAULT_TENANT`
+    pub default_tenant: Option<TenantId>,
+}
+
+/// 手动实现Debug，避免在日志中输出令牌
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut tenants: Vec<&str> = self.tokens.values().map(TenantId::as_str).collect();
+        tenants.sort_unstable();
+        tenants.dedup();
+        f.debug_struct("AuthConfig")
+            .field("tenants", &tenants)
+            .field("default_tenant", &self.default_tenant)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AuthConfig {
+    /// 读取接口认证配置
+    fn from_source(source: &ConfigSource) -> Result<Self> {
+        let mut tokens = HashMap::new();
+        for entry in source.list("auth.tokens") {
+            let (tenant, token) = entry
+                .split_once(':')
+                .filter(|(_, token)| !token.is_empty())
+                .ok_or_else(|| eyre!("Invalid auth.tokens entry, expected `tenant:token`"))?;
+            let tenant: TenantId = tenant.trim().parse().context("Can not parse auth.tokens")?;
+            if tokens.insert(token.to_string(), tenant).is_some() {
+                return Err(eyre!("Duplicate token in auth.tokens")).suggestion("每个令牌只能属于一个租户");
+            }
+        }
+
+        let default_tenant = source.optional("auth.default_tenant")?;
+
+        if tokens.is_empty() && default_tenant.is_none() {
+            tracing::warn!("⚠️ 未配置 AUTH_TOKENS 和 AUTH_DEFAULT_TENANT，所有业务请求都会返回401");
+        }
+
+        Ok(AuthConfig { tokens, default_tenant })
+    }
+
+    /// 根据 `Authorization` 请求头确定租户
+    ///
+    /// - 携带了 `Bearer` 令牌时，令牌必须已配置，否则返回 `None`
+    /// - 没有携带令牌时使用默认租户
+    pub fn resolve_tenant(&self, authorization: Option<&str>) -> Option<TenantId> {
+        match authorization {
+            Some(value) => {
+                let (scheme, token) = value.trim().split_once(' ')?;
+                if !scheme.eq_ignore_ascii_case("bearer") {
+                    return None;
+                }
+                self.tokens.get(token.trim()).cloned()
+            }
+            None => self.default_tenant.clone(),
+        }
+    }
 }
 
 /// 程序配置
+///
+/// Debug输出会隐藏所有密钥（链接字符串中的密码、S3密钥、访问令牌），可以直接打印到日志或者命令行
 #[derive(Validate, Debug)]
 pub struct AppConfig {
-    /// postgresql数据库链接字符串
-    pub postgresql_conn_str: String,
+    /// 启用的角色，默认启用所有角色
+    ///
+    /// 配置项 `roles`，环境变量 `ROLES`，例如 `ROLES=web,grpc`
+    pub roles: Roles,
+
+    /// 日志配置
+    pub log: LogConfig,
+
+    /// postgresql数据库配置
+    #[validate(nested)]
+    pub database: DatabaseConfig,
 
     /// redis配置
     #[validate(nested)]
     pub redis: RedisConfig,
+
+    /// redis任务消费者和消息重平衡配置
+    #[validate(nested)]
+    pub consumer: ConsumerConfig,
+
+    /// 定时任务配置
+    pub cron: CronConfig,
+
+    /// 启动时等待依赖服务的配置
+    pub startup: StartupConfig,
+
+    /// 子系统监督配置
+    pub supervisor: SupervisorConfig,
+
+    /// 优雅退出配置
+    pub shutdown: ShutdownConfig,
+
+    /// web服务配置
+    #[validate(nested)]
+    pub web: WebConfig,
+
+    /// gRPC服务配置
+    pub grpc: GrpcConfig,
+
+    /// 搜索配置
+    #[validate(nested)]
+    pub search: SearchConfig,
+
+    /// 附件存储配置
+    pub storage: StorageConfig,
+
+    /// 接口认证配置
+    pub auth: AuthConfig,
 }
 
 impl AppConfig {
+    /// 从配置文件和环境变量中加载配置，参考 [`AppConfig::load_with`]
     pub fn load() -> Result<Arc<AppConfig>> {
+        AppConfig::load_with(&ConfigOverrides::default())
+    }
+
+    /// 按 `配置文件 < 环境变量 < 命令行参数` 的优先级加载配置
+    pub fn load_with(overrides: &ConfigOverrides) -> Result<Arc<AppConfig>> {
+        Ok(Arc::new(AppConfig::from_source_checked(&AppConfig::load_source(overrides)?)?))
+    }
+
+    /// 加载 `.env` 文件，然后按优先级合并配置文件、环境变量和命令行参数中的配置项
+    pub fn load_source(overrides: &ConfigOverrides) -> Result<ConfigSource> {
         // 加载.env文件中的数据注入到环境变量中，方便本地测试
-        // 线上环境部署时会直接使用环境变量，不需要.env文件
-        dotenvy::dotenv()?;
-
-        // 读取数据库地址信息（仅支持postgresql）
-        let db_url = std::env::var("DATABASE_URL")
-            .context("Can not load DATABASE_URL in environment")
-            .suggestion("设置 DATABASE_URL 环境变量")?;
-
-        let redis_url = std::env::var("REDIS_URL")
-            .context("Can not load REDIS_URL in environment")
-            .suggestion("设置 REDIS_URL 环境变量")?;
-
-        let config = AppConfig {
-            postgresql_conn_str: db_url,
-            redis: RedisConfig {
-                max_consumer_count: std::env::var("MAX_CONSUMER_COUNT").map_or(5, |s| s.parse().unwrap_or(5)),
-                redis_conn_str: redis_url,
-                max_redis_pool_size: std::env::var("MAX_REDIS_POOL_SIZE").map_or(16, |s| s.parse().unwrap_or(16)),
-            },
+        // 线上环境部署时会直接使用环境变量或配置文件，不需要.env文件
+        if let Err(err) = dotenvy::dotenv()
+            && !err.not_found()
+        {
+            return Err(err).context("Can not load .env file");
+        }
+
+        ConfigSource::load(overrides)
+    }
+
+    /// 从合并后的配置项中读取所有配置，并校验配置是否有效
+    pub fn from_source_checked(source: &ConfigSource) -> Result<AppConfig> {
+        let config = AppConfig::from_source(source)?;
+        config.validate().context("Validate app config")?;
+
+        Ok(config)
+    }
+
+    /// 从合并后的配置项中读取所有配置
+    pub fn from_source(source: &ConfigSource) -> Result<AppConfig> {
+        Ok(AppConfig {
+            roles: source.parse("roles", Roles::all())?,
+            log: LogConfig::from_source(source)?,
+            database: DatabaseConfig::from_source(source)?,
+            redis: RedisConfig::from_source(source)?,
+            consumer: ConsumerConfig::from_source(source)?,
+            cron: CronConfig::from_source(source)?,
+            startup: StartupConfig::from_source(source)?,
+            supervisor: SupervisorConfig::from_source(source)?,
+            shutdown: ShutdownConfig::from_source(source)?,
+            web: WebConfig::from_source(source)?,
+            grpc: GrpcConfig::from_source(source)?,
+            search: SearchConfig::from_source(source)?,
+            storage: StorageConfig::from_source(source)?,
+            auth: AuthConfig::from_source(source)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listen_addr() {
+        assert_eq!(
+            "0.0.0.0:8080".parse::<ListenAddr>().unwrap(),
+            ListenAddr::Tcp("0.0.0.0:8080".to_string())
+        );
+        assert_eq!(
+            "[::1]:8080".parse::<ListenAddr>().unwrap(),
+            ListenAddr::Tcp("[::1]:8080".to_string())
+        );
+        assert_eq!(
+            "unix:/run/app.sock".parse::<ListenAddr>().unwrap(),
+            ListenAddr::Unix(PathBuf::from("/run/app.sock"))
+        );
+
+        assert!("localhost".parse::<ListenAddr>().is_err());
+        assert!(":8080".parse::<ListenAddr>().is_err());
+        assert!("unix:".parse::<ListenAddr>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_tenant() {
+        let tenant = |s: &str| s.parse::<TenantId>().unwrap();
+        let mut config = AuthConfig {
+            tokens: HashMap::from([("secret".to_string(), tenant("acme"))]),
+            default_tenant: None,
         };
 
-        config.validate().context(format!("Validate app config: {config:?}"))?;
+        assert_eq!(config.resolve_tenant(Some("Bearer secret")), Some(tenant("acme")));
+        assert_eq!(config.resolve_tenant(Some("bearer secret")), Some(tenant("acme")));
+        assert_eq!(config.resolve_tenant(Some("Bearer other")), None);
+        assert_eq!(config.resolve_tenant(Some("Basic secret")), None);
+        assert_eq!(config.resolve_tenant(None), None);
+
+        // 携带了错误令牌时不能退回默认租户
+        config.default_tenant = Some(tenant("default"));
+        assert_eq!(config.resolve_tenant(None), Some(tenant("default")));
+        assert_eq!(config.resolve_tenant(Some("Bearer other")), None);
+        assert!(!format!("{config:?}").contains("secret"));
+    }
+
+    #[test]
+    fn test_parse_roles() {
+        let roles: Roles = " Consumer, web ,consumer".parse().unwrap();
+        assert_eq!(roles.to_string(), "web,consumer");
+        assert!(roles.contains(Role::Consumer));
+        assert!(!roles.contains(Role::Cron));
+        assert!(roles.needs_database());
 
-        Ok(Arc::new(config))
+        assert!(!"consumer,cron".parse::<Roles>().unwrap().needs_database());
+        assert_eq!("all".parse::<Roles>().unwrap(), Roles::all());
+        assert!("".parse::<Roles>().is_err());
+        assert!("web,worker".parse::<Roles>().is_err());
     }
 }
@@ -0,0 +1,174 @@
+//! 配置热加载
+//!
+//! 收到 `SIGHUP` 信号或者调用运维接口 `POST /admin/config/reload` 时，重新读取配置文件、`_file` 指向的文件和 `.env` 文件，
+//! 通过 [`watch`] 通道把新配置发布给各个服务，服务使用 [`ConfigReceiver`] 读取最新的配置：
+//!
+//! - 日志过滤规则：`log.filter`
+//! - Redis消费者：心跳间隔、并发数、每次读取的消息数和阻塞时间，下一次读取消息时生效
+//! - 定时任务：重平衡的执行计划、锁的过期时间、批量大小和心跳超时时间
+//! - 业务接口的负载保护：并发请求数上限和请求超时时间
+//! - 搜索：按相关度搜索的默认相似度阈值，下一个请求生效
+//! - 优雅退出：各个阶段的超时时间，开始退出时生效
+//!
+//! 只有 [`RELOADABLE_KEYS`] 中的配置项可以在运行时修改。其他配置项（监听地址、连接池、Redis键名、角色等）修改后需要重启服务，
+//! 重新加载时只要有一个这样的配置项发生了变化，就会拒绝整个修改并继续使用原有配置，避免只生效一部分。
+//!
+//! 注意：运行中的进程的环境变量不会变化，`.env` 文件也不会覆盖已经存在的环境变量，所以热加载一般通过修改配置文件完成。
+
+use crate::models::config::AppConfig;
+use crate::models::config_source::{ConfigOverrides, ConfigSource};
+use color_eyre::eyre::eyre;
+use color_eyre::{Help, Result};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::watch;
+
+/// 可以在运行时修改的配置项
+pub const RELOADABLE_KEYS: &[&str] = &[
+    "log.filter",
+    "consumer.heartbeat_interval_secs",
+    "consumer.heartbeat_timeout_secs",
+    "consumer.handler_concurrency",
+    "consumer.read_count",
+    "consumer.read_block_ms",
+    "consumer.rebalance_lock_ttl_secs",
+    "consumer.rebalance_batch_size",
+    "cron.rebalance_schedule",
+    "shutdown.readiness_delay_secs",
+    "shutdown.web_timeout_secs",
+    "shutdown.consumer_timeout_secs",
+    "shutdown.cron_timeout_secs",
+    "shutdown.pool_timeout_secs",
+    "web.middleware.request_timeout_secs",
+    "web.middleware.upload_timeout_secs",
+    "web.middleware.max_concurrent_requests",
+    "search.similarity_threshold",
+];
+
+/// 读取最新配置的接收端，`borrow()` 得到当前配置，`changed()` 等待配置变化
+pub type ConfigReceiver = watch::Receiver<Arc<AppConfig>>;
+
+/// 配置热加载器
+///
+/// 程序中只创建一份，启动的服务通过 [`ConfigReloader::subscribe`] 订阅配置变化
+#[derive(Debug)]
+pub struct ConfigReloader {
+    /// 命令行中指定的配置，重新加载时保持不变
+    overrides: ConfigOverrides,
+
+    /// 当前生效的配置项，用于比较哪些配置项发生了变化
+    source: Mutex<ConfigSource>,
+
+    sender: watch::Sender<Arc<AppConfig>>,
+}
+
+impl ConfigReloader {
+    /// 加载配置，参考 [`AppConfig::load_with`]
+    pub fn load(overrides: ConfigOverrides) -> Result<Self> {
+        let source = AppConfig::load_source(&overrides)?;
+        ConfigReloader::from_source(source, overrides)
+    }
+
+    /// 使用已经合并好的配置项创建
+    pub fn from_source(source: ConfigSource, overrides: ConfigOverrides) -> Result<Self> {
+        let config = AppConfig::from_source_checked(&source)?;
+
+        Ok(ConfigReloader {
+            overrides,
+            source: Mutex::new(source),
+            sender: watch::Sender::new(Arc::new(config)),
+        })
+    }
+
+    /// 当前生效的配置
+    pub fn current(&self) -> Arc<AppConfig> {
+        Arc::clone(&self.sender.borrow())
+    }
+
+    /// 订阅配置变化
+    pub fn subscribe(&self) -> ConfigReceiver {
+        self.sender.subscribe()
+    }
+
+    /// 重新加载配置，返回发生变化的配置项
+    ///
+    /// 配置无效或者修改了不能热加载的配置项时返回错误，继续使用原有配置
+    pub fn reload(&self) -> Result<Vec<&'static str>> {
+        self.apply(AppConfig::load_source(&self.overrides)?)
+    }
+
+    /// 使用新的配置项替换当前配置，返回发生变化的配置项，没有变化时不会通知订阅者
+    pub fn apply(&self, source: ConfigSource) -> Result<Vec<&'static str>> {
+        let config = AppConfig::from_source_checked(&source)?;
+
+        let mut current = self.source.lock().unwrap_or_else(PoisonError::into_inner);
+        let changed = current.changed_keys(&source);
+
+        let restart_required: Vec<_> = changed.iter().filter(|key| !RELOADABLE_KEYS.contains(key)).copied().collect();
+        if !restart_required.is_empty() {
+            return Err(eyre!("Can not reload {} without restart", restart_required.join(", ")))
+                .suggestion("恢复这些配置项后重新加载，或者重启服务使其生效");
+        }
+
+        if !changed.is_empty() {
+            *current = source;
+            self.sender.send_replace(Arc::new(config));
+        }
+
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::config::CronConfig;
+    use crate::models::config_source::Origin;
+
+    fn source(values: &[(&str, &str)]) -> ConfigSource {
+        let mut source = ConfigSource::default();
+        for (key, value) in [("database.url", "postgres://localhost/db"), ("redis.url", "redis://127.0.0.1/")]
+            .iter()
+            .chain(values)
+        {
+            source.set(key, value.to_string(), Origin::Cli).unwrap();
+        }
+        source
+    }
+
+    #[test]
+    fn test_apply() {
+        let reloader = ConfigReloader::from_source(source(&[]), ConfigOverrides::default()).unwrap();
+        let mut config_rx = reloader.subscribe();
+
+        assert!(reloader.apply(source(&[])).unwrap().is_empty());
+        assert!(!config_rx.has_changed().unwrap());
+
+        let changed = reloader
+            .apply(source(&[
+                ("consumer.handler_concurrency", "8"),
+                ("log.filter", "info,database=debug"),
+            ]))
+            .unwrap();
+        assert_eq!(changed, ["log.filter", "consumer.handler_concurrency"]);
+        assert!(config_rx.has_changed().unwrap());
+        assert_eq!(config_rx.borrow_and_update().consumer.handler_concurrency, 8);
+
+        // 只要有一个配置项需要重启，整个修改都不生效
+        let err = reloader
+            .apply(source(&[
+                ("consumer.handler_concurrency", "16"),
+                ("web.listen_addr", "0.0.0.0:9090"),
+            ]))
+            .unwrap_err();
+        assert!(err.to_string().contains("web.listen_addr"), "{err}");
+        assert!(!err.to_string().contains("handler_concurrency"), "{err}");
+        assert_eq!(reloader.current().consumer.handler_concurrency, 8);
+
+        // 无效的配置也不会生效
+        assert!(reloader.apply(source(&[("consumer.read_count", "many")])).is_err());
+        assert!(reloader.apply(source(&[("cron.rebalance_schedule", "*/10 * * *")])).is_err());
+        assert!(reloader.apply(source(&[("search.similarity_threshold", "1.5")])).is_err());
+        assert_eq!(reloader.current().cron.rebalance_schedule, CronConfig::default().rebalance_schedule);
+        assert!(!config_rx.has_changed().unwrap());
+    }
+}
@@ -0,0 +1,58 @@
+//! 语言
+//!
+//! 接口返回的错误信息和参数校验信息会根据请求的 `Accept-Language` 使用对应的语言
+
+use color_eyre::eyre::eyre;
+use color_eyre::{Report, Result};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    /// 英语（美国）
+    #[default]
+    EnUs,
+
+    /// 简体中文
+    ZhCn,
+}
+
+impl Locale {
+    /// 所有支持的语言
+    pub const ALL: [Locale; 2] = [Locale::EnUs, Locale::ZhCn];
+
+    /// BCP 47 语言标签，例如 `zh-CN`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::EnUs => "en-US",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+
+    /// 根据语言标签匹配支持的语言，不区分大小写
+    ///
+    /// 只比较主语言，例如 `en-GB` 匹配 `en-US`，`zh`、`zh-Hans` 匹配 `zh-CN`
+    pub fn from_language_tag(tag: &str) -> Option<Locale> {
+        let language = tag.trim().split(['-', '_']).next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Some(Locale::EnUs),
+            "zh" => Some(Locale::ZhCn),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Locale::from_language_tag(s).ok_or_else(|| eyre!("Unsupported locale `{s}`, expected `en-US` or `zh-CN`"))
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
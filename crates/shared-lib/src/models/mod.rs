@@ -1,10 +1,24 @@
 pub mod config;
+pub mod config_reload;
+pub mod config_source;
+pub mod locale;
 pub mod redis_constants;
 pub mod redis_task;
+pub mod secret;
 pub mod tasks;
+pub mod tenant;
 
 // 重新导出具体的类型
-pub use config::{AppConfig, RedisConfig};
+pub use config::{
+    AppConfig, AuthConfig, ConsumerConfig, CronConfig, DatabaseConfig, GrpcConfig, ListenAddr, LogConfig, LogFileConfig, LogFormat,
+    MiddlewareConfig, RedisConfig, RestartPolicy, Role, Roles, S3Config, ShutdownConfig, StartupConfig, StorageBackend, StorageConfig,
+    SupervisorConfig, TlsConfig, WebConfig,
+};
+pub use config_reload::{ConfigReceiver, ConfigReloader};
+pub use config_source::{ConfigOverrides, ConfigSource};
+pub use locale::Locale;
 pub use redis_constants::*;
 pub use redis_task::RedisConsumerHeartBeat;
+pub use secret::Secret;
 pub use tasks::TaskInfo;
+pub use tenant::TenantId;
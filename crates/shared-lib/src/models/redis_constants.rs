@@ -2,6 +2,9 @@
 //!
 //! 统一管理所有Redis相关的键名、配置常量等，
 //! 确保整个系统中使用的Redis键名保持一致。
+//!
+//! 这些常量是 [`ConsumerConfig`](crate::models::config::ConsumerConfig) 的默认值，运行时使用配置中的值，
+//! 可以通过配置文件或环境变量中的 `consumer.*` 配置项调整。
 
 /// Redis消费者心跳存储键
 ///
@@ -0,0 +1,59 @@
+//! 租户模型
+//!
+//! 每个请求都属于一个租户，数据库通过行级安全策略（RLS）按租户隔离数据。
+//! 租户由调用方的访问令牌确定，参考 [`crate::models::config::AuthConfig`]。
+
+use color_eyre::eyre::eyre;
+use color_eyre::{Report, Result};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// 租户ID的最大长度，与 `tenant_id varchar(64)` 保持一致
+const MAX_TENANT_ID_LEN: usize = 64;
+
+/// 租户ID
+///
+/// 只能包含字母、数字、`_` 和 `-`，长度为1~64
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TenantId(String);
+
+impl TenantId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for TenantId {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.is_empty() || s.len() > MAX_TENANT_ID_LEN {
+            return Err(eyre!("Tenant id `{s}` must be 1 to {MAX_TENANT_ID_LEN} characters"));
+        }
+        if !s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-') {
+            return Err(eyre!("Tenant id `{s}` can only contain letters, digits, `_` and `-`"));
+        }
+        Ok(TenantId(s.to_string()))
+    }
+}
+
+impl Display for TenantId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tenant_id() {
+        assert_eq!("acme-01_a".parse::<TenantId>().unwrap().as_str(), "acme-01_a");
+
+        assert!("".parse::<TenantId>().is_err());
+        assert!("a b".parse::<TenantId>().is_err());
+        assert!("a'b".parse::<TenantId>().is_err());
+        assert!("a".repeat(65).parse::<TenantId>().is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! 🛑 关闭信号
+//!
+//! 程序入口通过 `watch::Sender<bool>` 广播关闭信号，值变为 `true` 表示需要关闭，
+//! 各个服务（Web、gRPC、消费者、定时任务）都使用 [`wait_for_shutdown`] 等待这个信号。
+
+use tokio::sync::watch::Receiver;
+
+/// 等待关闭信号，发送端被销毁时也视为关闭
+///
+/// 在 `select!` 中反复调用也不会错过信号：已经是 `true` 时立即返回。
+pub async fn wait_for_shutdown(shutdown_rx: &mut Receiver<bool>) {
+    while !*shutdown_rx.borrow_and_update() {
+        if shutdown_rx.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::sync::watch;
+
+    #[tokio::test]
+    async fn test_wait_for_shutdown() {
+        // 已经关闭时立即返回，反复等待也一样
+        let (tx, mut rx) = watch::channel(true);
+        wait_for_shutdown(&mut rx).await;
+        wait_for_shutdown(&mut rx).await;
+        drop(tx);
+
+        // 发送端被销毁视为关闭
+        let (tx, mut rx) = watch::channel(false);
+        drop(tx);
+        wait_for_shutdown(&mut rx).await;
+
+        // 还没有关闭时一直等待
+        let (tx, mut rx) = watch::channel(false);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), wait_for_shutdown(&mut rx))
+                .await
+                .is_err()
+        );
+        tx.send(true).unwrap();
+        wait_for_shutdown(&mut rx).await;
+    }
+}
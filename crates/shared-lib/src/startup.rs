@@ -0,0 +1,143 @@
+//! 🔌 启动时等待依赖服务
+//!
+//! 使用docker-compose、Kubernetes部署时，数据库和Redis可能比程序晚启动或者暂时不可用。
+//! 连接失败后按 [`StartupConfig`] 的退避时间重试并记录日志，超过 `startup.timeout_secs` 后返回最后一次的错误。
+
+use crate::models::config::{AppConfig, StartupConfig};
+use color_eyre::Result;
+use color_eyre::eyre::Context;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use std::fmt::Display;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// 重试直到成功，或者超过 `startup.timeout_secs`
+///
+/// `what` 描述正在做的事情，例如 `连接Redis`，用于日志
+pub async fn retry<T, E, F, Fut>(what: &str, config: &StartupConfig, attempt: F) -> Result<T, E>
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_when(what, config, |_| true, attempt).await
+}
+
+/// 与 [`retry`] 相同，但只重试 `retryable` 返回 `true` 的错误（例如连接失败），其他错误直接返回
+pub async fn retry_when<T, E, R, F, Fut>(what: &str, config: &StartupConfig, retryable: R, mut attempt: F) -> Result<T, E>
+where
+    E: Display,
+    R: Fn(&E) -> bool,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let deadline = Instant::now() + config.timeout;
+    let mut backoff = config.initial_backoff;
+    let mut retries = 0;
+
+    loop {
+        let err = match attempt().await {
+            Ok(value) => {
+                if retries > 0 {
+                    info!("✅ {what}成功，重试了 {retries} 次");
+                }
+                return Ok(value);
+            }
+            Err(err) if retryable(&err) => err,
+            Err(err) => return Err(err),
+        };
+
+        // 下次重试会超过总的等待时间，直接放弃
+        if Instant::now() + backoff > deadline {
+            if !config.timeout.is_zero() {
+                warn!("⏰ {what}失败，超过 {:?} 后放弃: {err}", config.timeout);
+            }
+            return Err(err);
+        }
+
+        retries += 1;
+        warn!("🔁 {what}失败，{backoff:?} 后第 {retries} 次重试: {err}");
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(config.max_backoff);
+    }
+}
+
+/// 创建Redis连接管理器，Redis暂时不可用时按 `startup` 配置重试
+///
+/// 连接管理器自身不重试，运行期间连接断开后在下一次执行命令时重新连接
+pub async fn connect_redis(config: &AppConfig) -> Result<ConnectionManager> {
+    let client = redis::Client::open(config.redis.redis_conn_str.expose()).context("Invalid redis.url")?;
+    let manager_config = ConnectionManagerConfig::new()
+        .set_number_of_retries(0)
+        .set_connection_timeout(config.redis.connect_timeout);
+
+    let conn = retry("连接Redis", &config.startup, || {
+        ConnectionManager::new_with_config(client.clone(), manager_config.clone())
+    })
+    .await
+    .context("Failed to connect to redis")?;
+
+    Ok(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn startup_config(timeout: Duration) -> StartupConfig {
+        StartupConfig {
+            timeout,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(20),
+            degraded: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        // 前两次失败，第三次成功
+        let mut attempts = 0;
+        let result: Result<u32, String> = retry("test", &startup_config(Duration::from_secs(1)), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move { if attempt < 3 { Err("refused".to_string()) } else { Ok(attempt) } }
+        })
+        .await;
+        assert_eq!(result, Ok(3));
+
+        // 超过总的等待时间后返回最后一次的错误
+        let mut attempts = 0;
+        let result: Result<(), String> = retry("test", &startup_config(Duration::from_millis(50)), || {
+            attempts += 1;
+            let attempt = attempts;
+            async move { Err(format!("refused {attempt}")) }
+        })
+        .await;
+        assert!((2..=5).contains(&attempts));
+        assert_eq!(result, Err(format!("refused {attempts}")));
+
+        // 不重试
+        let mut attempts = 0;
+        let result: Result<(), &str> = retry("test", &startup_config(Duration::ZERO), || {
+            attempts += 1;
+            async { Err("refused") }
+        })
+        .await;
+        assert_eq!((attempts, result), (1, Err("refused")));
+
+        // 不可重试的错误直接返回
+        let mut attempts = 0;
+        let result: Result<(), &str> = retry_when(
+            "test",
+            &startup_config(Duration::from_secs(1)),
+            |err| *err == "refused",
+            || {
+                attempts += 1;
+                async { Err("syntax error") }
+            },
+        )
+        .await;
+        assert_eq!((attempts, result), (1, Err("syntax error")));
+    }
+}
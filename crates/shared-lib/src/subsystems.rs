@@ -0,0 +1,127 @@
+//! 🧩 子系统运行状态
+//!
+//! 程序入口的监督器负责启动、重启各个子系统（Web、gRPC、消费者、定时任务等），并把状态记录到 [`Subsystems`] 中，
+//! 运维接口 `/health` 读取这些状态，方便查看哪个子系统正在重启、重启了多少次以及最后一次的错误。
+
+use crate::models::config::RestartPolicy;
+use color_eyre::Report;
+use serde::Serialize;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// 子系统的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubsystemState {
+    /// 正在等待依赖的服务（例如降级启动时等待数据库），还不能处理请求
+    Starting,
+    /// 正在运行
+    Running,
+    /// 异常退出，等待重启
+    Restarting,
+    /// 正常结束（例如收到了退出信号）
+    Stopped,
+    /// 异常退出，不会再重启
+    Failed,
+}
+
+/// 单个子系统的状态
+#[derive(Debug, Clone, Serialize)]
+pub struct SubsystemStatus {
+    /// 子系统名称
+    pub name: &'static str,
+
+    /// 当前状态
+    pub state: SubsystemState,
+
+    /// 重启策略
+    pub restart_policy: RestartPolicy,
+
+    /// 已经重启的次数
+    pub restarts: u32,
+
+    /// 最后一次异常退出的错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// 所有子系统的状态，克隆后共享同一份数据
+#[derive(Debug, Clone, Default)]
+pub struct Subsystems {
+    statuses: Arc<RwLock<Vec<SubsystemStatus>>>,
+}
+
+impl Subsystems {
+    /// 记录新启动的子系统
+    pub fn register(&self, name: &'static str, restart_policy: RestartPolicy) {
+        let mut statuses = self.statuses.write().unwrap_or_else(PoisonError::into_inner);
+        statuses.retain(|status| status.name != name);
+        statuses.push(SubsystemStatus {
+            name,
+            state: SubsystemState::Running,
+            restart_policy,
+            restarts: 0,
+            last_error: None,
+        });
+    }
+
+    /// 子系统正在等待依赖的服务
+    pub fn starting(&self, name: &str) {
+        self.update(name, |status| status.state = SubsystemState::Starting);
+    }
+
+    /// 子系统开始运行
+    pub fn running(&self, name: &str) {
+        self.update(name, |status| status.state = SubsystemState::Running);
+    }
+
+    /// 子系统异常退出，等待重启，返回这是第几次重启
+    pub fn restarting(&self, name: &str, err: &Report) -> u32 {
+        let mut restarts = 0;
+        self.update(name, |status| {
+            status.state = SubsystemState::Restarting;
+            status.restarts += 1;
+            status.last_error = Some(error_message(err));
+            restarts = status.restarts;
+        });
+        restarts
+    }
+
+    /// 子系统正常结束
+    pub fn stopped(&self, name: &str) {
+        self.update(name, |status| status.state = SubsystemState::Stopped);
+    }
+
+    /// 子系统异常退出，不会再重启
+    pub fn failed(&self, name: &str, err: &Report) {
+        self.update(name, |status| {
+            status.state = SubsystemState::Failed;
+            status.last_error = Some(error_message(err));
+        });
+    }
+
+    /// 正在等待依赖服务的子系统
+    pub fn starting_subsystem(&self) -> Option<&'static str> {
+        let statuses = self.statuses.read().unwrap_or_else(PoisonError::into_inner);
+        statuses
+            .iter()
+            .find(|status| status.state == SubsystemState::Starting)
+            .map(|status| status.name)
+    }
+
+    /// 所有子系统的状态，按启动顺序排列
+    pub fn snapshot(&self) -> Vec<SubsystemStatus> {
+        self.statuses.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    fn update(&self, name: &str, update: impl FnOnce(&mut SubsystemStatus)) {
+        let mut statuses = self.statuses.write().unwrap_or_else(PoisonError::into_inner);
+        if let Some(status) = statuses.iter_mut().find(|status| status.name == name) {
+            update(status);
+        }
+    }
+}
+
+/// 错误信息，包含完整的错误链，不包含错误位置等调试信息
+fn error_message(err: &Report) -> String {
+    err.chain().map(ToString::to_string).collect::<Vec<_>>().join(": ")
+}
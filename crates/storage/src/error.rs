@@ -0,0 +1,29 @@
+use thiserror::Error;
+
+/// 附件存储错误类型
+#[derive(Error, Debug)]
+pub enum StorageError {
+    /// 对象不存在
+    #[error("对象不存在: {0}")]
+    NotFound(String),
+
+    /// 对象路径不合法，例如包含 `..`
+    #[error("非法的对象路径: {0}")]
+    InvalidKey(String),
+
+    /// 存储配置错误，例如对象存储地址不合法
+    #[error("存储配置错误: {0}")]
+    InvalidConfig(String),
+
+    /// 本地文件读写错误
+    #[error("存储读写错误: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// 请求对象存储失败，例如连接失败、超时
+    #[error("请求对象存储失败: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// 对象存储返回了错误的状态码
+    #[error("对象存储返回错误 {status}: {message}")]
+    Service { status: u16, message: String },
+}
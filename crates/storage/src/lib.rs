@@ -0,0 +1,113 @@
+//! 附件存储模块
+//!
+//! 通过 [`Storage`] trait 屏蔽不同的存储后端，业务代码只关心对象的路径（key）：
+//! - [`LocalStorage`]：本地文件系统，适合单机部署和本地开发
+//! - [`S3Storage`]：S3兼容的对象存储（AWS S3、MinIO等），适合多实例部署
+//! - [`MemoryStorage`]：内存实现，主要用于测试
+//!
+//! 附件的元数据（文件名、大小、sha256等）保存在数据库中，这里只负责内容的读写。
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use shared_lib::models::config::{StorageBackend, StorageConfig};
+use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::Arc;
+
+pub mod error;
+pub mod local;
+pub mod memory;
+pub mod s3;
+mod sigv4;
+
+pub use error::StorageError;
+pub use local::LocalStorage;
+pub use memory::MemoryStorage;
+pub use s3::S3Storage;
+
+/// 存储操作结果类型
+pub type StorageResult<T> = Result<T, StorageError>;
+
+/// 对象内容的字节流
+pub type ByteStream = BoxStream<'static, std::io::Result<Bytes>>;
+
+/// 写入对象时的元数据
+#[derive(Debug, Clone)]
+pub struct ObjectMeta {
+    /// 内容类型，例如 `application/pdf`
+    pub content_type: String,
+
+    /// 内容长度（字节）
+    pub content_length: u64,
+
+    /// 内容的sha256（小写十六进制），S3后端会用它校验上传的内容
+    pub sha256: String,
+}
+
+/// 附件存储trait定义
+///
+/// 对象路径由业务层生成，只包含 `[A-Za-z0-9._-]` 和 `/`，不能以 `/` 开头，也不能包含 `..`
+#[async_trait::async_trait]
+pub trait Storage: Debug + Send + Sync + 'static {
+    /// 写入对象，对象已经存在时覆盖
+    ///
+    /// # 参数
+    /// - `key`: 对象路径
+    /// - `meta`: 对象元数据，`content_length` 必须与内容的实际长度一致
+    /// - `body`: 对象内容
+    async fn put(&self, key: &str, meta: ObjectMeta, body: ByteStream) -> StorageResult<()>;
+
+    /// 读取对象内容
+    ///
+    /// # 参数
+    /// - `key`: 对象路径
+    /// - `range`: 读取的字节范围 `[start, end)`，为 `None` 时读取全部内容。调用方需要保证范围不超过对象长度
+    ///
+    /// # 返回值
+    /// 返回对象内容的字节流，对象不存在时返回 [`StorageError::NotFound`]
+    async fn get(&self, key: &str, range: Option<Range<u64>>) -> StorageResult<ByteStream>;
+
+    /// 删除对象，对象不存在时不做任何操作
+    ///
+    /// # 参数
+    /// - `key`: 对象路径
+    async fn delete(&self, key: &str) -> StorageResult<()>;
+}
+
+/// 根据配置创建存储后端
+pub fn from_config(config: &StorageConfig) -> StorageResult<Arc<dyn Storage>> {
+    match &config.backend {
+        StorageBackend::Local { root } => Ok(Arc::new(LocalStorage::new(root))),
+        StorageBackend::S3(s3_config) => Ok(Arc::new(S3Storage::new(s3_config.clone())?)),
+    }
+}
+
+/// 检查对象路径是否合法
+pub(crate) fn validate_key(key: &str) -> StorageResult<()> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && key
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("projects/1/3f2a.bin").is_ok());
+
+        for key in ["", "/etc/passwd", "projects/../secret", "projects//1", "projects/1/a b", "./a"] {
+            assert!(validate_key(key).is_err(), "{key}");
+        }
+    }
+}
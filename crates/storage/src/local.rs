@@ -0,0 +1,133 @@
+//! 本地文件系统存储
+//!
+//! 对象保存在根目录下与对象路径相同的文件中，例如 `projects/1/abc` 对应 `<root>/projects/1/abc`。
+//! 写入时先写到同目录下的临时文件，完成后再重命名，读取方不会看到写了一半的文件。
+
+use crate::{ByteStream, ObjectMeta, Storage, StorageError, StorageResult, validate_key};
+use futures::{StreamExt, TryStreamExt};
+use std::io::{ErrorKind, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+use tracing::debug;
+
+/// 本地文件系统存储
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    /// 创建本地存储，根目录在第一次写入时自动创建
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> StorageResult<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, meta: ObjectMeta, mut body: ByteStream) -> StorageResult<()> {
+        let path = self.path(key)?;
+        debug!("💾 写入本地文件 {} ({} 字节)", path.display(), meta.content_length);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let temp_path = temp_path(&path);
+        let result = async {
+            let mut file = File::create(&temp_path).await?;
+            while let Some(chunk) = body.try_next().await? {
+                file.write_all(&chunk).await?;
+            }
+            file.sync_all().await?;
+            fs::rename(&temp_path, &path).await
+        }
+        .await;
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path).await;
+        }
+
+        Ok(result?)
+    }
+
+    async fn get(&self, key: &str, range: Option<Range<u64>>) -> StorageResult<ByteStream> {
+        let path = self.path(key)?;
+
+        let mut file = File::open(&path).await.map_err(|err| match err.kind() {
+            ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+            _ => StorageError::Io(err),
+        })?;
+
+        match range {
+            Some(range) => {
+                file.seek(SeekFrom::Start(range.start)).await?;
+                Ok(ReaderStream::new(file.take(range.end - range.start)).boxed())
+            }
+            None => Ok(ReaderStream::new(file).boxed()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        let path = self.path(key)?;
+        debug!("🗑️ 删除本地文件 {}", path.display());
+
+        match fs::remove_file(&path).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 同目录下的临时文件路径，保证重命名不会跨文件系统
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::stream;
+
+    fn body(chunks: &'static [&'static str]) -> ByteStream {
+        stream::iter(chunks.iter().map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())))).boxed()
+    }
+
+    async fn read(stream: ByteStream) -> String {
+        let chunks: Vec<Bytes> = stream.try_collect().await.unwrap();
+        String::from_utf8(chunks.concat()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_local_storage() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(root.path());
+        let meta = ObjectMeta {
+            content_type: "text/plain".to_string(),
+            content_length: 11,
+            sha256: String::new(),
+        };
+
+        storage.put("projects/1/a", meta, body(&["hello", " world"])).await.unwrap();
+        assert_eq!(read(storage.get("projects/1/a", None).await.unwrap()).await, "hello world");
+        assert_eq!(read(storage.get("projects/1/a", Some(6..9)).await.unwrap()).await, "wor");
+
+        // 只留下最终文件，没有临时文件
+        assert_eq!(std::fs::read_dir(root.path().join("projects/1")).unwrap().count(), 1);
+
+        storage.delete("projects/1/a").await.unwrap();
+        storage.delete("projects/1/a").await.unwrap();
+        assert!(matches!(storage.get("projects/1/a", None).await, Err(StorageError::NotFound(_))));
+    }
+}
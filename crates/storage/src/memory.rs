@@ -0,0 +1,73 @@
+//! 内存存储
+//!
+//! [`Storage`] 的内存实现，不需要文件系统和对象存储，主要用于测试。
+
+use crate::{ByteStream, ObjectMeta, Storage, StorageError, StorageResult, validate_key};
+use bytes::{Bytes, BytesMut};
+use futures::{StreamExt, TryStreamExt, stream};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// 内存存储
+///
+/// 内部使用 [`Arc`] 共享数据，`clone` 之后的实例操作的是同一份数据
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    objects: Arc<Mutex<BTreeMap<String, Bytes>>>,
+}
+
+impl MemoryStorage {
+    /// 创建空的内存存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前所有对象的路径，按路径排序
+    pub fn keys(&self) -> Vec<String> {
+        self.lock().keys().cloned().collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, Bytes>> {
+        self.objects.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for MemoryStorage {
+    async fn put(&self, key: &str, _meta: ObjectMeta, body: ByteStream) -> StorageResult<()> {
+        validate_key(key)?;
+
+        let content = body
+            .try_fold(BytesMut::new(), |mut content, chunk| async move {
+                content.extend_from_slice(&chunk);
+                Ok(content)
+            })
+            .await?;
+        self.lock().insert(key.to_string(), content.freeze());
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str, range: Option<Range<u64>>) -> StorageResult<ByteStream> {
+        validate_key(key)?;
+
+        let content = self
+            .lock()
+            .get(key)
+            .cloned()
+            .ok_or_else(|| StorageError::NotFound(key.to_string()))?;
+        let content = match range {
+            Some(range) => content.slice(range.start as usize..range.end as usize),
+            None => content,
+        };
+
+        Ok(stream::once(async move { Ok(content) }).boxed())
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        validate_key(key)?;
+        self.lock().remove(key);
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+//! S3兼容对象存储
+//!
+//! 直接通过HTTP请求访问S3 API（PutObject、GetObject、DeleteObject），使用SigV4签名，
+//! 支持AWS S3和MinIO等兼容实现。上传时使用调用方计算好的sha256作为 `x-amz-content-sha256`，
+//! 服务端会校验内容，传输过程中内容损坏时上传失败。
+
+use crate::sigv4::{self, Credentials, EMPTY_SHA256, SignRequest};
+use crate::{ByteStream, ObjectMeta, Storage, StorageError, StorageResult, validate_key};
+use chrono::Utc;
+use futures::{StreamExt, TryStreamExt};
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, RANGE};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
+use shared_lib::models::config::S3Config;
+use std::fmt::{Debug, Formatter};
+use std::ops::Range;
+use std::time::Duration;
+use tracing::debug;
+
+/// 错误响应内容最多保留的长度
+const MAX_ERROR_MESSAGE_LEN: usize = 512;
+
+/// S3兼容对象存储
+#[derive(Clone)]
+pub struct S3Storage {
+    client: Client,
+    config: S3Config,
+    /// 存储桶的基础地址，对象地址为 `<base>/<key>`
+    base: Url,
+}
+
+/// 手动实现Debug，避免在日志中输出密钥
+impl Debug for S3Storage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Storage")
+            .field("base", &self.base.as_str())
+            .field("region", &self.config.region)
+            .finish_non_exhaustive()
+    }
+}
+
+impl S3Storage {
+    /// 根据配置创建S3存储，不会检查存储桶是否存在
+    pub fn new(config: S3Config) -> StorageResult<Self> {
+        let invalid = |reason: &str| StorageError::InvalidConfig(format!("非法的S3_ENDPOINT `{}`: {reason}", config.endpoint));
+
+        let mut base = Url::parse(&config.endpoint).map_err(|err| invalid(&err.to_string()))?;
+        if !matches!(base.scheme(), "http" | "https") || base.host_str().is_none() {
+            return Err(invalid("只支持 http 和 https 地址"));
+        }
+
+        if config.path_style {
+            base.path_segments_mut()
+                .map_err(|_| invalid("不能作为基础地址"))?
+                .pop_if_empty()
+                .push(&config.bucket);
+        } else {
+            let host = format!("{}.{}", config.bucket, base.host_str().unwrap_or_default());
+            base.set_host(Some(&host)).map_err(|err| invalid(&err.to_string()))?;
+        }
+
+        let client = Client::builder().connect_timeout(Duration::from_secs(10)).build()?;
+
+        Ok(Self { client, config, base })
+    }
+
+    /// 创建签名后的请求
+    fn request(&self, method: Method, key: &str, headers: &[(&str, String)], payload_sha256: &str) -> StorageResult<RequestBuilder> {
+        validate_key(key)?;
+
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .map_err(|_| StorageError::InvalidKey(key.to_string()))?
+            .pop_if_empty()
+            .extend(key.split('/'));
+
+        // 与reqwest发送的Host请求头保持一致，默认端口不带端口号
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let path = format!("{}/{key}", self.base.path().trim_end_matches('/'));
+
+        let credentials = Credentials {
+            access_key_id: self.config.access_key_id.expose(),
+            secret_access_key: self.config.secret_access_key.expose(),
+            region: &self.config.region,
+        };
+        let sign_request = SignRequest {
+            method: method.as_str(),
+            host: &host,
+            path: &path,
+            headers,
+            payload_sha256,
+        };
+
+        let signed_headers = sigv4::sign(&credentials, &sign_request, Utc::now());
+
+        let mut builder = self.client.request(method.clone(), url);
+        for (name, value) in headers.iter().chain(signed_headers.iter()) {
+            builder = builder.header(*name, value);
+        }
+
+        Ok(builder)
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, meta: ObjectMeta, body: ByteStream) -> StorageResult<()> {
+        debug!("☁️ 上传对象 {key} ({} 字节)", meta.content_length);
+
+        let response = self
+            .request(Method::PUT, key, &[], &meta.sha256)?
+            .header(CONTENT_TYPE, meta.content_type)
+            .header(CONTENT_LENGTH, meta.content_length)
+            .body(reqwest::Body::wrap_stream(body))
+            .send()
+            .await?;
+        check_status(response).await?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str, range: Option<Range<u64>>) -> StorageResult<ByteStream> {
+        let headers: Vec<(&str, String)> = range
+            .map(|range| (RANGE.as_str(), format!("bytes={}-{}", range.start, range.end.saturating_sub(1))))
+            .into_iter()
+            .collect();
+
+        let response = self.request(Method::GET, key, &headers, EMPTY_SHA256)?.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(StorageError::NotFound(key.to_string()));
+        }
+        let response = check_status(response).await?;
+
+        Ok(response.bytes_stream().map_err(std::io::Error::other).boxed())
+    }
+
+    async fn delete(&self, key: &str) -> StorageResult<()> {
+        debug!("🗑️ 删除对象 {key}");
+
+        let response = self.request(Method::DELETE, key, &[], EMPTY_SHA256)?.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        check_status(response).await?;
+
+        Ok(())
+    }
+}
+
+/// 检查响应状态码，失败时读取响应内容作为错误信息
+async fn check_status(response: Response) -> StorageResult<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let message = response.text().await.unwrap_or_default();
+    let message = message.chars().take(MAX_ERROR_MESSAGE_LEN).collect();
+
+    Err(StorageError::Service {
+        status: status.as_u16(),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use futures::stream;
+    use sha2::{Digest, Sha256};
+    use shared_lib::models::secret::Secret;
+
+    /// 需要可访问的S3兼容存储，例如 `db_helper` 中的MinIO：
+    ///
+    /// ```bash
+    /// S3_ENDPOINT=http://localhost:9000 S3_BUCKET=attachments S3_ACCESS_KEY_ID=minioadmin \
+    ///   S3_SECRET_ACCESS_KEY=minioadmin cargo test -p storage -- --ignored
+    /// ```
+    #[tokio::test]
+    #[ignore = "需要S3兼容存储"]
+    async fn test_s3_storage() {
+        let env = |name: &str| std::env::var(name).unwrap_or_else(|_| panic!("{name} is not set"));
+        let storage = S3Storage::new(S3Config {
+            endpoint: env("S3_ENDPOINT"),
+            region: std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            bucket: env("S3_BUCKET"),
+            access_key_id: env("S3_ACCESS_KEY_ID").into(),
+            secret_access_key: env("S3_SECRET_ACCESS_KEY").into(),
+            path_style: true,
+        })
+        .unwrap();
+
+        let key = format!("test/{}", uuid::Uuid::new_v4());
+        let content = "hello world";
+        let meta = ObjectMeta {
+            content_type: "text/plain".to_string(),
+            content_length: content.len() as u64,
+            sha256: hex::encode(Sha256::digest(content)),
+        };
+        let body = stream::iter(["hello", " world"].map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())))).boxed();
+        storage.put(&key, meta, body).await.unwrap();
+
+        let read = |range| {
+            let storage = storage.clone();
+            let key = key.clone();
+            async move {
+                let chunks: Vec<Bytes> = storage.get(&key, range).await.unwrap().try_collect().await.unwrap();
+                String::from_utf8(chunks.concat()).unwrap()
+            }
+        };
+        assert_eq!(read(None).await, "hello world");
+        assert_eq!(read(Some(6..9)).await, "wor");
+
+        storage.delete(&key).await.unwrap();
+        storage.delete(&key).await.unwrap();
+        assert!(matches!(storage.get(&key, None).await, Err(StorageError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_object_url() {
+        let config = S3Config {
+            endpoint: "http://localhost:9000".to_string(),
+            region: "us-east-1".to_string(),
+            bucket: "attachments".to_string(),
+            access_key_id: Secret::new("minioadmin"),
+            secret_access_key: Secret::new("minioadmin"),
+            path_style: true,
+        };
+        let storage = S3Storage::new(config.clone()).unwrap();
+        let request = storage
+            .request(Method::GET, "projects/1/a", &[], EMPTY_SHA256)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(request.url().as_str(), "http://localhost:9000/attachments/projects/1/a");
+
+        let storage = S3Storage::new(S3Config {
+            endpoint: "https://s3.us-east-1.amazonaws.com".to_string(),
+            path_style: false,
+            ..config
+        })
+        .unwrap();
+        let request = storage
+            .request(Method::GET, "projects/1/a", &[], EMPTY_SHA256)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "https://attachments.s3.us-east-1.amazonaws.com/projects/1/a"
+        );
+        assert!(request.headers().contains_key("authorization"));
+    }
+}
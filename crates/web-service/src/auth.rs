@@ -0,0 +1,87 @@
+//! 调用方认证
+//!
+//! 根据请求头 `Authorization: Bearer <token>` 确定调用方所属的租户，令牌配置参考
+//! [`shared_lib::models::config::AuthConfig`]。业务接口通过 [`Tenant`] 提取器获取租户，
+//! 再调用 [`ProjectServiceTrait::with_tenant`] 得到只能访问该租户数据的服务实例。
+
+use crate::models::err::AppError;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use shared_lib::models::config::AuthConfig;
+use shared_lib::models::tenant::TenantId;
+use std::sync::Arc;
+
+/// 当前请求所属的租户
+///
+/// 令牌无效或者没有携带令牌（且没有配置默认租户）时返回401
+#[derive(Debug, Clone)]
+pub struct Tenant(pub TenantId);
+
+impl<S> FromRequestParts<S> for Tenant
+where
+    S: Send + Sync,
+    Arc<AuthConfig>: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let authorization = match parts.headers.get(AUTHORIZATION) {
+            Some(value) => Some(value.to_str().map_err(|_| AppError::Unauthorized)?),
+            None => None,
+        };
+
+        Arc::<AuthConfig>::from_ref(state)
+            .resolve_tenant(authorization)
+            .map(Tenant)
+            .ok_or(AppError::Unauthorized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::TestApp;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode, header};
+    use database::{ProjectCreate, ProjectRepositoryTrait};
+
+    fn get(uri: &str, authorization: Option<&str>) -> Request<Body> {
+        let mut request = Request::get(uri);
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized() {
+        let app = TestApp::new();
+
+        let response = app.anonymous_request(get("/api/v2/tags", None)).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers[header::WWW_AUTHENTICATE], "Bearer");
+
+        let response = app.anonymous_request(get("/api/v2/tags", Some("Bearer invalid"))).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+        assert_eq!(app.get("/api/v2/tags").await.status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_isolation() {
+        let app = TestApp::new();
+        let other = app.repository.with_tenant(&"other".parse().unwrap());
+        let project = other
+            .create_project(ProjectCreate {
+                project_name: "pay".to_string(),
+                comment: String::new(),
+                parent_id: None,
+            })
+            .await
+            .unwrap();
+
+        let response = app.get(&format!("/api/v2/projects/{}", project.id)).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+        assert!(other.get_project_by_id(project.id).await.is_ok());
+    }
+}
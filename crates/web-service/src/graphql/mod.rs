@@ -0,0 +1,182 @@
+//! GraphQL接口
+//!
+//! 基于 [`async_graphql`] 实现，与REST接口共用同一个 [`ProjectServiceTrait`]，只负责参数和返回值的转换。
+//!
+//! - `POST /graphql`: 执行GraphQL请求
+//! - `GET /graphql`: GraphiQL在线调试页面
+//!
+//! 路由挂载在业务路由上，与REST接口共用同一套中间件（请求体大小限制、超时、CORS等）。
+//! 与REST接口一样通过 [`Tenant`] 确定请求所属的租户，所有查询和修改只能访问该租户的数据。
+//! 错误统一转换为 [`AppError`]，错误信息和状态码与REST接口保持一致，状态码放在错误的 `extensions` 中。
+
+use crate::auth::Tenant;
+use crate::models::err::AppError;
+use crate::{AppState, services::ProjectServiceTrait};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{EmptySubscription, ErrorExtensions, Schema};
+use axum::extract::{FromRef, State};
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+use axum::{Json, Router};
+use shared_lib::models::config::AuthConfig;
+use std::sync::Arc;
+
+mod projects;
+
+pub use projects::{ProjectLoader, ProjectMutation, ProjectPage, ProjectQuery};
+
+/// GraphQL接口地址
+pub const GRAPHQL_PATH: &str = "/graphql";
+
+/// 查询的最大嵌套深度，防止恶意构造的深层查询
+const MAX_QUERY_DEPTH: usize = 16;
+
+/// 当前App的GraphQL Schema
+pub type AppSchema<PS> = Schema<ProjectQuery<PS>, ProjectMutation<PS>, EmptySubscription>;
+
+/// GraphQL路由的共享数据
+#[derive(Clone)]
+struct GraphQLState<PS: ProjectServiceTrait> {
+    schema: AppSchema<PS>,
+    project_service: Arc<PS>,
+    auth: Arc<AuthConfig>,
+}
+
+impl<PS: ProjectServiceTrait> FromRef<GraphQLState<PS>> for Arc<AuthConfig> {
+    fn from_ref(state: &GraphQLState<PS>) -> Self {
+        Arc::clone(&state.auth)
+    }
+}
+
+/// 创建GraphQL Schema
+pub fn schema<PS: ProjectServiceTrait>(project_service: Arc<PS>) -> AppSchema<PS> {
+    Schema::build(
+        ProjectQuery::new(Arc::clone(&project_service)),
+        ProjectMutation::new(project_service),
+        EmptySubscription,
+    )
+    .limit_depth(MAX_QUERY_DEPTH)
+    .finish()
+}
+
+/// 导出GraphQL路由
+pub fn routers<PS: ProjectServiceTrait>(state: AppState<PS>) -> Router {
+    let state = GraphQLState {
+        schema: schema(Arc::clone(&state.project_service)),
+        project_service: state.project_service,
+        auth: state.auth,
+    };
+
+    Router::new()
+        .route(GRAPHQL_PATH, get(graphiql).post(execute::<PS>))
+        .with_state(state)
+}
+
+/// 执行GraphQL请求
+///
+/// 每个请求使用独立的 [`DataLoader`]，同一个请求中的多次项目查询会被合并为一次批量查询，
+/// 缓存也只在当前请求内有效，不会读到其他请求修改前的数据，也不会读到其他租户的数据。
+async fn execute<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<GraphQLState<PS>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    let project_service = Arc::new(state.project_service.with_tenant(&tenant));
+    let loader = DataLoader::new(ProjectLoader::new(project_service), tokio::spawn);
+
+    Json(state.schema.execute(request.data(loader).data(tenant)).await)
+}
+
+/// GraphiQL在线调试页面
+async fn graphiql() -> impl IntoResponse {
+    Html(
+        GraphiQLSource::build()
+            .endpoint(GRAPHQL_PATH)
+            .title("rust-backend GraphQL")
+            .finish(),
+    )
+}
+
+/// 将 [`AppError`] 转换为GraphQL错误
+///
+/// `extensions` 中包含：
+/// - `code`: 状态码名称，例如 `NOT_FOUND`
+/// - `status`: 对应的http状态码，例如 `404`
+impl ErrorExtensions for AppError {
+    fn extend(&self) -> async_graphql::Error {
+        self.log();
+        let status = self.status_code();
+        let code = status.canonical_reason().unwrap_or("UNKNOWN").to_uppercase().replace(' ', "_");
+
+        async_graphql::Error::new(self.message()).extend_with(|_, extensions| {
+            extensions.set("code", code);
+            extensions.set("status", status.as_u16());
+        })
+    }
+}
+
+/// 将服务层错误转换为GraphQL错误
+fn graphql_error(err: impl Into<AppError>) -> async_graphql::Error {
+    err.into().extend()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::TestApp;
+    use axum::http::StatusCode;
+    use serde_json::{Value, json};
+
+    async fn execute(app: &TestApp, query: &str) -> Value {
+        let response = app.post_json("/graphql", &json!({ "query": query })).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+        response.json()
+    }
+
+    #[tokio::test]
+    async fn test_project_crud() {
+        let app = TestApp::new();
+
+        let created = execute(
+            &app,
+            r#"mutation { createProject(input: {projectName: "pay", comment: "comment"}) { id projectName } }"#,
+        )
+        .await;
+        let id = created["data"]["createProject"]["id"].as_i64().unwrap();
+
+        let updated = execute(
+            &app,
+            &format!(r#"mutation {{ updateProject(id: {id}, input: {{comment: "updated"}}) {{ comment }} }}"#),
+        )
+        .await;
+        assert_eq!(updated["data"]["updateProject"]["comment"], "updated");
+
+        // 同一个请求中的多次查询通过DataLoader批量加载，不存在的项目返回null
+        let fetched = execute(
+            &app,
+            &format!("{{ a: project(id: {id}) {{ projectName }} b: project(id: 404) {{ id }} }}"),
+        )
+        .await;
+        assert_eq!(fetched["data"]["a"]["projectName"], "pay");
+        assert_eq!(fetched["data"]["b"], Value::Null);
+
+        let page = execute(&app, r#"{ projects(projectName: "pa", pageSize: 10) { total data { id } } }"#).await;
+        assert_eq!(page["data"]["projects"]["total"], 1);
+
+        execute(&app, &format!("mutation {{ deleteProject(id: {id}) {{ id }} }}")).await;
+        let deleted = execute(&app, &format!("mutation {{ deleteProject(id: {id}) {{ id }} }}")).await;
+        assert_eq!(deleted["errors"][0]["extensions"]["code"], "NOT_FOUND");
+        assert_eq!(deleted["errors"][0]["extensions"]["status"], 404);
+    }
+
+    #[tokio::test]
+    async fn test_validation_failed() {
+        let app = TestApp::new();
+
+        let result = execute(&app, "{ projects(pageSize: 1000) { total } }").await;
+        assert_eq!(result["errors"][0]["extensions"]["code"], "BAD_REQUEST");
+        assert!(result["errors"][0]["message"].as_str().unwrap().starts_with("Validate failed"));
+
+        assert_eq!(app.get("/graphql").await.status, StatusCode::OK);
+    }
+}
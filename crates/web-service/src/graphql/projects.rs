@@ -0,0 +1,190 @@
+//! 项目相关的GraphQL查询和修改
+
+use crate::graphql::graphql_error;
+use crate::models::common::PageQuery;
+use crate::models::err::AppError;
+use crate::models::projects::{ProjectCreate, ProjectDetail, ProjectSearch, ProjectUpdate};
+use crate::services::ProjectServiceTrait;
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{Context, ErrorExtensions, Object, Result, SimpleObject};
+use shared_lib::models::tenant::TenantId;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::debug;
+use validator::Validate;
+
+/// 项目分页查询结果
+#[derive(SimpleObject)]
+pub struct ProjectPage {
+    /// 分页查询总数
+    pub total: u32,
+
+    /// 分页查询的开始页数
+    pub page_index: u32,
+
+    /// 分页查询的每页大小
+    pub page_size: u32,
+
+    /// 当前页的项目
+    pub data: Vec<ProjectDetail>,
+}
+
+/// 当前请求所属租户的服务实例，租户由GraphQL路由放入请求数据中
+fn tenant_service<PS: ProjectServiceTrait>(project_service: &PS, ctx: &Context<'_>) -> Result<PS> {
+    Ok(project_service.with_tenant(ctx.data::<TenantId>()?))
+}
+
+/// 按ID批量加载项目
+///
+/// 同一个请求中的多次 `project(id)` 查询会被合并，只调用一次 [`ProjectServiceTrait::get_projects_by_ids`]。
+/// 每个请求单独创建，使用的服务实例已经指定了当前请求的租户
+pub struct ProjectLoader<PS: ProjectServiceTrait> {
+    project_service: Arc<PS>,
+}
+
+impl<PS: ProjectServiceTrait> ProjectLoader<PS> {
+    pub fn new(project_service: Arc<PS>) -> Self {
+        Self { project_service }
+    }
+}
+
+impl<PS: ProjectServiceTrait> Loader<i32> for ProjectLoader<PS> {
+    type Value = ProjectDetail;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, ids: &[i32]) -> Result<HashMap<i32, Self::Value>, Self::Error> {
+        debug!("🔍 批量加载项目(GraphQL) {:?}", ids);
+
+        let projects = self
+            .project_service
+            .get_projects_by_ids(ids)
+            .await
+            .map_err(|err| Arc::new(AppError::from(err)))?;
+
+        Ok(projects.into_iter().map(|project| (project.id, project.into())).collect())
+    }
+}
+
+/// 项目查询
+pub struct ProjectQuery<PS: ProjectServiceTrait> {
+    project_service: Arc<PS>,
+}
+
+impl<PS: ProjectServiceTrait> ProjectQuery<PS> {
+    pub fn new(project_service: Arc<PS>) -> Self {
+        Self { project_service }
+    }
+}
+
+#[Object(name = "Query")]
+impl<PS: ProjectServiceTrait> ProjectQuery<PS> {
+    /// 根据项目名称（模糊搜索）和标签搜索项目
+    ///
+    /// - `any_tags`: 至少包含其中一个标签
+    /// - `all_tags`: 包含所有标签
+    async fn projects(
+        &self,
+        ctx: &Context<'_>,
+        project_name: Option<String>,
+        #[graphql(default)] any_tags: Vec<String>,
+        #[graphql(default)] all_tags: Vec<String>,
+        #[graphql(default = 1)] page_index: u32,
+        #[graphql(default = 20)] page_size: u32,
+    ) -> Result<ProjectPage> {
+        let search = ProjectSearch {
+            project_name,
+            any_tags,
+            all_tags,
+            page_query: PageQuery { page_index, page_size },
+        };
+        debug!("🔍 搜索项目(GraphQL) {:#?}", search);
+
+        search.validate().map_err(graphql_error)?;
+
+        let offset = (page_index.saturating_sub(1)) * page_size;
+        let result = tenant_service(self.project_service.as_ref(), ctx)?
+            .find_projects(search.filter(), page_size as i64, offset as i64)
+            .await
+            .map_err(graphql_error)?;
+
+        Ok(ProjectPage {
+            total: result.total,
+            page_index,
+            page_size,
+            data: result.projects.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// 查询指定项目信息，项目不存在时返回null
+    async fn project(&self, ctx: &Context<'_>, id: i32) -> Result<Option<ProjectDetail>> {
+        let loader = ctx.data::<DataLoader<ProjectLoader<PS>>>()?;
+        loader.load_one(id).await.map_err(|err| err.extend())
+    }
+
+    /// 批量查询项目信息，按传入的ID顺序返回，不存在的项目会被忽略
+    async fn projects_by_ids(&self, ctx: &Context<'_>, ids: Vec<i32>) -> Result<Vec<ProjectDetail>> {
+        let loader = ctx.data::<DataLoader<ProjectLoader<PS>>>()?;
+        let mut projects = loader.load_many(ids.iter().copied()).await.map_err(|err| err.extend())?;
+
+        Ok(ids.iter().filter_map(|id| projects.remove(id)).collect())
+    }
+}
+
+/// 项目修改
+pub struct ProjectMutation<PS: ProjectServiceTrait> {
+    project_service: Arc<PS>,
+}
+
+impl<PS: ProjectServiceTrait> ProjectMutation<PS> {
+    pub fn new(project_service: Arc<PS>) -> Self {
+        Self { project_service }
+    }
+}
+
+#[Object(name = "Mutation")]
+impl<PS: ProjectServiceTrait> ProjectMutation<PS> {
+    /// 创建项目
+    async fn create_project(&self, ctx: &Context<'_>, input: ProjectCreate) -> Result<ProjectDetail> {
+        debug!("Creating project(GraphQL) {:#?}", input);
+
+        let project = database::models::ProjectCreate {
+            project_name: input.project_name,
+            comment: input.comment,
+            parent_id: input.parent_id,
+        };
+        let project = tenant_service(self.project_service.as_ref(), ctx)?
+            .create_project(project)
+            .await
+            .map_err(graphql_error)?;
+
+        Ok(project.into())
+    }
+
+    /// 更新项目信息，未填写的字段保持不变
+    async fn update_project(&self, ctx: &Context<'_>, id: i32, input: ProjectUpdate) -> Result<ProjectDetail> {
+        debug!("Updating project(GraphQL) {} {:#?}", id, input);
+
+        let update = database::models::ProjectUpdate {
+            project_name: input.project_name,
+            comment: input.comment,
+        };
+        let project = tenant_service(self.project_service.as_ref(), ctx)?
+            .update_project(id, update)
+            .await
+            .map_err(graphql_error)?;
+
+        Ok(project.into())
+    }
+
+    /// 删除指定的项目
+    async fn delete_project(&self, ctx: &Context<'_>, id: i32) -> Result<ProjectDetail> {
+        debug!("Deleting project(GraphQL) {}", id);
+
+        let project = tenant_service(self.project_service.as_ref(), ctx)?
+            .delete_project(id)
+            .await
+            .map_err(graphql_error)?;
+
+        Ok(project.into())
+    }
+}
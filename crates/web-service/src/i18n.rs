@@ -0,0 +1,249 @@
+//! 错误信息的多语言支持
+//!
+//! 业务路由的最外层中间件根据请求的 `Accept-Language` 协商语言（参考 [`negotiate`]），
+//! 没有支持的语言时使用配置的默认语言（[`MiddlewareConfig::default_locale`](shared_lib::models::config::MiddlewareConfig)）。
+//! 协商结果保存在 task-local 变量中，[`AppError`](crate::models::err::AppError) 转换为响应时通过 [`current_locale`] 读取，
+//! 所以handler、提取器和内层中间件返回的错误都会使用协商的语言。
+//!
+//! 所有文案都在 [`Catalogue`] 中定义，增加语言时只需要增加一份 [`Catalogue`] 并在 [`catalogue`] 中返回。
+
+use axum::extract::{Request, State};
+use axum::http::header::ACCEPT_LANGUAGE;
+use axum::middleware::Next;
+use axum::response::Response;
+use shared_lib::models::locale::Locale;
+use std::borrow::Cow;
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+tokio::task_local! {
+    /// 当前请求协商的语言
+    static LOCALE: Locale;
+}
+
+/// 当前请求协商的语言，不在业务路由中（例如单独调用handler）时返回 [`Locale::default`]
+pub fn current_locale() -> Locale {
+    LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+/// 协商语言的中间件
+pub async fn localize(State(default_locale): State<Locale>, request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(negotiate)
+        .unwrap_or(default_locale);
+
+    LOCALE.scope(locale, next.run(request)).await
+}
+
+/// 根据 `Accept-Language` 请求头选择支持的语言
+///
+/// 按权重（`q`）从高到低依次匹配，权重相同时保持请求头中的顺序，`q=0` 表示不接受该语言。
+/// 都不支持时返回 `None`，`*` 也返回 `None`，由调用方使用默认语言
+pub fn negotiate(accept_language: &str) -> Option<Locale> {
+    let mut candidates: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    candidates.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    candidates.into_iter().find_map(|(tag, _)| Locale::from_language_tag(tag))
+}
+
+/// 一种语言的全部文案
+///
+/// 参数校验文案中的 `{min}`、`{max}`、`{equal}`、`{code}` 会被替换为实际的值
+pub struct Catalogue {
+    pub validation_failed: &'static str,
+    pub resource_not_found: &'static str,
+    pub record_not_found: &'static str,
+    pub already_exists: &'static str,
+    /// 参数为 `{id}` 和 `{parent_id}`
+    pub invalid_hierarchy: &'static str,
+    pub database_error: &'static str,
+    pub repository_error: &'static str,
+    pub redis_error: &'static str,
+    pub storage_error: &'static str,
+    pub invalid_multipart: &'static str,
+    pub unauthorized: &'static str,
+    pub timeout: &'static str,
+    pub overloaded: &'static str,
+    pub internal_error: &'static str,
+
+    pub field_required: &'static str,
+    pub length_between: &'static str,
+    pub length_min: &'static str,
+    pub length_max: &'static str,
+    pub length_equal: &'static str,
+    pub range_between: &'static str,
+    pub range_min: &'static str,
+    pub range_max: &'static str,
+    pub field_invalid: &'static str,
+}
+
+const EN_US: Catalogue = Catalogue {
+    validation_failed: "Validate failed",
+    resource_not_found: "Can not found resource",
+    record_not_found: "Record not found",
+    already_exists: "Resource already exists",
+    invalid_hierarchy: "Invalid project hierarchy: project {id} can not be moved under itself or its descendant {parent_id}",
+    database_error: "Database error",
+    repository_error: "Repository error",
+    redis_error: "Redis error",
+    storage_error: "Storage error",
+    invalid_multipart: "Invalid multipart request",
+    unauthorized: "Missing or invalid access token",
+    timeout: "Request timed out",
+    overloaded: "Service is overloaded, please retry later",
+    internal_error: "Something went wrong",
+
+    field_required: "is required",
+    length_between: "length must be between {min} and {max}",
+    length_min: "length must be at least {min}",
+    length_max: "length must be at most {max}",
+    length_equal: "length must be {equal}",
+    range_between: "must be between {min} and {max}",
+    range_min: "must be at least {min}",
+    range_max: "must be at most {max}",
+    field_invalid: "is invalid ({code})",
+};
+
+const ZH_CN: Catalogue = Catalogue {
+    validation_failed: "参数校验失败",
+    resource_not_found: "资源不存在",
+    record_not_found: "记录不存在",
+    already_exists: "资源已存在",
+    invalid_hierarchy: "项目层级错误：项目 {id} 不能移动到自身或其子项目 {parent_id} 下",
+    database_error: "数据库错误",
+    repository_error: "数据访问错误",
+    redis_error: "Redis错误",
+    storage_error: "附件存储错误",
+    invalid_multipart: "multipart请求格式错误",
+    unauthorized: "缺少访问令牌或令牌无效",
+    timeout: "请求处理超时",
+    overloaded: "服务繁忙，请稍后重试",
+    internal_error: "服务内部错误",
+
+    field_required: "不能为空",
+    length_between: "长度必须在 {min} 到 {max} 之间",
+    length_min: "长度不能小于 {min}",
+    length_max: "长度不能超过 {max}",
+    length_equal: "长度必须为 {equal}",
+    range_between: "必须在 {min} 到 {max} 之间",
+    range_min: "不能小于 {min}",
+    range_max: "不能大于 {max}",
+    field_invalid: "格式不正确（{code}）",
+};
+
+/// 指定语言的文案
+pub fn catalogue(locale: Locale) -> &'static Catalogue {
+    match locale {
+        Locale::EnUs => &EN_US,
+        Locale::ZhCn => &ZH_CN,
+    }
+}
+
+/// 将参数校验错误转换为指定语言的文本，例如 `page_query.page_size: must be between 1 and 100`
+///
+/// 嵌套结构体的字段使用 `.` 连接，列表元素使用 `[下标]`，多个错误使用 `; ` 分隔，按字段名排序。
+/// 校验规则中指定了 `message` 时直接使用该文本
+pub fn validation_message(errors: &ValidationErrors, locale: Locale) -> String {
+    let mut messages = Vec::new();
+    collect_field_errors(errors, "", catalogue(locale), &mut messages);
+    messages.sort();
+    messages.join("; ")
+}
+
+fn collect_field_errors(errors: &ValidationErrors, prefix: &str, catalogue: &Catalogue, messages: &mut Vec<String>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{prefix}.{field}")
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                messages.extend(field_errors.iter().map(|err| format!("{path}: {}", field_error(err, catalogue))));
+            }
+            ValidationErrorsKind::Struct(nested) => collect_field_errors(nested, &path, catalogue, messages),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(nested, &format!("{path}[{index}]"), catalogue, messages);
+                }
+            }
+        }
+    }
+}
+
+fn field_error(err: &ValidationError, catalogue: &Catalogue) -> String {
+    if let Some(message) = &err.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| err.params.get(name).map(|value| value.to_string());
+    let (min, max, equal) = (param("min"), param("max"), param("equal"));
+
+    let template = match (err.code.as_ref(), &min, &max, &equal) {
+        ("required", ..) => catalogue.field_required,
+        ("length", _, _, Some(_)) => catalogue.length_equal,
+        ("length", Some(_), Some(_), _) => catalogue.length_between,
+        ("length", Some(_), None, _) => catalogue.length_min,
+        ("length", None, Some(_), _) => catalogue.length_max,
+        ("range", Some(_), Some(_), _) => catalogue.range_between,
+        ("range", Some(_), None, _) => catalogue.range_min,
+        ("range", None, Some(_), _) => catalogue.range_max,
+        _ => catalogue.field_invalid,
+    };
+
+    let mut message = Cow::Borrowed(template);
+    for (name, value) in [("{min}", min), ("{max}", max), ("{equal}", equal)] {
+        if let Some(value) = value {
+            message = Cow::Owned(message.replace(name, &value));
+        }
+    }
+    message.replace("{code}", &err.code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::PageQuery;
+    use validator::Validate;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate("zh-CN,zh;q=0.9,en;q=0.8"), Some(Locale::ZhCn));
+        assert_eq!(negotiate("fr-FR, en-GB;q=0.5, zh;q=0.7"), Some(Locale::ZhCn));
+        assert_eq!(negotiate("zh;q=0, en"), Some(Locale::EnUs));
+        assert_eq!(negotiate("fr, *;q=0.1"), None);
+        assert_eq!(negotiate(""), None);
+    }
+
+    #[test]
+    fn test_validation_message() {
+        let page = PageQuery {
+            page_index: 0,
+            page_size: 1000,
+        };
+        let errors = page.validate().unwrap_err();
+
+        assert_eq!(
+            validation_message(&errors, Locale::EnUs),
+            "page_index: must be at least 1; page_size: must be between 1 and 100"
+        );
+        assert_eq!(
+            validation_message(&errors, Locale::ZhCn),
+            "page_index: 不能小于 1; page_size: 必须在 1 到 100 之间"
+        );
+    }
+}
@@ -3,45 +3,124 @@
 //! 提供 HTTP API 接口和文档服务
 
 use color_eyre::Result;
-use sqlx::{Pool, Postgres};
+use database::DatabasePool;
+use metrics_exporter_prometheus::PrometheusHandle;
+use shared_lib::logging::LogFilter;
+use shared_lib::models::config::AuthConfig;
+use shared_lib::models::config_reload::{ConfigReceiver, ConfigReloader};
+use shared_lib::shutdown::wait_for_shutdown;
+use shared_lib::subsystems::Subsystems;
 use std::sync::Arc;
 use tokio::sync::watch::Receiver;
-use tracing::info;
+use tokio::try_join;
 
+pub mod auth;
+pub mod graphql;
+pub mod i18n;
+pub mod metrics;
+pub mod middleware;
 pub mod models;
 pub mod routes;
+pub mod server;
 pub mod services;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 
+use middleware::{Deprecation, LoadLimits};
+use server::ListenerConfig;
 use services::{ProjectService, ProjectServiceTrait};
 
 /// 应用共享状态
 #[derive(Debug, Clone)]
 pub struct AppState<PS: ProjectServiceTrait> {
+    /// 项目服务，处理请求时需要通过 [`ProjectServiceTrait::with_tenant`] 指定租户
     pub project_service: Arc<PS>,
+
+    /// 访问令牌与租户的映射，参考 [`auth::Tenant`]
+    pub auth: Arc<AuthConfig>,
+
+    /// 监控指标句柄，运维路由通过它导出指标
+    pub metrics: PrometheusHandle,
+
+    /// 附件上传请求的最大字节数，参考 [`shared_lib::models::config::StorageConfig`]
+    pub max_upload_size: usize,
+
+    /// v1中已经有v2替代的接口的弃用信息，参考 [`shared_lib::models::config::WebConfig::v1_sunset`]
+    pub v1_deprecation: Deprecation,
+
+    /// 配置热加载器，运维接口通过它重新加载配置
+    pub reloader: Arc<ConfigReloader>,
+
+    /// 日志过滤规则，运维接口通过它临时调整日志级别
+    pub log_filter: Arc<LogFilter>,
+
+    /// 各个子系统的运行状态，存活探针返回这些状态
+    pub subsystems: Subsystems,
+
+    /// 程序开始退出的信号，收到信号后就绪探针返回503，但仍然正常处理请求，直到收到关闭信号
+    pub draining_rx: Receiver<bool>,
+}
+
+impl<PS: ProjectServiceTrait> axum::extract::FromRef<AppState<PS>> for Arc<AuthConfig> {
+    fn from_ref(state: &AppState<PS>) -> Self {
+        Arc::clone(&state.auth)
+    }
 }
 
 /// 启动 Web 服务
-pub async fn start_web_service(pool: Pool<Postgres>, mut shutdown_rx: Receiver<bool>) -> Result<()> {
+///
+/// 同时启动两个监听器，共享同一份 [`AppState`] 和关闭信号：
+/// - 业务监听器：提供 `/api/*` 接口和 `/graphql` 接口
+/// - 运维监听器：提供文档、健康检查、监控指标和管理接口
+///
+/// 监听地址、TLS和HTTP/2等参数参考 [`shared_lib::models::config::WebConfig`]，
+/// 其中业务接口的并发请求数上限和请求超时时间支持热加载，其他参数修改后需要重启
+///
+/// 程序退出时先收到 `draining_rx` 信号，就绪探针开始返回503，让负载均衡摘除流量；
+/// 之后收到 `shutdown_rx` 信号，停止接收新连接并等待处理中的请求完成
+pub async fn start_web_service(
+    reloader: Arc<ConfigReloader>,
+    log_filter: Arc<LogFilter>,
+    pool: DatabasePool,
+    subsystems: Subsystems,
+    draining_rx: Receiver<bool>,
+    shutdown_rx: Receiver<bool>,
+) -> Result<()> {
+    let app_config = reloader.current();
     let project_repository = database::ProjectRepository::new(pool.clone());
-    let project_service = ProjectService::new(project_repository);
+    let storage = storage::from_config(&app_config.storage)?;
+    let project_service = ProjectService::new(project_repository, storage);
 
     let shared_state = AppState {
         project_service: Arc::new(project_service),
+        auth: Arc::new(app_config.auth.clone()),
+        metrics: metrics::prometheus_handle(),
+        max_upload_size: app_config.storage.max_upload_size,
+        v1_deprecation: Deprecation::new(app_config.web.v1_deprecated_at, app_config.web.v1_sunset),
+        reloader: Arc::clone(&reloader),
+        log_filter,
+        subsystems,
+        draining_rx,
     };
 
-    let router = routes::create_app_router(shared_state);
-
-    let bind_addr = "0.0.0.0:8080";
-    info!("🚀 启动 Web Service 在 {}", bind_addr);
+    let routers = routes::create_app_routers(shared_state, &app_config.web.middleware)?;
 
-    let listener = tokio::net::TcpListener::bind(bind_addr).await?;
-
-    axum::serve(listener, router.into_make_service())
-        .with_graceful_shutdown(async move {
-            shutdown_rx.changed().await.expect("Failed to receive shutdown signal");
-            info!("🛑 Web Service 正在关闭...");
-        })
-        .await?;
+    // 任意一个监听器启动失败都会直接返回错误，由上层触发整个程序退出
+    try_join!(
+        server::serve(routers.public, ListenerConfig::public(&app_config.web), shutdown_rx.clone()),
+        server::serve(routers.admin, ListenerConfig::admin(&app_config.web), shutdown_rx.clone()),
+        follow_load_limits(reloader.subscribe(), routers.load_limits, shutdown_rx),
+    )?;
 
     Ok(())
 }
+
+/// 配置变化时更新业务接口的负载保护参数，收到关闭信号后退出
+async fn follow_load_limits(mut config: ConfigReceiver, load_limits: Arc<LoadLimits>, mut shutdown_rx: Receiver<bool>) -> Result<()> {
+    loop {
+        tokio::select! {
+            _ = wait_for_shutdown(&mut shutdown_rx) => return Ok(()),
+            Ok(()) = config.changed() => load_limits.update(&config.borrow_and_update().web.middleware),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! HTTP监控指标
+//!
+//! 使用 [`metrics`] 记录请求指标，由运维监听器的 `/metrics` 接口以Prometheus文本格式导出。
+//!
+//! 记录的指标：
+//! - `http_requests_total`: 请求总数，按 `method`、`path`、`status` 区分
+//! - `http_request_duration_seconds`: 请求耗时分布，按 `method`、`path` 区分
+
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::LazyLock;
+use std::time::Instant;
+use tracing::warn;
+
+/// 请求总数指标名称
+const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+
+/// 请求耗时指标名称
+const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
+
+/// 请求耗时的分桶（秒）
+const DURATION_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// 全局的Prometheus recorder，只能安装一次
+static PROMETHEUS: LazyLock<PrometheusHandle> = LazyLock::new(|| {
+    let recorder = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Full(HTTP_REQUEST_DURATION_SECONDS.to_string()), DURATION_BUCKETS)
+        .expect("duration buckets should not be empty")
+        .build_recorder();
+    let handle = recorder.handle();
+
+    if metrics::set_global_recorder(recorder).is_err() {
+        warn!("Global metrics recorder is already installed, /metrics will not export any data");
+    }
+
+    handle
+});
+
+/// 获取Prometheus指标句柄，第一次调用时安装全局recorder
+pub fn prometheus_handle() -> PrometheusHandle {
+    PROMETHEUS.clone()
+}
+
+/// 记录HTTP请求指标的中间件
+///
+/// `path` 使用路由模板（例如 `/api/v1/projects/{id}`），避免路径参数导致指标数量膨胀。
+/// 未匹配到路由的请求统一记为 `unmatched`。
+pub async fn track_http_metrics(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| "unmatched".to_string(), |path| path.as_str().to_string());
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    metrics::counter!(HTTP_REQUESTS_TOTAL, "method" => method.clone(), "path" => path.clone(), "status" => status).increment(1);
+    metrics::histogram!(HTTP_REQUEST_DURATION_SECONDS, "method" => method, "path" => path).record(start.elapsed().as_secs_f64());
+
+    response
+}
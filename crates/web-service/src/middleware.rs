@@ -0,0 +1,392 @@
+//! 业务接口的中间件
+//!
+//! 根据 [`MiddlewareConfig`] 为业务路由添加以下中间件（从外到内）：
+//! 1. 语言协商：根据 `Accept-Language` 确定错误信息的语言，参考 [`i18n`](crate::i18n)
+//! 2. CORS：只允许白名单中的来源跨域访问
+//! 3. 负载保护：并发请求数超过上限时直接返回503，单个请求处理超时返回504，参数都支持热加载，参考 [`LoadLimits`]。
+//!    附件上传接口在路由中通过 [`upload_timeout`] 使用单独的超时时间
+//! 4. 压缩：响应压缩（gzip/br/zstd）和请求解压
+//! 5. 请求体大小限制：限制的是解压后的大小，避免压缩炸弹。附件上传接口在路由中单独设置了更大的限制
+//!
+//! 另外提供 [`Deprecation`]，为已弃用的接口添加 `Deprecation` 和 `Sunset` 响应头。
+
+use crate::i18n;
+use crate::models::err::AppError;
+use axum::Router;
+use axum::extract::{DefaultBodyLimit, Request, State};
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::{DateTime, NaiveDate, Utc};
+use color_eyre::Result;
+use color_eyre::eyre::Context;
+use shared_lib::models::config::MiddlewareConfig;
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tower_http::compression::CompressionLayer;
+use tower_http::compression::predicate::{DefaultPredicate, Predicate};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::decompression::RequestDecompressionLayer;
+
+/// 浏览器缓存CORS预检结果的时间
+const CORS_MAX_AGE: Duration = Duration::from_secs(3600);
+
+/// 为路由添加中间件
+///
+/// `load_limits` 为负载保护的参数，需要在配置热加载后调用 [`LoadLimits::update`] 更新
+pub fn apply(router: Router, config: &MiddlewareConfig, load_limits: Arc<LoadLimits>) -> Result<Router> {
+    // 使用axum的限制，在提取请求体时生效，解压中间件在外层，所以限制的是解压后的大小。
+    // 不使用tower-http的RequestBodyLimitLayer，因为内层路由无法再放宽它的限制，附件上传接口需要更大的限制
+    let mut router = router.layer(DefaultBodyLimit::max(config.max_body_size));
+
+    if config.compression {
+        // 支持断点续传的响应（带有Accept-Ranges）不压缩，否则Range请求的偏移量与压缩后的内容对不上
+        let compression = CompressionLayer::new().compress_when(DefaultPredicate::new().and(NotForRangeResponses));
+        router = router.layer(RequestDecompressionLayer::new()).layer(compression);
+    }
+
+    // Router::layer会为每个路由单独创建中间件，所有路由共享同一个LoadLimits，所以并发限制是全局的
+    let router = router.layer(axum::middleware::from_fn_with_state(load_limits, load_protection));
+
+    let router = match cors_layer(&config.cors_allowed_origins)? {
+        Some(cors) => router.layer(cors),
+        None => router,
+    };
+
+    // 放在最外层，内层中间件返回的错误（例如超时、过载）也使用协商的语言
+    Ok(router.layer(axum::middleware::from_fn_with_state(config.default_locale, i18n::localize)))
+}
+
+/// 压缩条件：不压缩带有 `Accept-Ranges` 的响应（例如附件下载）
+///
+/// tower-http默认已经跳过了带有 `Content-Range` 的部分内容响应，这里把完整内容的响应也排除掉，
+/// 否则客户端拿到压缩后的内容和长度，之后的Range请求无法续传
+#[derive(Debug, Clone, Copy)]
+struct NotForRangeResponses;
+
+impl Predicate for NotForRangeResponses {
+    fn should_compress<B>(&self, response: &axum::http::Response<B>) -> bool
+    where
+        B: axum::body::HttpBody,
+    {
+        !response.headers().contains_key(axum::http::header::ACCEPT_RANGES)
+    }
+}
+
+/// 根据白名单创建CORS中间件，白名单为空时不启用
+fn cors_layer(allowed_origins: &[String]) -> Result<Option<CorsLayer>> {
+    if allowed_origins.is_empty() {
+        return Ok(None);
+    }
+
+    let allow_origin = if allowed_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = allowed_origins
+            .iter()
+            .map(|origin| HeaderValue::from_str(origin).with_context(|| format!("Invalid CORS origin `{origin}`")))
+            .collect::<Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods(Any)
+            .allow_headers(Any)
+            .max_age(CORS_MAX_AGE),
+    ))
+}
+
+/// 负载保护的参数，可以在运行时调整
+///
+/// - 并发请求数上限：使用信号量实现，调大时立即生效；调小时正在处理的请求不受影响，请求结束后才回收多余的许可，
+///   回收完成之前再次调大时先抵消还没有回收的许可
+/// - 请求超时时间、上传超时时间：对修改之后收到的请求生效
+#[derive(Debug)]
+pub struct LoadLimits {
+    semaphore: Arc<Semaphore>,
+
+    /// 当前的并发请求数上限和还没有回收的许可，同时用来保证多次调整依次执行
+    concurrency: Arc<Mutex<ConcurrencyLimit>>,
+
+    /// 请求超时时间（毫秒）
+    request_timeout_ms: AtomicU64,
+
+    /// 上传超时时间（毫秒）
+    upload_timeout_ms: AtomicU64,
+}
+
+impl LoadLimits {
+    pub fn new(config: &MiddlewareConfig) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(config.max_concurrent_requests)),
+            concurrency: Arc::new(Mutex::new(ConcurrencyLimit {
+                max: config.max_concurrent_requests,
+                pending_reclaim: 0,
+                reclaiming: false,
+            })),
+            request_timeout_ms: AtomicU64::new(config.request_timeout.as_millis() as u64),
+            upload_timeout_ms: AtomicU64::new(config.upload_timeout.as_millis() as u64),
+        }
+    }
+
+    /// 使用新的配置调整并发请求数上限和超时时间
+    pub fn update(&self, config: &MiddlewareConfig) {
+        self.request_timeout_ms
+            .store(config.request_timeout.as_millis() as u64, AtomicOrdering::Relaxed);
+        self.upload_timeout_ms
+            .store(config.upload_timeout.as_millis() as u64, AtomicOrdering::Relaxed);
+
+        let mut limit = self.concurrency.lock().unwrap_or_else(PoisonError::into_inner);
+        let target = config.max_concurrent_requests;
+        match target.cmp(&limit.max) {
+            Ordering::Greater => {
+                // 先抵消还没有回收的许可，否则回收任务会把新增的许可也回收掉
+                let added = target - limit.max;
+                let cancelled = added.min(limit.pending_reclaim);
+                limit.pending_reclaim -= cancelled;
+                self.semaphore.add_permits(added - cancelled);
+            }
+            Ordering::Less => {
+                let excess = limit.max - target;
+                let forgotten = self.semaphore.forget_permits(excess);
+                // 剩下的许可被正在处理的请求占用，等请求结束后再回收
+                limit.pending_reclaim += excess - forgotten;
+                if limit.pending_reclaim > 0 && !limit.reclaiming {
+                    limit.reclaiming = true;
+                    tokio::spawn(reclaim_permits(Arc::clone(&self.semaphore), Arc::clone(&self.concurrency)));
+                }
+            }
+            Ordering::Equal => {}
+        }
+        limit.max = target;
+    }
+
+    /// 当前的请求超时时间
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms.load(AtomicOrdering::Relaxed))
+    }
+
+    /// 当前的上传超时时间
+    pub fn upload_timeout(&self) -> Duration {
+        Duration::from_millis(self.upload_timeout_ms.load(AtomicOrdering::Relaxed))
+    }
+}
+
+/// 并发请求数上限
+#[derive(Debug)]
+struct ConcurrencyLimit {
+    /// 当前的上限
+    max: usize,
+
+    /// 调小上限时被正在处理的请求占用、还需要回收的许可数
+    pending_reclaim: usize,
+
+    /// 是否有回收任务正在运行
+    reclaiming: bool,
+}
+
+/// 逐个回收请求结束后释放的许可，直到没有需要回收的许可
+///
+/// 等待期间上限被调大时 `pending_reclaim` 会减少，多拿到的许可直接归还
+async fn reclaim_permits(semaphore: Arc<Semaphore>, concurrency: Arc<Mutex<ConcurrencyLimit>>) {
+    loop {
+        let Ok(permit) = semaphore.acquire().await else {
+            return;
+        };
+        let mut limit = concurrency.lock().unwrap_or_else(PoisonError::into_inner);
+        if limit.pending_reclaim > 0 {
+            permit.forget();
+            limit.pending_reclaim -= 1;
+        }
+        if limit.pending_reclaim == 0 {
+            limit.reclaiming = false;
+            return;
+        }
+    }
+}
+
+/// 负载保护：没有空闲的许可时直接返回503，处理超时返回504
+async fn load_protection(State(limits): State<Arc<LoadLimits>>, mut request: Request, next: Next) -> Result<Response, AppError> {
+    let Ok(_permit) = limits.semaphore.try_acquire() else {
+        return Err(AppError::Overloaded);
+    };
+
+    let started = Instant::now();
+    let (request_timeout, upload_timeout) = (limits.request_timeout(), limits.upload_timeout());
+    let is_upload = UploadRequest::default();
+    request.extensions_mut().insert(is_upload.clone());
+
+    let response = next.run(request);
+    tokio::pin!(response);
+    loop {
+        let timeout = if is_upload.get() { upload_timeout } else { request_timeout };
+        tokio::select! {
+            response = &mut response => return Ok(response),
+            // 处理过程中内层路由可能切换为上传超时时间，到期后重新检查
+            _ = tokio::time::sleep_until(started + timeout) => {
+                if !is_upload.get() || timeout == upload_timeout {
+                    return Err(AppError::Timeout);
+                }
+            }
+        }
+    }
+}
+
+/// 请求是否使用上传超时时间，由负载保护中间件添加，内层路由通过 [`upload_timeout`] 设置
+#[derive(Debug, Clone, Default)]
+struct UploadRequest(Arc<AtomicBool>);
+
+impl UploadRequest {
+    fn get(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
+    }
+}
+
+/// 使用上传超时时间（`web.middleware.upload_timeout_secs`）代替请求超时时间
+///
+/// 附件上传等需要长时间接收请求体的接口使用，配合 [`axum::middleware::map_request`] 添加到内层路由上，
+/// 与 [`DefaultBodyLimit`] 类似，内层的设置覆盖全局的设置
+pub async fn upload_timeout(request: Request) -> Request {
+    if let Some(is_upload) = request.extensions().get::<UploadRequest>() {
+        is_upload.0.store(true, AtomicOrdering::Relaxed);
+    }
+    request
+}
+
+/// `Deprecation` 响应头，参考 [RFC 9745](https://www.rfc-editor.org/rfc/rfc9745)
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+
+/// `Sunset` 响应头，参考 [RFC 8594](https://www.rfc-editor.org/rfc/rfc8594)
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// 接口弃用信息
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    /// 弃用时间，格式为 `@<unix时间戳>`
+    deprecation: HeaderValue,
+
+    /// 计划下线时间，格式为HTTP-date
+    sunset: HeaderValue,
+}
+
+impl Deprecation {
+    /// 创建弃用信息，时间均为UTC零点
+    pub fn new(deprecated_at: NaiveDate, sunset_at: NaiveDate) -> Self {
+        let deprecated_at = deprecated_at.and_time(Default::default()).and_utc();
+        let sunset_at = sunset_at.and_time(Default::default()).and_utc();
+
+        Self {
+            deprecation: header_value(format!("@{}", deprecated_at.timestamp())),
+            sunset: header_value(http_date(sunset_at)),
+        }
+    }
+
+    /// 在响应中添加 `Deprecation` 和 `Sunset` 头
+    ///
+    /// 配合 [`axum::middleware::map_response`] 使用
+    pub fn apply_headers(&self, mut response: Response) -> Response {
+        let headers = response.headers_mut();
+        headers.insert(DEPRECATION, self.deprecation.clone());
+        headers.insert(SUNSET, self.sunset.clone());
+        response
+    }
+}
+
+/// 格式化为HTTP-date，例如 `Sun, 18 Apr 2027 00:00:00 GMT`
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// 日期格式化的结果只包含ASCII字符，一定是合法的响应头
+fn header_value(value: String) -> HeaderValue {
+    HeaderValue::try_from(value).expect("formatted date should be a valid header value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deprecation_headers() {
+        let deprecation = Deprecation::new(
+            NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(),
+            NaiveDate::from_ymd_opt(2027, 4, 18).unwrap(),
+        );
+
+        let response = deprecation.apply_headers(Response::default());
+
+        assert_eq!(response.headers()[DEPRECATION], "@1792281600");
+        assert_eq!(response.headers()[SUNSET], "Sun, 18 Apr 2027 00:00:00 GMT");
+    }
+
+    #[tokio::test]
+    async fn test_load_limits_update() {
+        let config = |max_concurrent_requests| MiddlewareConfig {
+            max_concurrent_requests,
+            request_timeout: Duration::from_secs(5),
+            ..MiddlewareConfig::default()
+        };
+        let limits = LoadLimits::new(&MiddlewareConfig::default());
+        assert_eq!(limits.request_timeout(), Duration::from_secs(30));
+        assert_eq!(limits.upload_timeout(), Duration::from_secs(1800));
+
+        limits.update(&config(4));
+        assert_eq!(limits.semaphore.available_permits(), 4);
+        assert_eq!(limits.request_timeout(), Duration::from_secs(5));
+
+        // 调小时，正在处理的请求结束后才回收许可
+        let first = limits.semaphore.try_acquire().unwrap();
+        let second = limits.semaphore.try_acquire().unwrap();
+        limits.update(&config(1));
+        assert_eq!(limits.semaphore.available_permits(), 0);
+
+        drop(first);
+        tokio::task::yield_now().await;
+        assert_eq!(limits.semaphore.available_permits(), 0);
+        drop(second);
+        assert_eq!(limits.semaphore.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_limits_lower_then_raise() {
+        let config = |max_concurrent_requests| MiddlewareConfig {
+            max_concurrent_requests,
+            ..MiddlewareConfig::default()
+        };
+        let limits = LoadLimits::new(&config(100));
+
+        // 50个请求正在处理时调小到10，再调回100，回收完成之前的调大不能被回收任务吃掉
+        for _ in 0..3 {
+            let in_flight = Arc::clone(&limits.semaphore).try_acquire_many_owned(50).unwrap();
+            limits.update(&config(10));
+            assert_eq!(limits.semaphore.available_permits(), 0);
+
+            limits.update(&config(100));
+            tokio::task::yield_now().await;
+            assert_eq!(limits.semaphore.available_permits(), 50);
+
+            drop(in_flight);
+            tokio::task::yield_now().await;
+            assert_eq!(limits.semaphore.available_permits(), 100);
+        }
+
+        // 部分请求结束后调大，只抵消剩下还没有回收的许可
+        let mut in_flight = Arc::clone(&limits.semaphore).try_acquire_many_owned(50).unwrap();
+        limits.update(&config(10));
+        drop(in_flight.split(20).unwrap());
+        tokio::task::yield_now().await;
+        assert_eq!(limits.semaphore.available_permits(), 0);
+
+        limits.update(&config(30));
+        assert_eq!(limits.semaphore.available_permits(), 0);
+        drop(in_flight);
+        tokio::task::yield_now().await;
+        assert_eq!(limits.semaphore.available_permits(), 30);
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use shared_lib::logging::FilterDirectives;
+use shared_lib::subsystems::{SubsystemState, SubsystemStatus};
+
+/// 健康检查结果
+#[derive(Serialize, Debug)]
+pub struct HealthStatus {
+    /// `ok` 表示正常，`degraded` 表示有子系统没有在运行，`unavailable` 表示依赖的服务不可用
+    pub status: &'static str,
+
+    /// 不可用时的错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// 各个子系统的状态和重启次数，只有存活探针返回
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subsystems: Vec<SubsystemStatus>,
+}
+
+impl HealthStatus {
+    pub fn ok() -> Self {
+        Self {
+            status: "ok",
+            error: None,
+            subsystems: Vec::new(),
+        }
+    }
+
+    /// 可以处理请求，但可选的依赖服务（例如只读副本）不可用
+    pub fn degraded(error: String) -> Self {
+        Self {
+            status: "degraded",
+            error: Some(error),
+            subsystems: Vec::new(),
+        }
+    }
+
+    pub fn unavailable(error: String) -> Self {
+        Self {
+            status: "unavailable",
+            error: Some(error),
+            subsystems: Vec::new(),
+        }
+    }
+
+    /// 根据子系统的状态判断是否正常
+    pub fn subsystems(subsystems: Vec<SubsystemStatus>) -> Self {
+        let degraded = subsystems.iter().any(|subsystem| subsystem.state != SubsystemState::Running);
+        Self {
+            status: if degraded { "degraded" } else { "ok" },
+            error: None,
+            subsystems,
+        }
+    }
+}
+
+/// 程序构建信息
+#[derive(Serialize, Debug)]
+pub struct BuildInfo {
+    /// 程序名称
+    pub name: &'static str,
+
+    /// 程序版本
+    pub version: &'static str,
+}
+
+/// 重新加载配置的结果
+#[derive(Serialize, Debug)]
+pub struct ConfigReload {
+    /// 发生变化并且已经生效的配置项，为空表示配置没有变化
+    pub changed: Vec<&'static str>,
+}
+
+/// 日志过滤规则
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LogFilterStatus {
+    /// 配置项 `log.filter` 中的过滤规则
+    pub configured: String,
+
+    /// 临时设置的过滤规则，设置后优先于配置中的规则
+    pub temporary: Option<String>,
+}
+
+impl From<FilterDirectives> for LogFilterStatus {
+    fn from(directives: FilterDirectives) -> Self {
+        Self {
+            configured: directives.configured,
+            temporary: directives.temporary,
+        }
+    }
+}
+
+/// 临时设置日志过滤规则的请求
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LogFilterUpdate {
+    /// 过滤规则，使用 `EnvFilter` 的语法，例如 `info,database=debug`
+    pub directives: String,
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// 附件信息
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct AttachmentInfo {
+    #[schema(example = 7)]
+    /// 附件ID
+    pub id: i32,
+
+    #[schema(example = 1)]
+    /// 所属项目ID
+    pub project_id: i32,
+
+    #[schema(example = "需求文档.pdf")]
+    /// 上传时的原始文件名
+    pub file_name: String,
+
+    #[schema(example = "application/pdf")]
+    /// 内容类型
+    pub content_type: String,
+
+    #[schema(example = 102400)]
+    /// 内容大小（字节）
+    pub size: i64,
+
+    #[schema(example = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08")]
+    /// 内容的sha256（小写十六进制），下载时的ETag为加上双引号的sha256
+    pub sha256: String,
+
+    /// 上传时间（RFC 3339格式）
+    pub created_at: DateTime<Utc>,
+}
+
+/// 从数据库层的 AttachmentInfo 转换为 web-service 层的 AttachmentInfo，不对外暴露存储路径
+impl From<database::models::AttachmentInfo> for AttachmentInfo {
+    fn from(db_attachment: database::models::AttachmentInfo) -> Self {
+        Self {
+            id: db_attachment.id,
+            project_id: db_attachment.project_id,
+            file_name: db_attachment.file_name,
+            content_type: db_attachment.content_type,
+            size: db_attachment.size,
+            sha256: db_attachment.sha256,
+            created_at: db_attachment.created_at.and_utc(),
+        }
+    }
+}
+
+/// 上传附件的请求体（`multipart/form-data`），只用于生成文档
+#[derive(Debug, ToSchema)]
+pub struct AttachmentUpload {
+    /// 附件内容，文件名和内容类型取自该字段的 `filename` 和 `Content-Type`
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
+
+/// 上传文件的基本信息，来自multipart字段
+#[derive(Debug, Validate)]
+pub struct AttachmentFile {
+    #[validate(length(min = 1, max = 255))]
+    /// 文件名，不包含客户端的目录
+    pub file_name: String,
+
+    #[validate(length(min = 1, max = 255))]
+    /// 内容类型，客户端没有提供时为 `application/octet-stream`
+    pub content_type: String,
+}
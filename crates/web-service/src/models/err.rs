@@ -1,11 +1,16 @@
+use crate::i18n::{self, current_locale};
 use axum::{
-    http::StatusCode,
+    extract::multipart::MultipartError,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use color_eyre::eyre::Error;
 use database::DatabaseError;
 use redis::RedisError;
+use shared_lib::models::locale::Locale;
+use storage::StorageError;
 use thiserror::Error;
+use tracing::error;
 use validator::ValidationErrors;
 
 /// 使用 [`thiserror`] 定义错误类型
@@ -20,35 +25,120 @@ pub enum AppError {
     #[error(transparent)]
     DatabaseError(#[from] sqlx::Error),
 
-    /// 仓库层数据库错误
+    /// 仓库层数据库错误，记录不存在时转换为404，违反唯一约束、项目层级错误时转换为409
     #[error(transparent)]
     RepositoryError(#[from] DatabaseError),
 
     #[error(transparent)]
     RedisError(#[from] RedisError),
 
+    /// 附件存储错误，对象不存在时转换为404
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+
+    /// multipart请求解析错误，例如请求体超过大小限制，状态码由 [`MultipartError::status`] 决定
+    #[error(transparent)]
+    Multipart(#[from] MultipartError),
+
+    /// 没有携带访问令牌或者令牌无效，转换为401
+    #[error("Missing or invalid access token")]
+    Unauthorized,
+
+    /// 请求处理超时，转换为504
+    #[error("Request timed out")]
+    Timeout,
+
+    /// 服务过载，并发请求数超过上限时直接拒绝，转换为503
+    #[error("Service is overloaded, please retry later")]
+    Overloaded,
+
     /// 其他类型错误
     #[error(transparent)]
     InternalError(#[from] Error),
 }
 
+impl AppError {
+    /// 错误对应的http状态码
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::ValidationFailed(_) => StatusCode::BAD_REQUEST,
+            AppError::DatabaseError(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND,
+            AppError::RepositoryError(DatabaseError::SqlxError(sqlx::Error::RowNotFound)) => StatusCode::NOT_FOUND,
+            AppError::RepositoryError(DatabaseError::SqlxError(sqlx::Error::Database(err))) if err.is_unique_violation() => {
+                StatusCode::CONFLICT
+            }
+            AppError::RepositoryError(DatabaseError::InvalidHierarchy { .. }) => StatusCode::CONFLICT,
+            AppError::StorageError(StorageError::NotFound(_)) => StatusCode::NOT_FOUND,
+            AppError::Multipart(err) => err.status(),
+            AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            AppError::Overloaded => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::DatabaseError(_)
+            | AppError::RepositoryError(_)
+            | AppError::RedisError(_)
+            | AppError::StorageError(_)
+            | AppError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// 返回给用户的错误信息，使用当前请求协商的语言，参考 [`i18n`](crate::i18n)
+    pub fn message(&self) -> String {
+        self.message_in(current_locale())
+    }
+
+    /// 指定语言的错误信息
+    ///
+    /// 4xx错误只返回用户能处理的信息，5xx错误只返回通用信息，原始错误通过 [`AppError::log`] 记录到日志中
+    pub fn message_in(&self, locale: Locale) -> String {
+        let text = i18n::catalogue(locale);
+        match self {
+            AppError::ValidationFailed(err) => format!("{}: {}", text.validation_failed, i18n::validation_message(err, locale)),
+            AppError::DatabaseError(sqlx::Error::RowNotFound) => text.resource_not_found.to_string(),
+            AppError::DatabaseError(_) => text.database_error.to_string(),
+            AppError::RepositoryError(DatabaseError::SqlxError(sqlx::Error::RowNotFound)) => text.record_not_found.to_string(),
+            AppError::RepositoryError(DatabaseError::SqlxError(sqlx::Error::Database(db_err))) if db_err.is_unique_violation() => {
+                text.already_exists.to_string()
+            }
+            AppError::RepositoryError(DatabaseError::InvalidHierarchy { id, parent_id }) => text
+                .invalid_hierarchy
+                .replace("{id}", &id.to_string())
+                .replace("{parent_id}", &parent_id.to_string()),
+            AppError::RepositoryError(_) => text.repository_error.to_string(),
+            AppError::RedisError(_) => text.redis_error.to_string(),
+            AppError::StorageError(StorageError::NotFound(_)) => text.resource_not_found.to_string(),
+            AppError::StorageError(_) => text.storage_error.to_string(),
+            AppError::Multipart(err) => format!("{}: {}", text.invalid_multipart, err.body_text()),
+            AppError::Unauthorized => text.unauthorized.to_string(),
+            AppError::Timeout => text.timeout.to_string(),
+            AppError::Overloaded => text.overloaded.to_string(),
+            AppError::InternalError(_) => text.internal_error.to_string(),
+        }
+    }
+
+    /// 服务端错误（5xx）的原始错误不返回给用户，记录到日志中方便排查问题
+    pub fn log(&self) {
+        match self {
+            AppError::Timeout | AppError::Overloaded => {}
+            _ if self.status_code().is_server_error() => error!("❌ 请求处理失败: {:?}", self),
+            _ => {}
+        }
+    }
+}
+
 /// Tell axum how to convert `AppError` into a response.
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        match self {
-            AppError::ValidationFailed(err) => (StatusCode::BAD_REQUEST, format!("Validate failed: {err}")).into_response(),
-            AppError::DatabaseError(err) => match err {
-                sqlx::Error::RowNotFound => (StatusCode::NOT_FOUND, format!("Can not found resource: {err}")).into_response(),
-                _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error: {err}")).into_response(),
-            },
-            AppError::RepositoryError(err) => match err {
-                DatabaseError::SqlxError(sqlx::Error::RowNotFound) => {
-                    (StatusCode::NOT_FOUND, format!("Record not found: {err}")).into_response()
-                }
-                _ => (StatusCode::INTERNAL_SERVER_ERROR, format!("Repository error: {err}")).into_response(),
-            },
-            AppError::RedisError(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis error: {err}")).into_response(),
-            AppError::InternalError(err) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Something went wrong: {err}")).into_response(),
+        self.log();
+        let locale = current_locale();
+        let mut response = (self.status_code(), self.message_in(locale)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_LANGUAGE, HeaderValue::from_static(locale.as_str()));
+        if matches!(self, AppError::Unauthorized) {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
         }
+        response
     }
 }
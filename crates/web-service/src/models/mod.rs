@@ -1,4 +1,7 @@
+pub mod admin;
+pub mod attachments;
 pub mod common;
 pub mod err;
 pub mod projects;
+pub mod tags;
 pub mod users;
@@ -1,36 +1,20 @@
-use crate::models::common::PageQuery;
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use utoipa::ToSchema;
-use validator::Validate;
 
-/// 搜索项目列表信息
-///
-/// - `project_name`为可选参数
-#[derive(Deserialize, Debug, ToSchema, Validate)]
-pub struct ProjectSearch {
-    #[schema(example = "foo")]
-    #[validate(length(min = 1, max = 100))]
-    /// 查询的项目名称（模糊搜索）
-    pub project_name: Option<String>,
-
-    /// 查询分页信息
-    #[validate(nested)]
-    pub page_query: PageQuery,
-}
-
-#[derive(Deserialize, Debug, ToSchema)]
-pub struct ProjectCreate {
-    /// 新建项目名称
-    #[schema(example = "foo")]
-    pub project_name: String,
-
-    /// 项目说明
-    #[schema(example = "comment")]
-    pub comment: String,
-}
+// v1接口的请求和返回值类型定义在 api-models 中，与Rust客户端共用
+pub use api_models::projects::{
+    ProjectCreate, ProjectHighlight, ProjectInfo, ProjectRankedSearch, ProjectSearch, ProjectSearchHit, ProjectSuggestQuery, ProjectUpdate,
+};
 
-#[derive(Deserialize, Debug, ToSchema, Serialize)]
-pub struct ProjectInfo {
+/// 项目详细信息（v2）
+///
+/// 与 [`ProjectInfo`] 相比增加了创建时间和更新时间
+#[derive(Deserialize, Debug, Clone, ToSchema, Serialize, SimpleObject)]
+#[graphql(name = "Project")]
+pub struct ProjectDetail {
     #[schema(example = 15)]
     /// 项目ID
     pub id: i32,
@@ -42,25 +26,116 @@ pub struct ProjectInfo {
     /// 项目说明
     #[schema(example = "foo_bar")]
     pub comment: String,
+
+    #[schema(example = 3)]
+    /// 父项目ID，顶层项目为空
+    pub parent_id: Option<i32>,
+
+    /// 创建时间（RFC 3339格式）
+    pub created_at: DateTime<Utc>,
+
+    /// 最后更新时间（RFC 3339格式）
+    pub updated_at: DateTime<Utc>,
+
+    #[schema(example = json!(["env:prod", "team:payments"]))]
+    /// 项目标签，按名称排序
+    pub tags: Vec<String>,
 }
 
-/// 从数据库层的 ProjectInfo 转换为 web-service 层的 ProjectInfo
-impl From<database::models::ProjectInfo> for ProjectInfo {
+/// 从数据库层的 ProjectInfo 转换为 web-service 层的 ProjectDetail
+impl From<database::models::ProjectInfo> for ProjectDetail {
     fn from(db_project: database::models::ProjectInfo) -> Self {
         Self {
             id: db_project.id,
             project_name: db_project.project_name,
             comment: db_project.comment,
+            parent_id: db_project.parent_id,
+            created_at: db_project.created_at.and_utc(),
+            updated_at: db_project.updated_at.and_utc(),
+            tags: db_project.tags,
         }
     }
 }
 
-/// 更新项目的信息
+/// 按相关度搜索的单条结果（v2）
+///
+/// 与 [`ProjectSearchHit`] 相比，项目信息使用 [`ProjectDetail`]
 #[derive(Deserialize, Debug, ToSchema, Serialize)]
-pub struct ProjectUpdate {
-    #[schema(example = "bar")]
-    pub project_name: Option<String>,
+pub struct ProjectDetailSearchHit {
+    /// 项目信息
+    pub project: ProjectDetail,
+
+    #[schema(example = 0.57)]
+    /// 相关度得分（0~1）
+    pub score: f32,
+
+    /// 命中片段高亮
+    pub highlight: ProjectHighlight,
+}
+
+/// 从数据库层的 ProjectSearchHit 转换为 v2 的 ProjectDetailSearchHit
+impl From<database::models::ProjectSearchHit> for ProjectDetailSearchHit {
+    fn from(hit: database::models::ProjectSearchHit) -> Self {
+        Self {
+            project: hit.project.into(),
+            score: hit.score,
+            highlight: ProjectHighlight {
+                project_name: hit.name_highlight,
+                comment: hit.comment_highlight,
+            },
+        }
+    }
+}
+
+/// 移动项目
+#[derive(Deserialize, Debug, ToSchema, Serialize)]
+pub struct ProjectMove {
+    #[schema(example = 3)]
+    /// 新的父项目ID，为空时移动为顶层项目
+    pub parent_id: Option<i32>,
+}
+
+/// 项目树节点
+#[derive(Deserialize, Debug, ToSchema, Serialize)]
+pub struct ProjectTree {
+    /// 项目信息
+    #[serde(flatten)]
+    pub project: ProjectDetail,
+
+    /// 子项目，按ID排序
+    #[schema(no_recursion)]
+    pub children: Vec<ProjectTree>,
+}
+
+impl ProjectTree {
+    /// 根据子树列表构建项目树
+    ///
+    /// `subtree` 的第一个元素为根项目，参考 [`ProjectServiceTrait::get_project_subtree`](crate::services::ProjectServiceTrait::get_project_subtree)，
+    /// 列表为空时返回 `None`
+    pub fn build(subtree: Vec<database::models::ProjectInfo>) -> Option<Self> {
+        let mut projects = subtree.into_iter().map(ProjectDetail::from);
+        let root = projects.next()?;
+
+        let mut children: HashMap<i32, Vec<ProjectDetail>> = HashMap::new();
+        for project in projects {
+            if let Some(parent_id) = project.parent_id {
+                children.entry(parent_id).or_default().push(project);
+            }
+        }
+
+        Some(Self::attach_children(root, &mut children))
+    }
+
+    fn attach_children(project: ProjectDetail, children: &mut HashMap<i32, Vec<ProjectDetail>>) -> Self {
+        let mut direct_children = children.remove(&project.id).unwrap_or_default();
+        direct_children.sort_by_key(|child| child.id);
 
-    #[schema(example = "foo")]
-    pub comment: Option<String>,
+        Self {
+            children: direct_children
+                .into_iter()
+                .map(|child| Self::attach_children(child, children))
+                .collect(),
+            project,
+        }
+    }
 }
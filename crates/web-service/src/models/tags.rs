@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// 标签信息
+#[derive(Deserialize, Serialize, Debug, ToSchema)]
+pub struct TagInfo {
+    #[schema(example = 3)]
+    /// 标签ID
+    pub id: i32,
+
+    #[schema(example = "team:payments")]
+    /// 标签名称
+    pub name: String,
+
+    #[schema(example = "支付团队负责的项目")]
+    /// 标签说明
+    pub comment: String,
+
+    /// 创建时间（RFC 3339格式）
+    pub created_at: DateTime<Utc>,
+
+    /// 最后更新时间（RFC 3339格式）
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 从数据库层的 TagInfo 转换为 web-service 层的 TagInfo
+impl From<database::models::TagInfo> for TagInfo {
+    fn from(db_tag: database::models::TagInfo) -> Self {
+        Self {
+            id: db_tag.id,
+            name: db_tag.name,
+            comment: db_tag.comment,
+            created_at: db_tag.created_at.and_utc(),
+            updated_at: db_tag.updated_at.and_utc(),
+        }
+    }
+}
+
+/// 创建标签
+///
+/// 标签名称在租户内唯一，建议使用 `key:value` 格式，例如 `team:payments`、`env:prod`
+#[derive(Deserialize, Serialize, Debug, ToSchema, Validate)]
+pub struct TagCreate {
+    #[schema(example = "team:payments")]
+    #[validate(length(min = 1, max = 100))]
+    /// 标签名称
+    pub name: String,
+
+    #[schema(example = "支付团队负责的项目")]
+    #[validate(length(max = 255))]
+    #[serde(default)]
+    /// 标签说明
+    pub comment: String,
+}
+
+/// 更新标签的信息
+#[derive(Deserialize, Serialize, Debug, ToSchema, Validate)]
+pub struct TagUpdate {
+    #[schema(example = "team:billing")]
+    #[validate(length(min = 1, max = 100))]
+    /// 新的标签名称，不填时保持不变
+    pub name: Option<String>,
+
+    #[schema(example = "账单团队负责的项目")]
+    #[validate(length(max = 255))]
+    /// 新的标签说明，不填时保持不变
+    pub comment: Option<String>,
+}
@@ -0,0 +1,228 @@
+//! 运维接口
+//!
+//! 只挂载在运维监听器上，不对外暴露：
+//! - `/health`: 存活探针，进程能响应请求即返回200，同时返回各个子系统的状态和重启次数
+//! - `/health/ready`: 就绪探针，数据库不可用、有子系统正在等待依赖的服务（降级启动）或者程序正在退出时返回503
+//! - `/metrics`: Prometheus格式的监控指标
+//! - `/admin/*`: 管理接口
+//!     - `GET /admin/info`: 程序构建信息
+//!     - `POST /admin/config/reload`: 重新加载配置，与发送 `SIGHUP` 信号的效果相同，参考 [`shared_lib::models::config_reload`]
+//!     - `GET|PUT|DELETE /admin/log/filter`: 查看、临时设置、清除日志过滤规则，参考 [`shared_lib::logging::LogFilter`]
+
+use crate::models::admin::{BuildInfo, ConfigReload, HealthStatus, LogFilterStatus, LogFilterUpdate};
+use crate::models::common::Reply;
+use crate::{AppState, services::ProjectServiceTrait};
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use color_eyre::Report;
+use tracing::{info, warn};
+
+/// 导出所有运维接口
+pub fn routers<PS: ProjectServiceTrait>(state: AppState<PS>) -> Router {
+    Router::new()
+        .route("/health", get(health::<PS>))
+        .route("/health/ready", get(ready::<PS>))
+        .route("/metrics", get(metrics::<PS>))
+        .route("/admin/info", get(info))
+        .route("/admin/config/reload", post(reload_config::<PS>))
+        .route(
+            "/admin/log/filter",
+            get(log_filter::<PS>).put(set_log_filter::<PS>).delete(clear_log_filter::<PS>),
+        )
+        .with_state(state)
+}
+
+/// 存活探针，有子系统正在重启或者已经失败时状态为 `degraded`，但仍然返回200，避免整个进程被重启
+async fn health<PS: ProjectServiceTrait>(State(state): State<AppState<PS>>) -> Json<HealthStatus> {
+    Json(HealthStatus::subsystems(state.subsystems.snapshot()))
+}
+
+/// 就绪探针，检查数据库（主库）是否可用以及所有子系统是否已经启动，同时报告只读副本的状态
+async fn ready<PS: ProjectServiceTrait>(State(state): State<AppState<PS>>) -> impl IntoResponse {
+    if *state.draining_rx.borrow() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthStatus::unavailable("Shutting down".to_string())),
+        );
+    }
+
+    // 降级启动时数据库迁移完成前不接收流量
+    if let Some(name) = state.subsystems.starting_subsystem() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthStatus::unavailable(format!("Subsystem `{name}` is starting"))),
+        );
+    }
+
+    if let Err(err) = state.project_service.ping().await {
+        warn!("Readiness check failed: {}", err);
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(HealthStatus::unavailable(err.to_string())));
+    }
+
+    // 只读副本是可选的，不可用时只读查询改用主库，仍然接收流量，只报告为 `degraded`
+    match state.project_service.ping_replica().await {
+        Some(Err(err)) => {
+            warn!("Read replica is unavailable: {}", err);
+            (
+                StatusCode::OK,
+                Json(HealthStatus::degraded(format!(
+                    "Read replica is unavailable, reads fall back to the primary: {err}"
+                ))),
+            )
+        }
+        _ => (StatusCode::OK, Json(HealthStatus::ok())),
+    }
+}
+
+/// 导出Prometheus格式的监控指标
+async fn metrics<PS: ProjectServiceTrait>(State(state): State<AppState<PS>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        state.metrics.render(),
+    )
+}
+
+/// 程序构建信息
+async fn info() -> Json<Reply<BuildInfo>> {
+    Json(Reply {
+        data: BuildInfo {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+        },
+    })
+}
+
+/// 重新加载配置
+///
+/// 配置无效或者修改了需要重启才能生效的配置项时返回422，继续使用原有配置
+async fn reload_config<PS: ProjectServiceTrait>(State(state): State<AppState<PS>>) -> impl IntoResponse {
+    match state.reloader.reload() {
+        Ok(changed) => {
+            info!("🔄 通过运维接口重新加载配置，变化的配置项: {:?}", changed);
+            Ok(Json(Reply {
+                data: ConfigReload { changed },
+            }))
+        }
+        Err(err) => {
+            warn!("Failed to reload config: {:?}", err);
+            Err((StatusCode::UNPROCESSABLE_ENTITY, error_message(&err)))
+        }
+    }
+}
+
+/// 当前的日志过滤规则
+async fn log_filter<PS: ProjectServiceTrait>(State(state): State<AppState<PS>>) -> Json<Reply<LogFilterStatus>> {
+    Json(Reply {
+        data: state.log_filter.directives().into(),
+    })
+}
+
+/// 临时设置日志过滤规则，例如临时打开某个模块的debug日志，规则无效时返回400
+///
+/// 临时规则优先于配置中的规则，直到调用 `DELETE /admin/log/filter` 清除或者重启服务
+async fn set_log_filter<PS: ProjectServiceTrait>(
+    State(state): State<AppState<PS>>,
+    Json(update): Json<LogFilterUpdate>,
+) -> Result<Json<Reply<LogFilterStatus>>, (StatusCode, String)> {
+    match state.log_filter.set_temporary(&update.directives) {
+        Ok(()) => {
+            info!("📝 临时设置日志过滤规则: {}", update.directives);
+            Ok(log_filter(State(state)).await)
+        }
+        Err(err) => Err((StatusCode::BAD_REQUEST, error_message(&err))),
+    }
+}
+
+/// 清除临时的日志过滤规则，恢复使用配置中的规则
+async fn clear_log_filter<PS: ProjectServiceTrait>(
+    State(state): State<AppState<PS>>,
+) -> Result<Json<Reply<LogFilterStatus>>, (StatusCode, String)> {
+    // 先记录日志，恢复后的规则可能会过滤掉这条日志
+    info!("📝 清除临时的日志过滤规则");
+    match state.log_filter.clear_temporary() {
+        Ok(()) => Ok(log_filter(State(state)).await),
+        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, error_message(&err))),
+    }
+}
+
+/// 返回给运维人员的错误信息，包含完整的错误链，不包含错误位置等调试信息
+fn error_message(err: &Report) -> String {
+    err.chain().map(ToString::to_string).collect::<Vec<_>>().join(": ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::admin::{LogFilterStatus, LogFilterUpdate};
+    use crate::models::common::Reply;
+    use crate::test_support::TestApp;
+    use axum::http::StatusCode;
+    use color_eyre::eyre::eyre;
+    use serde_json::{Value, json};
+    use shared_lib::models::config::RestartPolicy;
+
+    #[tokio::test]
+    async fn test_health() {
+        let app = TestApp::new();
+
+        for uri in ["/health", "/health/ready", "/metrics", "/admin/info"] {
+            assert_eq!(app.admin_get(uri).await.status, StatusCode::OK, "{uri}");
+            // 运维接口不在业务路由上
+            assert_eq!(app.get(uri).await.status, StatusCode::NOT_FOUND, "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_subsystems() {
+        let app = TestApp::new();
+        app.subsystems.register("web", RestartPolicy::Escalate);
+        app.subsystems.register("cron", RestartPolicy::OnFailure);
+        assert_eq!(app.admin_get("/health").await.json::<Value>()["status"], "ok");
+
+        app.subsystems.restarting("cron", &eyre!("redis is down"));
+        // 子系统正在重启时进程仍然存活
+        let response = app.admin_get("/health").await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(
+            response.json::<Value>(),
+            json!({
+                "status": "degraded",
+                "subsystems": [
+                    {"name": "web", "state": "running", "restart_policy": "escalate", "restarts": 0},
+                    {"name": "cron", "state": "restarting", "restart_policy": "on-failure", "restarts": 1, "last_error": "redis is down"},
+                ],
+            })
+        );
+
+        // 降级启动时，等待数据库的子系统启动完成前不接收流量
+        app.subsystems.register("database-pool", RestartPolicy::Escalate);
+        app.subsystems.starting("database-pool");
+        assert_eq!(app.admin_get("/health/ready").await.status, StatusCode::SERVICE_UNAVAILABLE);
+        app.subsystems.running("database-pool");
+        assert_eq!(app.admin_get("/health/ready").await.status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_log_filter() {
+        let app = TestApp::new();
+
+        let update = |directives: &str| LogFilterUpdate {
+            directives: directives.to_string(),
+        };
+        let response = app.admin_put_json("/admin/log/filter", &update("info,database=debug")).await;
+        assert_eq!(response.status, StatusCode::OK);
+        let status = response.json::<Reply<LogFilterStatus>>().data;
+        assert_eq!(status.configured, "info");
+        assert_eq!(status.temporary.as_deref(), Some("info,database=debug"));
+
+        let response = app.admin_put_json("/admin/log/filter", &update("database=loud")).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert!(response.text().contains("database=loud"), "{}", response.text());
+
+        let response = app.admin_delete("/admin/log/filter").await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(response.json::<Reply<LogFilterStatus>>().data.temporary, None);
+    }
+}
@@ -1,15 +1,27 @@
 //! 路由入口
 //!
-//! 提供 [`create_app_router`] 函数，导出当前App的所有路由。
+//! 提供 [`create_app_routers`] 函数，导出当前App的所有路由。
 //!
-//! 用户可以在导出路由时传入共享数据 shared_state，这样所有路由函数都可以访问。
+//! 路由分为两组，分别挂载到不同的监听器上：
+//! - 业务路由：包含所有API版本（`/api/v1`、`/api/v2`）和GraphQL接口（`/graphql`），对外提供服务
+//! - 运维路由：文档、健康检查、监控指标和管理接口，只在内部访问
+//!
+//! 每个API版本使用独立的 [`OpenApiRouter`] 生成独立的OpenAPI文档，文档地址为 `/docs/<版本>`。
+//!
+//! 用户可以在导出路由时传入共享数据 shared_state，两组路由共享同一份数据。
 
+use crate::metrics::track_http_metrics;
+use crate::middleware::{Deprecation, LoadLimits};
 use crate::routes::projects::__path_create_project;
 use crate::routes::projects::__path_delete_project;
 use crate::routes::projects::__path_find_projects;
 use crate::routes::projects::__path_get_project;
+use crate::routes::projects::__path_rank_search_projects;
+use crate::routes::projects::__path_suggest_projects;
 use crate::routes::projects::__path_update_project;
-use crate::routes::projects::{create_project, delete_project, find_projects, get_project, update_project};
+use crate::routes::projects::{
+    create_project, delete_project, find_projects, get_project, rank_search_projects, suggest_projects, update_project,
+};
 use crate::routes::users::__path_create_user;
 use crate::routes::users::__path_delete_user;
 use crate::routes::users::__path_find_users;
@@ -17,16 +29,75 @@ use crate::routes::users::__path_get_user;
 use crate::routes::users::__path_update_user;
 use crate::routes::users::{create_user, delete_user, find_users, get_user, update_user};
 use crate::{AppState, services::ProjectServiceTrait};
-use axum::Router;
+use axum::response::Redirect;
+use axum::routing::get;
+use axum::{Router, middleware};
+use color_eyre::Result;
+use shared_lib::models::config::MiddlewareConfig;
+use std::sync::Arc;
 use utoipa::OpenApi;
+use utoipa::openapi::{Deprecated, PathItem};
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 use utoipa_scalar::{Scalar, Servable};
 
+pub mod admin;
 pub mod projects;
 pub mod users;
+pub mod v2;
+
+/// 最新版本的文档地址，访问 `/docs` 时会跳转到这里
+const LATEST_DOCS_PATH: &str = "/docs/v2";
+
+/// API版本
+struct ApiVersion {
+    /// 路由前缀，例如 `/api/v1`
+    prefix: &'static str,
+
+    /// 文档地址，例如 `/docs/v1`
+    docs_path: &'static str,
 
-/// 导出当前App的所有路由
+    /// 当前版本的路由
+    router: OpenApiRouter,
+}
+
+/// 将路由标记为已弃用
+///
+/// - 响应中添加 `Deprecation` 和 `Sunset` 头
+/// - OpenAPI文档中的接口标记为 `deprecated`
+fn deprecated<S>(router: OpenApiRouter<S>, deprecation: Deprecation) -> OpenApiRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let mut router = router.route_layer(middleware::map_response(move |response| {
+        let deprecation = deprecation.clone();
+        async move { deprecation.apply_headers(response) }
+    }));
+
+    router.get_openapi_mut().paths.paths.values_mut().for_each(mark_deprecated);
+
+    router
+}
+
+/// 将路径下的所有接口标记为已弃用
+fn mark_deprecated(item: &mut PathItem) {
+    let operations = [
+        &mut item.get,
+        &mut item.put,
+        &mut item.post,
+        &mut item.delete,
+        &mut item.options,
+        &mut item.head,
+        &mut item.patch,
+        &mut item.trace,
+    ];
+
+    for operation in operations.into_iter().flatten() {
+        operation.deprecated = Some(Deprecated::True);
+    }
+}
+
+/// 导出v1版本的所有路由
 ///
 /// ## 参数定义
 /// - state: 共享数据，参考 [`AppState`] 定义。一般存放数据库连接池之类的全局共享数据。
@@ -50,24 +121,45 @@ pub mod users;
 /// ```
 ///
 fn routers<PS: ProjectServiceTrait>(state: AppState<PS>) -> OpenApiRouter {
+    // 已经有v2替代的接口，弃用时间和计划下线时间参考配置 `web.v1_deprecated_at`、`web.v1_sunset`
+    let deprecated_routers = deprecated(
+        OpenApiRouter::new()
+            .routes(routes!(find_projects))
+            .routes(routes!(rank_search_projects))
+            .routes(routes!(get_project, create_project, update_project, delete_project)),
+        state.v1_deprecation.clone(),
+    );
+
     OpenApiRouter::new()
-        .routes(routes!(find_projects))
-        .routes(routes!(get_project, create_project, update_project, delete_project))
+        .merge(deprecated_routers)
+        .routes(routes!(suggest_projects))
         .routes(routes!(find_users))
         .routes(routes!(get_user, create_user, update_user, delete_user))
         .with_state(state)
 }
 
+/// 当前App的路由
+pub struct AppRouters {
+    /// 业务路由，挂载到对外的监听器
+    pub public: Router,
+
+    /// 运维路由，挂载到运维监听器
+    pub admin: Router,
+
+    /// 业务路由的负载保护参数，配置热加载后需要更新
+    pub load_limits: Arc<LoadLimits>,
+}
+
 /// 创建当前App的路由
 ///
 /// 完成以下功能：
 /// - 生成OpenAPI文档
-/// - 生成App路由
-/// - 使用Scalar作为最终在线文档格式
+/// - 生成App路由，并为业务路由添加中间件（参考 [`crate::middleware`]）和请求监控指标
+/// - 使用Scalar作为最终在线文档格式，文档只在运维路由中提供
 ///
 /// 由于使用了 `utoipa` 库来自动化生成`openapi`文档，因此我们没有使用原生的 [`Router`]，而是使用了
 /// [`OpenApiRouter`] 。
-pub fn create_app_router<PS: ProjectServiceTrait>(shared_state: AppState<PS>) -> Router {
+pub fn create_app_routers<PS: ProjectServiceTrait>(shared_state: AppState<PS>, middleware_config: &MiddlewareConfig) -> Result<AppRouters> {
     // 当前项目的OpenAPI声明
     #[derive(OpenApi)]
     #[openapi(
@@ -83,15 +175,85 @@ Rust后端例子，覆盖场景：
     )]
     struct ApiDoc;
 
-    // 使用`utoipa_axum`提供的OpenApiRouter来创建路由。
-    // 同时传递共享状态数据到路由中供使用。
-    // 最终拿到的变量：
-    // - router: Axum的Router，实际的路由对象
-    // - api: utoipa的OpenApi，生成的OpenAPI对象
-    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .nest("/api/v1", routers(shared_state))
-        .split_for_parts();
-
-    // 合并文档路由，用户可通过 /docs 访问文档网页地址
-    router.merge(Scalar::with_url("/docs", api))
+    let versions = [
+        ApiVersion {
+            prefix: "/api/v1",
+            docs_path: "/docs/v1",
+            router: routers(shared_state.clone()),
+        },
+        ApiVersion {
+            prefix: "/api/v2",
+            docs_path: "/docs/v2",
+            router: v2::routers(shared_state.clone()),
+        },
+    ];
+
+    let mut public = crate::graphql::routers(shared_state.clone());
+    let mut admin = admin::routers(shared_state).route("/docs", get(|| async { Redirect::temporary(LATEST_DOCS_PATH) }));
+
+    for version in versions {
+        // 使用`utoipa_axum`提供的OpenApiRouter来创建路由，每个版本生成独立的文档。
+        // 最终拿到的变量：
+        // - router: Axum的Router，实际的路由对象
+        // - api: utoipa的OpenApi，生成的OpenAPI对象
+        let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+            .nest(version.prefix, version.router)
+            .split_for_parts();
+
+        public = public.merge(router);
+
+        // 合并文档路由，用户可通过运维监听器的 /docs/<版本> 访问文档网页地址
+        admin = admin.merge(Scalar::with_url(version.docs_path, api));
+    }
+
+    // 监控指标放在最外层，这样超时、过载等中间件直接返回的响应也会被记录
+    let load_limits = Arc::new(LoadLimits::new(middleware_config));
+    let public =
+        crate::middleware::apply(public, middleware_config, Arc::clone(&load_limits))?.layer(middleware::from_fn(track_http_metrics));
+
+    Ok(AppRouters {
+        public,
+        admin,
+        load_limits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::common::Reply;
+    use crate::models::projects::ProjectDetail;
+    use crate::test_support::TestApp;
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_api_versions() {
+        let app = TestApp::new();
+
+        let response = app
+            .post_json("/api/v2/projects", &json!({"project_name": "pay", "comment": "comment"}))
+            .await;
+        let project = response.json::<Reply<ProjectDetail>>().data;
+        assert!(response.headers.get("deprecation").is_none());
+
+        // v1中被v2替代的接口带有弃用信息，其余接口不受影响
+        let response = app.get(&format!("/api/v1/projects/{}", project.id)).await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(response.headers.contains_key("deprecation"));
+        assert_eq!(response.headers["sunset"], "Sun, 18 Apr 2027 00:00:00 GMT");
+
+        let response = app.get("/api/v1/projects/suggestions?q=pay").await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert!(response.headers.get("deprecation").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_docs_only_on_admin_router() {
+        let app = TestApp::new();
+
+        assert_eq!(app.get("/docs/v1").await.status, StatusCode::NOT_FOUND);
+        assert_eq!(app.admin_get("/docs/v1").await.status, StatusCode::OK);
+        assert_eq!(app.admin_get("/docs/v2").await.status, StatusCode::OK);
+        assert_eq!(app.admin_get("/docs").await.status, StatusCode::TEMPORARY_REDIRECT);
+    }
 }
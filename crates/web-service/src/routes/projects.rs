@@ -1,14 +1,15 @@
 //! 项目相关接口
 //!
 
-use std::sync::Arc;
-
+use crate::auth::Tenant;
 use crate::models::common::{Reply, ReplyList};
 use crate::models::err::AppError;
-use crate::models::projects::{ProjectCreate, ProjectInfo, ProjectSearch, ProjectUpdate};
+use crate::models::projects::{
+    ProjectCreate, ProjectInfo, ProjectRankedSearch, ProjectSearch, ProjectSearchHit, ProjectSuggestQuery, ProjectUpdate,
+};
 use crate::{AppState, services::ProjectServiceTrait};
 use axum::Json;
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use color_eyre::Result;
 use tracing::debug;
 use validator::Validate;
@@ -55,6 +56,7 @@ use validator::Validate;
     ),
 )]
 pub async fn find_projects<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
     State(state): State<AppState<PS>>,
     Json(search): Json<ProjectSearch>,
 ) -> Result<Json<ReplyList<ProjectInfo>>, AppError> {
@@ -67,11 +69,11 @@ pub async fn find_projects<PS: ProjectServiceTrait>(
     let offset = (search.page_query.page_index.saturating_sub(1)) * search.page_query.page_size;
 
     // 获取项目服务实例
-    let project_service = Arc::clone(&state.project_service);
+    let project_service = state.project_service.with_tenant(&tenant);
 
     // 调用服务方法执行搜索
     let result = project_service
-        .find_projects(search.project_name.clone(), search.page_query.page_size as i64, offset as i64)
+        .find_projects(search.filter(), search.page_query.page_size as i64, offset as i64)
         .await?;
 
     // 使用OK返回成功的结果
@@ -83,6 +85,75 @@ pub async fn find_projects<PS: ProjectServiceTrait>(
     }))
 }
 
+/// 按相关度搜索项目
+///
+/// 与 [`find_projects`] 的区别：
+/// - 同时搜索项目名称和项目说明
+/// - 基于`pg_trgm`的相似度匹配，能够容忍拼写错误
+/// - 结果按相关度从高到低排序，并返回命中片段高亮
+#[utoipa::path(post,
+    path = "/search-projects/ranked",
+    tag = "projects",
+    request_body = ProjectRankedSearch,
+    responses(
+        (status = 200, description = "Ranked search results", body = ReplyList<ProjectSearchHit>)
+    ),
+)]
+pub async fn rank_search_projects<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Json(search): Json<ProjectRankedSearch>,
+) -> Result<Json<ReplyList<ProjectSearchHit>>, AppError> {
+    debug!("🔍 相关度搜索项目 {:#?}", search);
+
+    search.validate()?;
+
+    let offset = (search.page_query.page_index.saturating_sub(1)) * search.page_query.page_size;
+    let threshold = search
+        .threshold
+        .unwrap_or_else(|| state.reloader.current().search.similarity_threshold);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let result = project_service
+        .search_projects_ranked(search.keyword, threshold, search.page_query.page_size as i64, offset as i64)
+        .await?;
+
+    Ok(Json(ReplyList {
+        total: result.total,
+        data: result.hits.into_iter().map(Into::into).collect(),
+        page_size: search.page_query.page_size,
+        page_index: search.page_query.page_index,
+    }))
+}
+
+/// 项目名称输入提示
+///
+/// 用于输入框联想，返回与输入内容最接近的前N个项目名称（去重）
+#[utoipa::path(get,
+    path = "/projects/suggestions",
+    tag = "projects",
+    params(ProjectSuggestQuery),
+    responses(
+        (status = 200, description = "Suggested project names", body = Reply<Vec<String>>)
+    ),
+)]
+pub async fn suggest_projects<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Query(query): Query<ProjectSuggestQuery>,
+) -> Result<Json<Reply<Vec<String>>>, AppError> {
+    debug!("🔍 项目名称提示 {:#?}", query);
+
+    query.validate()?;
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let names = project_service
+        .suggest_project_names(query.q, query.limit.unwrap_or(10) as i64)
+        .await?;
+
+    Ok(Json(Reply { data: names }))
+}
+
 /// 创建项目
 ///
 /// 根据用户输入参数创建项目信息
@@ -94,16 +165,18 @@ pub async fn find_projects<PS: ProjectServiceTrait>(
     )
 )]
 pub async fn create_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
     State(state): State<AppState<PS>>,
     Json(project): Json<ProjectCreate>,
 ) -> Result<Json<Reply<ProjectInfo>>, AppError> {
     debug!("Creating project {:#?}", project);
 
     // 获取项目服务实例
-    let project_service = Arc::clone(&state.project_service);
+    let project_service = state.project_service.with_tenant(&tenant);
     let db_project = database::models::ProjectCreate {
         project_name: project.project_name,
         comment: project.comment,
+        parent_id: project.parent_id,
     };
     let project = project_service.create_project(db_project).await?;
 
@@ -113,12 +186,13 @@ pub async fn create_project<PS: ProjectServiceTrait>(
 /// 查询指定项目信息
 #[utoipa::path(get, path = "/projects/{id}", tag = "projects")]
 pub async fn get_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
     State(state): State<AppState<PS>>,
     Path(project_id): Path<i32>,
 ) -> Result<Json<ProjectInfo>, AppError> {
     debug!("Getting project id {:#?}", project_id);
 
-    let project_service = Arc::clone(&state.project_service);
+    let project_service = state.project_service.with_tenant(&tenant);
     let project = project_service.get_project_by_id(project_id).await?;
 
     Ok(Json(project.into()))
@@ -130,13 +204,14 @@ pub async fn get_project<PS: ProjectServiceTrait>(
 ///
 #[utoipa::path(patch, path = "/projects/{id}", tag = "projects")]
 pub async fn update_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
     State(state): State<AppState<PS>>,
     Path(project_id): Path<i32>,
     Json(info): Json<ProjectUpdate>,
 ) -> Result<Json<ProjectInfo>, AppError> {
     debug!("Updating project {} with {:#?}", project_id, info);
 
-    let project_service = Arc::clone(&state.project_service);
+    let project_service = state.project_service.with_tenant(&tenant);
     let db_update = database::models::ProjectUpdate {
         project_name: info.project_name,
         comment: info.comment,
@@ -149,13 +224,146 @@ pub async fn update_project<PS: ProjectServiceTrait>(
 /// 删除指定的项目
 #[utoipa::path(delete, path = "/projects/{id}", tag = "projects")]
 pub async fn delete_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
     State(state): State<AppState<PS>>,
     Path(project_id): Path<i32>,
 ) -> Result<Json<ProjectInfo>, AppError> {
     debug!("delete project {:#?}", project_id);
 
-    let project_service = Arc::clone(&state.project_service);
+    let project_service = state.project_service.with_tenant(&tenant);
     let project = project_service.delete_project(project_id).await?;
 
     Ok(Json(project.into()))
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::common::{Reply, ReplyList};
+    use crate::models::projects::{ProjectInfo, ProjectSearchHit};
+    use crate::test_support::{TestApp, test_config_source};
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode, header};
+    use serde_json::json;
+
+    async fn create(app: &TestApp, name: &str) -> ProjectInfo {
+        let response = app
+            .post_json("/api/v1/projects", &json!({"project_name": name, "comment": "comment"}))
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+        response.json::<Reply<ProjectInfo>>().data
+    }
+
+    #[tokio::test]
+    async fn test_find_projects() {
+        let app = TestApp::new();
+        for name in ["pay-1", "pay-2", "pay-3", "other"] {
+            create(&app, name).await;
+        }
+
+        let search = json!({"project_name": "pay", "page_query": {"page_index": 2, "page_size": 2}});
+        let response = app.post_json("/api/v1/search-projects", &search).await;
+        assert_eq!(response.status, StatusCode::OK);
+
+        let page: ReplyList<ProjectInfo> = response.json();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.page_index, 2);
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.data[0].project_name, "pay-3");
+    }
+
+    #[tokio::test]
+    async fn test_rank_search_default_threshold() {
+        let app = TestApp::new();
+        create(&app, "payments").await;
+
+        let search = json!({"keyword": "paymnts", "page_query": {"page_index": 1, "page_size": 10}});
+        let response = app.post_json("/api/v1/search-projects/ranked", &search).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+        assert_eq!(response.json::<ReplyList<ProjectSearchHit>>().total, 1);
+
+        // 请求中没有指定阈值时使用配置的默认值，修改配置后下一个请求生效
+        app.reloader
+            .apply(test_config_source(&[("search.similarity_threshold", "0.9")]))
+            .unwrap();
+        let response = app.post_json("/api/v1/search-projects/ranked", &search).await;
+        assert_eq!(response.json::<ReplyList<ProjectSearchHit>>().total, 0);
+
+        // 请求中指定的阈值优先
+        let search = json!({"keyword": "paymnts", "threshold": 0.3, "page_query": {"page_index": 1, "page_size": 10}});
+        let response = app.post_json("/api/v1/search-projects/ranked", &search).await;
+        assert_eq!(response.json::<ReplyList<ProjectSearchHit>>().total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_validation_failed() {
+        let app = TestApp::new();
+
+        let search = json!({"project_name": "pay", "page_query": {"page_index": 1, "page_size": 0}});
+        let response = app.post_json("/api/v1/search-projects", &search).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert!(response.text().starts_with("Validate failed"), "{}", response.text());
+
+        // 缺少必填字段时由Json提取器直接拒绝
+        let response = app.post_json("/api/v1/projects", &json!({"project_name": "pay"})).await;
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_localized_errors() {
+        let app = TestApp::new();
+
+        let request = Request::post("/api/v1/search-projects")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT_LANGUAGE, "zh-CN,zh;q=0.9,en;q=0.8")
+            .body(Body::from(
+                json!({"project_name": "pay", "page_query": {"page_index": 1, "page_size": 0}}).to_string(),
+            ))
+            .unwrap();
+        let response = app.request(request).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers[header::CONTENT_LANGUAGE], "zh-CN");
+        assert_eq!(response.text(), "参数校验失败: page_query.page_size: 必须在 1 到 100 之间");
+
+        // 不支持的语言使用默认语言
+        let request = Request::get("/api/v1/projects/404")
+            .header(header::ACCEPT_LANGUAGE, "fr-FR")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.request(request).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+        assert_eq!(response.headers[header::CONTENT_LANGUAGE], "en-US");
+        assert_eq!(response.text(), "Record not found");
+
+        // 5xx错误不返回原始错误，避免混入其他语言的错误信息
+        app.repository.set_unavailable(true);
+        let request = Request::get("/api/v1/projects/1")
+            .header(header::ACCEPT_LANGUAGE, "en-US")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.request(request).await;
+        assert_eq!(response.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers[header::CONTENT_LANGUAGE], "en-US");
+        assert_eq!(response.text(), "Repository error");
+    }
+
+    #[tokio::test]
+    async fn test_project_not_found() {
+        let app = TestApp::new();
+        let project = create(&app, "pay").await;
+
+        let response = app.get(&format!("/api/v1/projects/{}", project.id)).await;
+        assert_eq!(response.json::<ProjectInfo>().project_name, "pay");
+
+        let response = app.delete(&format!("/api/v1/projects/{}", project.id)).await;
+        assert_eq!(response.status, StatusCode::OK);
+
+        for response in [
+            app.get(&format!("/api/v1/projects/{}", project.id)).await,
+            app.patch_json(&format!("/api/v1/projects/{}", project.id), &json!({"comment": "new"}))
+                .await,
+            app.delete(&format!("/api/v1/projects/{}", project.id)).await,
+        ] {
+            assert_eq!(response.status, StatusCode::NOT_FOUND, "{}", response.text());
+        }
+    }
+}
@@ -0,0 +1,444 @@
+//! 项目附件相关接口（v2）
+//!
+//! 附件内容保存在附件存储中（参考 [`storage`]），元数据保存在数据库中：
+//! - 上传使用 `multipart/form-data`，文件放在 `file` 字段中，大小限制为 `STORAGE_MAX_UPLOAD_SIZE`
+//! - 下载以流的方式返回内容，不会一次性读入内存，支持单个范围的 `Range` 请求（断点续传）和 `If-Range`
+
+use crate::auth::Tenant;
+use crate::models::attachments::{AttachmentFile, AttachmentInfo, AttachmentUpload};
+use crate::models::common::Reply;
+use crate::models::err::AppError;
+use crate::{AppState, services::ProjectServiceTrait};
+use axum::Json;
+use axum::body::Body;
+use axum::extract::{Multipart, Path, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use color_eyre::Result;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use std::ops::Range;
+use tracing::debug;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+/// 上传文件所在的multipart字段
+const FILE_FIELD: &str = "file";
+
+/// 客户端没有提供内容类型时使用的默认值
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// `filename*` 参数中不需要编码的字符，参考 [RFC 8187](https://www.rfc-editor.org/rfc/rfc8187) 的 `attr-char`
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// 查询项目的所有附件，按上传顺序排序
+#[utoipa::path(get,
+    path = "/projects/{id}/attachments",
+    tag = "attachments",
+    params(("id" = i32, Path, description = "项目ID")),
+    responses(
+        (status = 200, description = "Attachments of the project", body = Reply<Vec<AttachmentInfo>>),
+        (status = 404, description = "Project not found")
+    )
+)]
+pub async fn find_attachments<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(project_id): Path<i32>,
+) -> Result<Json<Reply<Vec<AttachmentInfo>>>, AppError> {
+    debug!("🔍 查询项目 {} 的附件", project_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let attachments = project_service.find_attachments(project_id).await?;
+
+    Ok(Json(Reply {
+        data: attachments.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// 上传附件
+///
+/// 请求体超过 `STORAGE_MAX_UPLOAD_SIZE` 时返回413。其他multipart字段会被忽略
+#[utoipa::path(post,
+    path = "/projects/{id}/attachments",
+    tag = "attachments",
+    params(("id" = i32, Path, description = "项目ID")),
+    request_body(content = AttachmentUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Uploaded attachment", body = Reply<AttachmentInfo>),
+        (status = 400, description = "Missing `file` field or invalid file name"),
+        (status = 404, description = "Project not found"),
+        (status = 413, description = "Attachment is too large")
+    )
+)]
+pub async fn upload_attachment<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(project_id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<Json<Reply<AttachmentInfo>>, AppError> {
+    debug!("📎 上传项目 {} 的附件", project_id);
+
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() != Some(FILE_FIELD) {
+            continue;
+        }
+
+        let file = AttachmentFile {
+            file_name: base_name(field.file_name().unwrap_or_default()).to_string(),
+            content_type: field.content_type().unwrap_or(DEFAULT_CONTENT_TYPE).to_string(),
+        };
+        file.validate()?;
+
+        let project_service = state.project_service.with_tenant(&tenant);
+        let attachment = project_service
+            .upload_attachment(project_id, file.file_name, file.content_type, field)
+            .await?;
+
+        return Ok(Json(Reply { data: attachment.into() }));
+    }
+
+    let mut errors = ValidationErrors::new();
+    errors.add(FILE_FIELD, ValidationError::new("required"));
+    Err(errors.into())
+}
+
+/// 下载附件
+///
+/// 支持 `Range: bytes=<start>-<end>`、`bytes=<start>-`、`bytes=-<suffix>` 三种形式的单个范围，
+/// 多个范围或者格式不正确时返回完整内容。`If-Range` 只支持ETag，与当前ETag不一致时返回完整内容
+#[utoipa::path(get,
+    path = "/projects/{id}/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("id" = i32, Path, description = "项目ID"),
+        ("attachment_id" = i32, Path, description = "附件ID"),
+        ("Range" = Option<String>, Header, description = "读取的字节范围，例如 `bytes=0-1023`"),
+        ("If-Range" = Option<String>, Header, description = "ETag，与附件当前的ETag一致时才使用 `Range`")
+    ),
+    responses(
+        (status = 200, description = "Attachment content", content_type = "application/octet-stream", body = [u8]),
+        (status = 206, description = "Partial attachment content", content_type = "application/octet-stream", body = [u8]),
+        (status = 404, description = "Project or attachment not found"),
+        (status = 416, description = "Range not satisfiable")
+    )
+)]
+pub async fn download_attachment<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path((project_id, attachment_id)): Path<(i32, i32)>,
+    request_headers: HeaderMap,
+) -> Result<Response, AppError> {
+    debug!("📥 下载项目 {} 的附件 {}", project_id, attachment_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let attachment = project_service.get_attachment(project_id, attachment_id).await?;
+
+    let size = attachment.size as u64;
+    let etag = format!("\"{}\"", attachment.sha256);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(header::ETAG, header_value(&etag));
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&attachment.content_type).unwrap_or(HeaderValue::from_static(DEFAULT_CONTENT_TYPE)),
+    );
+    headers.insert(header::CONTENT_DISPOSITION, content_disposition(&attachment.file_name));
+
+    let if_range_matches = request_headers
+        .get(header::IF_RANGE)
+        .is_none_or(|if_range| if_range.as_bytes() == etag.as_bytes());
+    let range = match request_headers.get(header::RANGE).and_then(|range| range.to_str().ok()) {
+        Some(range) if if_range_matches => parse_range(range, size),
+        _ => RangeRequest::Full,
+    };
+
+    let (status, range) = match range {
+        RangeRequest::Full => (StatusCode::OK, None),
+        RangeRequest::Partial(range) => {
+            headers.insert(
+                header::CONTENT_RANGE,
+                header_value(&format!("bytes {}-{}/{size}", range.start, range.end - 1)),
+            );
+            (StatusCode::PARTIAL_CONTENT, Some(range))
+        }
+        RangeRequest::Unsatisfiable => {
+            headers.insert(header::CONTENT_RANGE, header_value(&format!("bytes */{size}")));
+            return Ok((StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response());
+        }
+    };
+
+    let content_length = range.as_ref().map_or(size, |range| range.end - range.start);
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(content_length));
+
+    let body = project_service.read_attachment(&attachment, range).await?;
+
+    Ok((status, headers, Body::from_stream(body)).into_response())
+}
+
+/// 删除附件
+#[utoipa::path(delete,
+    path = "/projects/{id}/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("id" = i32, Path, description = "项目ID"),
+        ("attachment_id" = i32, Path, description = "附件ID")
+    ),
+    responses(
+        (status = 200, description = "Deleted attachment", body = Reply<AttachmentInfo>),
+        (status = 404, description = "Project or attachment not found")
+    )
+)]
+pub async fn delete_attachment<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path((project_id, attachment_id)): Path<(i32, i32)>,
+) -> Result<Json<Reply<AttachmentInfo>>, AppError> {
+    debug!("🗑️ 删除项目 {} 的附件 {}", project_id, attachment_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let attachment = project_service.delete_attachment(project_id, attachment_id).await?;
+
+    Ok(Json(Reply { data: attachment.into() }))
+}
+
+/// `Range` 请求头的解析结果
+#[derive(Debug, PartialEq)]
+enum RangeRequest {
+    /// 返回完整内容
+    Full,
+    /// 返回部分内容 `[start, end)`
+    Partial(Range<u64>),
+    /// 范围超出内容长度，返回416
+    Unsatisfiable,
+}
+
+/// 解析 `Range` 请求头，参考 [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#name-range)
+///
+/// 只支持单个范围，服务端可以忽略不支持的 `Range`，所以多个范围和格式错误时都返回完整内容
+fn parse_range(range: &str, size: u64) -> RangeRequest {
+    let Some(spec) = range
+        .split_once('=')
+        .filter(|(unit, _)| unit.trim().eq_ignore_ascii_case("bytes"))
+        .map(|(_, spec)| spec.trim())
+    else {
+        return RangeRequest::Full;
+    };
+    let Some((start, end)) = spec.split_once('-').filter(|_| !spec.contains(',')) else {
+        return RangeRequest::Full;
+    };
+
+    match (start.trim(), end.trim()) {
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => RangeRequest::Unsatisfiable,
+            Ok(_) if size == 0 => RangeRequest::Unsatisfiable,
+            Ok(suffix) => RangeRequest::Partial(size - suffix.min(size)..size),
+            Err(_) => RangeRequest::Full,
+        },
+        (start, end) => {
+            let Ok(start) = start.parse::<u64>() else {
+                return RangeRequest::Full;
+            };
+            let end = match end {
+                "" => size,
+                end => match end.parse::<u64>() {
+                    Ok(end) if end >= start => end.saturating_add(1).min(size),
+                    _ => return RangeRequest::Full,
+                },
+            };
+
+            if start >= size {
+                RangeRequest::Unsatisfiable
+            } else {
+                RangeRequest::Partial(start..end)
+            }
+        }
+    }
+}
+
+/// 去掉客户端文件名中的目录（部分浏览器会带上完整路径）
+fn base_name(file_name: &str) -> &str {
+    file_name.rsplit(['/', '\\']).next().unwrap_or_default().trim()
+}
+
+/// 生成 `Content-Disposition` 响应头
+///
+/// `filename` 参数只能使用ASCII字符，非ASCII字符替换为 `_`，完整的文件名放在 `filename*` 参数中
+fn content_disposition(file_name: &str) -> HeaderValue {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let encoded = utf8_percent_encode(file_name, ATTR_CHAR);
+
+    header_value(&format!("attachment; filename=\"{fallback}\"; filename*=UTF-8''{encoded}"))
+}
+
+/// 创建响应头，内容都是ASCII字符，不会失败
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).expect("header value should be visible ASCII")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::projects::ProjectDetail;
+    use crate::test_support::{TEST_MAX_UPLOAD_SIZE, TestApp};
+    use axum::http::{Method, Request};
+    use futures::StreamExt;
+    use serde_json::json;
+    use shared_lib::models::config::MiddlewareConfig;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), RangeRequest::Partial(0..10));
+        assert_eq!(parse_range("bytes=90-", 100), RangeRequest::Partial(90..100));
+        assert_eq!(parse_range("bytes=90-200", 100), RangeRequest::Partial(90..100));
+        assert_eq!(parse_range("bytes=-10", 100), RangeRequest::Partial(90..100));
+        assert_eq!(parse_range("bytes=-200", 100), RangeRequest::Partial(0..100));
+        assert_eq!(parse_range("bytes=100-", 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-", 0), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-1,5-6", 100), RangeRequest::Full);
+        assert_eq!(parse_range("bytes=9-0", 100), RangeRequest::Full);
+        assert_eq!(parse_range("items=0-9", 100), RangeRequest::Full);
+    }
+
+    #[tokio::test]
+    async fn test_upload_timeout() {
+        // 上传接口使用上传超时时间，慢速客户端不受请求超时时间限制
+        let app = TestApp::with_middleware_config(&MiddlewareConfig {
+            request_timeout: Duration::from_millis(100),
+            upload_timeout: Duration::from_secs(5),
+            ..MiddlewareConfig::default()
+        });
+        let response = app
+            .post_json("/api/v2/projects", &json!({"project_name": "pay", "comment": ""}))
+            .await;
+        let project = response.json::<Reply<ProjectDetail>>().data;
+
+        // 请求体分两次发送，间隔超过请求超时时间
+        let slow_request = |uri: &str, content_type: &str, chunks: [&'static str; 2]| {
+            let body = futures::stream::iter(chunks).then(|chunk| async move {
+                tokio::time::sleep(Duration::from_millis(150)).await;
+                Ok::<_, std::io::Error>(chunk)
+            });
+            Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from_stream(body))
+                .unwrap()
+        };
+
+        let request = slow_request(
+            &format!("/api/v2/projects/{}/attachments", project.id),
+            "multipart/form-data; boundary=b",
+            [
+                "--b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\r\n",
+                "hello\r\n--b--\r\n",
+            ],
+        );
+        let response = app.request(request).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+
+        let request = slow_request(
+            "/api/v2/projects",
+            "application/json",
+            [r#"{"project_name": "#, r#""slow", "comment": ""}"#],
+        );
+        assert_eq!(app.request(request).await.status, StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_attachments() {
+        // 附件上传使用单独的大小限制，不受WEB_MAX_BODY_SIZE限制
+        let app = TestApp::with_middleware_config(&MiddlewareConfig {
+            max_body_size: 1024,
+            ..MiddlewareConfig::default()
+        });
+        let response = app
+            .post_json("/api/v2/projects", &json!({"project_name": "pay", "comment": ""}))
+            .await;
+        let project = response.json::<Reply<ProjectDetail>>().data;
+        let uri = format!("/api/v2/projects/{}/attachments", project.id);
+
+        let response = app
+            .post_file(&uri, "C:\\fakepath\\需求 文档.txt", "text/plain", b"hello world")
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+        let attachment = response.json::<Reply<AttachmentInfo>>().data;
+        assert_eq!(attachment.file_name, "需求 文档.txt");
+        assert_eq!(attachment.size, 11);
+        assert_eq!(app.storage.keys().len(), 1);
+
+        let download_uri = format!("{uri}/{}", attachment.id);
+        let response = app.get(&download_uri).await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(response.text(), "hello world");
+        assert_eq!(
+            response.headers[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"__ __.txt\"; filename*=UTF-8''%E9%9C%80%E6%B1%82%20%E6%96%87%E6%A1%A3.txt"
+        );
+
+        let range_request = |range: &str, if_range: &str| {
+            Request::builder()
+                .method(Method::GET)
+                .uri(&download_uri)
+                .header(header::RANGE, range)
+                .header(header::IF_RANGE, if_range)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let etag = format!("\"{}\"", attachment.sha256);
+        let response = app.request(range_request("bytes=6-", &etag)).await;
+        assert_eq!(response.status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.text(), "world");
+        assert_eq!(response.headers[header::CONTENT_RANGE], "bytes 6-10/11");
+
+        let response = app.request(range_request("bytes=6-", "\"outdated\"")).await;
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(response.text(), "hello world");
+
+        let response = app.request(range_request("bytes=20-", &etag)).await;
+        assert_eq!(response.status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers[header::CONTENT_RANGE], "bytes */11");
+
+        let response = app.post_file(&uri, "large.bin", "application/octet-stream", &[0; 4096]).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+        let response = app
+            .post_file(&uri, "huge.bin", "application/octet-stream", &vec![0; TEST_MAX_UPLOAD_SIZE])
+            .await;
+        assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(app.storage.keys().len(), 2);
+
+        let response = app.post_file("/api/v2/projects/999/attachments", "a.txt", "text/plain", b"a").await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+        let response = app.get(&uri).await;
+        assert_eq!(response.json::<Reply<Vec<AttachmentInfo>>>().data.len(), 2);
+
+        // 删除项目时同时删除附件内容
+        app.delete(&format!("/api/v2/projects/{}", project.id)).await;
+        assert!(app.storage.keys().is_empty());
+        assert_eq!(app.get(&download_uri).await.status, StatusCode::NOT_FOUND);
+    }
+}
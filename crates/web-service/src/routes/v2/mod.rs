@@ -0,0 +1,68 @@
+//! v2版本路由
+//!
+//! 只包含响应格式发生变化的接口和新增的接口（例如标签、项目层级、附件），其余接口（例如名称提示）直接复用v1的handler。
+
+use crate::middleware::upload_timeout;
+use crate::routes::projects::__path_suggest_projects;
+use crate::routes::projects::suggest_projects;
+use crate::routes::v2::attachments::__path_delete_attachment;
+use crate::routes::v2::attachments::__path_download_attachment;
+use crate::routes::v2::attachments::__path_find_attachments;
+use crate::routes::v2::attachments::__path_upload_attachment;
+use crate::routes::v2::attachments::{delete_attachment, download_attachment, find_attachments, upload_attachment};
+use crate::routes::v2::projects::__path_create_project;
+use crate::routes::v2::projects::__path_delete_project;
+use crate::routes::v2::projects::__path_find_projects;
+use crate::routes::v2::projects::__path_get_project;
+use crate::routes::v2::projects::__path_get_project_ancestors;
+use crate::routes::v2::projects::__path_get_project_tree;
+use crate::routes::v2::projects::__path_move_project;
+use crate::routes::v2::projects::__path_rank_search_projects;
+use crate::routes::v2::projects::__path_update_project;
+use crate::routes::v2::projects::{
+    create_project, delete_project, find_projects, get_project, get_project_ancestors, get_project_tree, move_project,
+    rank_search_projects, update_project,
+};
+use crate::routes::v2::tags::__path_attach_tag;
+use crate::routes::v2::tags::__path_create_tag;
+use crate::routes::v2::tags::__path_delete_tag;
+use crate::routes::v2::tags::__path_detach_tag;
+use crate::routes::v2::tags::__path_find_tags;
+use crate::routes::v2::tags::__path_get_tag;
+use crate::routes::v2::tags::__path_update_tag;
+use crate::routes::v2::tags::{attach_tag, create_tag, delete_tag, detach_tag, find_tags, get_tag, update_tag};
+use crate::{AppState, services::ProjectServiceTrait};
+use axum::extract::DefaultBodyLimit;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+pub mod attachments;
+pub mod projects;
+pub mod tags;
+
+/// 导出v2版本的所有路由
+///
+/// 注意事项与 [`crate::routes`] 中的v1路由一致：同一个 [`routes!`] 宏中不能定义多个相同类型的http接口
+pub fn routers<PS: ProjectServiceTrait>(state: AppState<PS>) -> OpenApiRouter {
+    // 附件上传使用单独的请求体大小限制和超时时间，内层的设置会覆盖中间件中的全局设置
+    let upload_routers = OpenApiRouter::new()
+        .routes(routes!(upload_attachment))
+        .layer(DefaultBodyLimit::max(state.max_upload_size))
+        .layer(axum::middleware::map_request(upload_timeout));
+
+    OpenApiRouter::new()
+        .merge(upload_routers)
+        .routes(routes!(find_projects))
+        .routes(routes!(rank_search_projects))
+        .routes(routes!(suggest_projects))
+        .routes(routes!(get_project, create_project, update_project, delete_project))
+        .routes(routes!(get_project_ancestors))
+        .routes(routes!(get_project_tree))
+        .routes(routes!(move_project))
+        .routes(routes!(find_tags, create_tag))
+        .routes(routes!(get_tag, update_tag, delete_tag))
+        .routes(routes!(attach_tag, detach_tag))
+        .routes(routes!(find_attachments))
+        .routes(routes!(download_attachment, delete_attachment))
+        .with_state(state)
+}
@@ -0,0 +1,332 @@
+//! 项目相关接口（v2）
+//!
+//! 与v1的区别：
+//! - 所有接口统一使用 [`Reply`] / [`ReplyList`] 包装返回值
+//! - 返回 [`ProjectDetail`]，包含创建时间和更新时间
+//! - 新增项目层级相关接口：查询祖先、查询子树、移动项目
+//!
+//! 业务逻辑与v1共用同一个 [`ProjectServiceTrait`]，这里只负责参数和返回值的转换。
+
+use crate::auth::Tenant;
+use crate::models::common::{Reply, ReplyList};
+use crate::models::err::AppError;
+use crate::models::projects::{
+    ProjectCreate, ProjectDetail, ProjectDetailSearchHit, ProjectMove, ProjectRankedSearch, ProjectSearch, ProjectTree, ProjectUpdate,
+};
+use crate::{AppState, services::ProjectServiceTrait};
+use axum::Json;
+use axum::extract::{Path, State};
+use color_eyre::Result;
+use tracing::debug;
+use validator::Validate;
+
+/// 根据查询参数搜索项目
+#[utoipa::path(post,
+    path = "/search-projects",
+    tag = "projects",
+    request_body = ProjectSearch,
+    responses(
+        (status = 200, description = "Search results", body = ReplyList<ProjectDetail>)
+    ),
+)]
+pub async fn find_projects<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Json(search): Json<ProjectSearch>,
+) -> Result<Json<ReplyList<ProjectDetail>>, AppError> {
+    debug!("🔍 搜索项目(v2) {:#?}", search);
+
+    search.validate()?;
+
+    let offset = (search.page_query.page_index.saturating_sub(1)) * search.page_query.page_size;
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let result = project_service
+        .find_projects(search.filter(), search.page_query.page_size as i64, offset as i64)
+        .await?;
+
+    Ok(Json(ReplyList {
+        total: result.total,
+        data: result.projects.into_iter().map(Into::into).collect(),
+        page_size: search.page_query.page_size,
+        page_index: search.page_query.page_index,
+    }))
+}
+
+/// 按相关度搜索项目
+///
+/// 请求参数与v1相同，没有指定 `threshold` 时使用配置项 `search.similarity_threshold`
+#[utoipa::path(post,
+    path = "/search-projects/ranked",
+    tag = "projects",
+    request_body = ProjectRankedSearch,
+    responses(
+        (status = 200, description = "Ranked search results", body = ReplyList<ProjectDetailSearchHit>)
+    ),
+)]
+pub async fn rank_search_projects<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Json(search): Json<ProjectRankedSearch>,
+) -> Result<Json<ReplyList<ProjectDetailSearchHit>>, AppError> {
+    debug!("🔍 相关度搜索项目(v2) {:#?}", search);
+
+    search.validate()?;
+
+    let offset = (search.page_query.page_index.saturating_sub(1)) * search.page_query.page_size;
+    let threshold = search
+        .threshold
+        .unwrap_or_else(|| state.reloader.current().search.similarity_threshold);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let result = project_service
+        .search_projects_ranked(search.keyword, threshold, search.page_query.page_size as i64, offset as i64)
+        .await?;
+
+    Ok(Json(ReplyList {
+        total: result.total,
+        data: result.hits.into_iter().map(Into::into).collect(),
+        page_size: search.page_query.page_size,
+        page_index: search.page_query.page_index,
+    }))
+}
+
+/// 创建项目
+#[utoipa::path(post,
+    path = "/projects",
+    tag = "projects",
+    request_body = ProjectCreate,
+    responses(
+        (status = 200, description = "Create project result", body = Reply<ProjectDetail>)
+    )
+)]
+pub async fn create_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Json(project): Json<ProjectCreate>,
+) -> Result<Json<Reply<ProjectDetail>>, AppError> {
+    debug!("Creating project(v2) {:#?}", project);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let db_project = database::models::ProjectCreate {
+        project_name: project.project_name,
+        comment: project.comment,
+        parent_id: project.parent_id,
+    };
+    let project = project_service.create_project(db_project).await?;
+
+    Ok(Json(Reply { data: project.into() }))
+}
+
+/// 查询指定项目信息
+#[utoipa::path(get,
+    path = "/projects/{id}",
+    tag = "projects",
+    params(("id" = i32, Path, description = "项目ID")),
+    responses(
+        (status = 200, description = "Project detail", body = Reply<ProjectDetail>),
+        (status = 404, description = "Project not found")
+    )
+)]
+pub async fn get_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(project_id): Path<i32>,
+) -> Result<Json<Reply<ProjectDetail>>, AppError> {
+    debug!("Getting project(v2) id {:#?}", project_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let project = project_service.get_project_by_id(project_id).await?;
+
+    Ok(Json(Reply { data: project.into() }))
+}
+
+/// 更新项目信息
+#[utoipa::path(patch,
+    path = "/projects/{id}",
+    tag = "projects",
+    params(("id" = i32, Path, description = "项目ID")),
+    request_body = ProjectUpdate,
+    responses(
+        (status = 200, description = "Updated project", body = Reply<ProjectDetail>),
+        (status = 404, description = "Project not found")
+    )
+)]
+pub async fn update_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(project_id): Path<i32>,
+    Json(info): Json<ProjectUpdate>,
+) -> Result<Json<Reply<ProjectDetail>>, AppError> {
+    debug!("Updating project(v2) {} with {:#?}", project_id, info);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let db_update = database::models::ProjectUpdate {
+        project_name: info.project_name,
+        comment: info.comment,
+    };
+    let project = project_service.update_project(project_id, db_update).await?;
+
+    Ok(Json(Reply { data: project.into() }))
+}
+
+/// 删除指定的项目
+///
+/// 子项目不会被删除，而是挂到被删除项目的父项目下（顶层项目的子项目变为顶层项目）
+#[utoipa::path(delete,
+    path = "/projects/{id}",
+    tag = "projects",
+    params(("id" = i32, Path, description = "项目ID")),
+    responses(
+        (status = 200, description = "Deleted project", body = Reply<ProjectDetail>),
+        (status = 404, description = "Project not found")
+    )
+)]
+pub async fn delete_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(project_id): Path<i32>,
+) -> Result<Json<Reply<ProjectDetail>>, AppError> {
+    debug!("delete project(v2) {:#?}", project_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let project = project_service.delete_project(project_id).await?;
+
+    Ok(Json(Reply { data: project.into() }))
+}
+
+/// 查询项目的所有祖先
+///
+/// 返回从顶层项目到直接父项目的列表，不包含项目自身
+#[utoipa::path(get,
+    path = "/projects/{id}/ancestors",
+    tag = "projects",
+    params(("id" = i32, Path, description = "项目ID")),
+    responses(
+        (status = 200, description = "Ancestors from the root project", body = Reply<Vec<ProjectDetail>>),
+        (status = 404, description = "Project not found")
+    )
+)]
+pub async fn get_project_ancestors<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(project_id): Path<i32>,
+) -> Result<Json<Reply<Vec<ProjectDetail>>>, AppError> {
+    debug!("Getting project ancestors(v2) id {:#?}", project_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let ancestors = project_service.get_project_ancestors(project_id).await?;
+
+    Ok(Json(Reply {
+        data: ancestors.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// 查询以项目为根的项目树
+#[utoipa::path(get,
+    path = "/projects/{id}/tree",
+    tag = "projects",
+    params(("id" = i32, Path, description = "项目ID")),
+    responses(
+        (status = 200, description = "Project with all its descendants", body = Reply<ProjectTree>),
+        (status = 404, description = "Project not found")
+    )
+)]
+pub async fn get_project_tree<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(project_id): Path<i32>,
+) -> Result<Json<Reply<ProjectTree>>, AppError> {
+    debug!("Getting project tree(v2) id {:#?}", project_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let subtree = project_service.get_project_subtree(project_id).await?;
+    let tree = ProjectTree::build(subtree).ok_or(sqlx::Error::RowNotFound)?;
+
+    Ok(Json(Reply { data: tree }))
+}
+
+/// 移动项目到新的父项目下
+///
+/// 子项目随项目一起移动。新的父项目是项目自身或其子孙时返回409
+#[utoipa::path(put,
+    path = "/projects/{id}/parent",
+    tag = "projects",
+    params(("id" = i32, Path, description = "项目ID")),
+    request_body = ProjectMove,
+    responses(
+        (status = 200, description = "Moved project", body = Reply<ProjectDetail>),
+        (status = 404, description = "Project or parent project not found"),
+        (status = 409, description = "The move would create a cycle")
+    )
+)]
+pub async fn move_project<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(project_id): Path<i32>,
+    Json(target): Json<ProjectMove>,
+) -> Result<Json<Reply<ProjectDetail>>, AppError> {
+    debug!("Moving project(v2) {} to {:#?}", project_id, target);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let project = project_service.move_project(project_id, target.parent_id).await?;
+
+    Ok(Json(Reply { data: project.into() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::common::{Reply, ReplyList};
+    use crate::models::projects::{ProjectDetail, ProjectDetailSearchHit, ProjectTree};
+    use crate::test_support::TestApp;
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_rank_search_projects() {
+        let app = TestApp::new();
+        let body = json!({"project_name": "payments", "comment": "handles payment callbacks"});
+        let project = app.post_json("/api/v2/projects", &body).await.json::<Reply<ProjectDetail>>().data;
+
+        let search = json!({"keyword": "payment", "page_query": {"page_index": 1, "page_size": 10}});
+        let response = app.post_json("/api/v2/search-projects/ranked", &search).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.text());
+
+        let page = response.json::<ReplyList<ProjectDetailSearchHit>>();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.data[0].project.id, project.id);
+        assert_eq!(page.data[0].project.created_at, project.created_at);
+        assert_eq!(page.data[0].highlight.project_name.as_deref(), Some("<em>payment</em>s"));
+    }
+
+    #[tokio::test]
+    async fn test_project_tree() {
+        let app = TestApp::new();
+
+        let mut parent_id = None;
+        let mut ids = Vec::new();
+        for name in ["program", "project", "sub-project"] {
+            let body = json!({"project_name": name, "comment": "", "parent_id": parent_id});
+            let project = app.post_json("/api/v2/projects", &body).await.json::<Reply<ProjectDetail>>().data;
+            parent_id = Some(project.id);
+            ids.push(project.id);
+        }
+
+        let response = app.get(&format!("/api/v2/projects/{}/ancestors", ids[2])).await;
+        let ancestors = response.json::<Reply<Vec<ProjectDetail>>>().data;
+        assert_eq!(ancestors.iter().map(|p| p.id).collect::<Vec<_>>(), ids[..2]);
+
+        let response = app.get(&format!("/api/v2/projects/{}/tree", ids[0])).await;
+        let tree = response.json::<Reply<ProjectTree>>().data;
+        assert_eq!(tree.project.project_name, "program");
+        assert_eq!(tree.children[0].children[0].project.id, ids[2]);
+
+        let response = app
+            .put_json(&format!("/api/v2/projects/{}/parent", ids[0]), &json!({"parent_id": ids[2]}))
+            .await;
+        assert_eq!(response.status, StatusCode::CONFLICT);
+
+        let body = json!({"project_name": "orphan", "comment": "", "parent_id": 999});
+        assert_eq!(app.post_json("/api/v2/projects", &body).await.status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,254 @@
+//! 标签相关接口（v2）
+//!
+//! 标签名称在租户内唯一，项目和标签是多对多的关系。项目搜索接口可以通过 `any_tags` / `all_tags` 按标签过滤，
+//! 参考 [`ProjectSearch`](crate::models::projects::ProjectSearch)。
+
+use crate::auth::Tenant;
+use crate::models::common::Reply;
+use crate::models::err::AppError;
+use crate::models::projects::ProjectDetail;
+use crate::models::tags::{TagCreate, TagInfo, TagUpdate};
+use crate::{AppState, services::ProjectServiceTrait};
+use axum::Json;
+use axum::extract::{Path, State};
+use color_eyre::Result;
+use tracing::debug;
+use validator::Validate;
+
+/// 查询所有标签，按名称排序
+#[utoipa::path(get,
+    path = "/tags",
+    tag = "tags",
+    responses(
+        (status = 200, description = "All tags", body = Reply<Vec<TagInfo>>)
+    )
+)]
+pub async fn find_tags<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+) -> Result<Json<Reply<Vec<TagInfo>>>, AppError> {
+    debug!("🔍 查询所有标签");
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let tags = project_service.find_tags().await?;
+
+    Ok(Json(Reply {
+        data: tags.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// 创建标签
+#[utoipa::path(post,
+    path = "/tags",
+    tag = "tags",
+    request_body = TagCreate,
+    responses(
+        (status = 200, description = "Created tag", body = Reply<TagInfo>),
+        (status = 409, description = "Tag name already exists")
+    )
+)]
+pub async fn create_tag<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Json(tag): Json<TagCreate>,
+) -> Result<Json<Reply<TagInfo>>, AppError> {
+    debug!("Creating tag {:#?}", tag);
+
+    tag.validate()?;
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let db_tag = database::models::TagCreate {
+        name: tag.name,
+        comment: tag.comment,
+    };
+    let tag = project_service.create_tag(db_tag).await?;
+
+    Ok(Json(Reply { data: tag.into() }))
+}
+
+/// 查询指定标签信息
+#[utoipa::path(get,
+    path = "/tags/{id}",
+    tag = "tags",
+    params(("id" = i32, Path, description = "标签ID")),
+    responses(
+        (status = 200, description = "Tag detail", body = Reply<TagInfo>),
+        (status = 404, description = "Tag not found")
+    )
+)]
+pub async fn get_tag<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(tag_id): Path<i32>,
+) -> Result<Json<Reply<TagInfo>>, AppError> {
+    debug!("Getting tag id {:#?}", tag_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let tag = project_service.get_tag_by_id(tag_id).await?;
+
+    Ok(Json(Reply { data: tag.into() }))
+}
+
+/// 更新标签信息
+#[utoipa::path(patch,
+    path = "/tags/{id}",
+    tag = "tags",
+    params(("id" = i32, Path, description = "标签ID")),
+    request_body = TagUpdate,
+    responses(
+        (status = 200, description = "Updated tag", body = Reply<TagInfo>),
+        (status = 404, description = "Tag not found"),
+        (status = 409, description = "Tag name already exists")
+    )
+)]
+pub async fn update_tag<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(tag_id): Path<i32>,
+    Json(info): Json<TagUpdate>,
+) -> Result<Json<Reply<TagInfo>>, AppError> {
+    debug!("Updating tag {} with {:#?}", tag_id, info);
+
+    info.validate()?;
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let db_update = database::models::TagUpdate {
+        name: info.name,
+        comment: info.comment,
+    };
+    let tag = project_service.update_tag(tag_id, db_update).await?;
+
+    Ok(Json(Reply { data: tag.into() }))
+}
+
+/// 删除指定的标签，同时解除与所有项目的关联
+#[utoipa::path(delete,
+    path = "/tags/{id}",
+    tag = "tags",
+    params(("id" = i32, Path, description = "标签ID")),
+    responses(
+        (status = 200, description = "Deleted tag", body = Reply<TagInfo>),
+        (status = 404, description = "Tag not found")
+    )
+)]
+pub async fn delete_tag<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path(tag_id): Path<i32>,
+) -> Result<Json<Reply<TagInfo>>, AppError> {
+    debug!("delete tag {:#?}", tag_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let tag = project_service.delete_tag(tag_id).await?;
+
+    Ok(Json(Reply { data: tag.into() }))
+}
+
+/// 为项目添加标签，标签已存在时不做修改
+#[utoipa::path(put,
+    path = "/projects/{id}/tags/{tag_id}",
+    tag = "tags",
+    params(
+        ("id" = i32, Path, description = "项目ID"),
+        ("tag_id" = i32, Path, description = "标签ID")
+    ),
+    responses(
+        (status = 200, description = "Project with its tags", body = Reply<ProjectDetail>),
+        (status = 404, description = "Project or tag not found")
+    )
+)]
+pub async fn attach_tag<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path((project_id, tag_id)): Path<(i32, i32)>,
+) -> Result<Json<Reply<ProjectDetail>>, AppError> {
+    debug!("Attaching tag {} to project {}", tag_id, project_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let project = project_service.attach_tag(project_id, tag_id).await?;
+
+    Ok(Json(Reply { data: project.into() }))
+}
+
+/// 移除项目的标签，项目没有该标签时不做修改
+#[utoipa::path(delete,
+    path = "/projects/{id}/tags/{tag_id}",
+    tag = "tags",
+    params(
+        ("id" = i32, Path, description = "项目ID"),
+        ("tag_id" = i32, Path, description = "标签ID")
+    ),
+    responses(
+        (status = 200, description = "Project with its tags", body = Reply<ProjectDetail>),
+        (status = 404, description = "Project or tag not found")
+    )
+)]
+pub async fn detach_tag<PS: ProjectServiceTrait>(
+    Tenant(tenant): Tenant,
+    State(state): State<AppState<PS>>,
+    Path((project_id, tag_id)): Path<(i32, i32)>,
+) -> Result<Json<Reply<ProjectDetail>>, AppError> {
+    debug!("Detaching tag {} from project {}", tag_id, project_id);
+
+    let project_service = state.project_service.with_tenant(&tenant);
+    let project = project_service.detach_tag(project_id, tag_id).await?;
+
+    Ok(Json(Reply { data: project.into() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::common::{Reply, ReplyList};
+    use crate::models::projects::ProjectDetail;
+    use crate::models::tags::TagInfo;
+    use crate::test_support::TestApp;
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tag_filters() {
+        let app = TestApp::new();
+
+        let mut projects = Vec::new();
+        for name in ["pay", "billing"] {
+            let response = app
+                .post_json("/api/v2/projects", &json!({"project_name": name, "comment": ""}))
+                .await;
+            projects.push(response.json::<Reply<ProjectDetail>>().data);
+        }
+        let mut tags = Vec::new();
+        for name in ["team:payments", "env:prod"] {
+            let response = app.post_json("/api/v2/tags", &json!({"name": name})).await;
+            assert_eq!(response.status, StatusCode::OK);
+            tags.push(response.json::<Reply<TagInfo>>().data);
+        }
+
+        let response = app.post_json("/api/v2/tags", &json!({"name": "env:prod"})).await;
+        assert_eq!(response.status, StatusCode::CONFLICT);
+
+        for tag in &tags {
+            let response = app.put(&format!("/api/v2/projects/{}/tags/{}", projects[0].id, tag.id)).await;
+            assert_eq!(response.status, StatusCode::OK);
+        }
+        let response = app.put(&format!("/api/v2/projects/{}/tags/{}", projects[1].id, tags[0].id)).await;
+        assert_eq!(response.json::<Reply<ProjectDetail>>().data.tags, vec!["team:payments"]);
+
+        let search = |tags: serde_json::Value| json!({"page_query": {"page_index": 1, "page_size": 10}, "all_tags": tags});
+        let response = app.post_json("/api/v2/search-projects", &search(json!(["team:payments"]))).await;
+        assert_eq!(response.json::<ReplyList<ProjectDetail>>().total, 2);
+        let response = app
+            .post_json("/api/v2/search-projects", &search(json!(["team:payments", "env:prod"])))
+            .await;
+        let page = response.json::<ReplyList<ProjectDetail>>();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.data[0].tags, vec!["env:prod", "team:payments"]);
+
+        let response = app
+            .delete(&format!("/api/v2/projects/{}/tags/{}", projects[0].id, tags[1].id))
+            .await;
+        assert_eq!(response.json::<Reply<ProjectDetail>>().data.tags, vec!["team:payments"]);
+
+        let response = app.put(&format!("/api/v2/projects/{}/tags/999", projects[0].id)).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,409 @@
+//! HTTP监听与连接处理
+//!
+//! 根据 [`ListenerConfig`] 创建监听器并处理连接，支持：
+//! - TCP 或 Unix domain socket 监听
+//! - 可选的TLS（rustls），证书文件变化后自动重新加载
+//! - 可选的HTTP/2
+//! - 优雅关闭：收到关闭信号后停止接收新连接，等待已有连接处理完毕
+//!
+//! 由于 [`axum::serve`] 不支持TLS，这里直接使用 `hyper-util` 处理连接，逻辑与 [`axum::serve`] 保持一致。
+
+use axum::Router;
+use color_eyre::Result;
+use color_eyre::eyre::Context;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::service::TowerToHyperService;
+use shared_lib::models::config::{ListenAddr, TlsConfig, WebConfig};
+use shared_lib::shutdown::wait_for_shutdown;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::sync::watch::Receiver;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tracing::{debug, info, warn};
+
+/// 检查证书文件是否变化的时间间隔
+const TLS_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+
+/// TLS握手的超时时间，避免建立TCP连接后不发送ClientHello的客户端一直占用连接，导致退出时等待超时
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 所有监听器上还没有关闭的连接数
+static OPEN_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// 所有监听器上还没有关闭的连接数，优雅退出超时时用于报告放弃了多少连接
+pub fn open_connections() -> usize {
+    OPEN_CONNECTIONS.load(Ordering::Relaxed)
+}
+
+/// 单个监听器的配置
+#[derive(Debug, Clone, Copy)]
+pub struct ListenerConfig<'a> {
+    /// 监听器名称，用于日志和错误信息
+    pub name: &'static str,
+
+    /// 监听地址
+    pub listen_addr: &'a ListenAddr,
+
+    /// TLS配置，为 `None` 时使用明文HTTP
+    pub tls: Option<&'a TlsConfig>,
+
+    /// 是否启用HTTP/2
+    pub http2: bool,
+}
+
+impl<'a> ListenerConfig<'a> {
+    /// 对外提供业务接口的监听器
+    pub fn public(config: &'a WebConfig) -> Self {
+        Self {
+            name: "Web Service",
+            listen_addr: &config.listen_addr,
+            tls: config.tls.as_ref(),
+            http2: config.http2,
+        }
+    }
+
+    /// 提供文档、健康检查等运维接口的监听器，固定使用明文HTTP/1.1
+    pub fn admin(config: &'a WebConfig) -> Self {
+        Self {
+            name: "Admin Service",
+            listen_addr: &config.admin_listen_addr,
+            tls: None,
+            http2: false,
+        }
+    }
+}
+
+/// 提供HTTP服务，直到收到关闭信号并且所有连接处理完毕
+pub async fn serve(router: Router, config: ListenerConfig<'_>, mut shutdown_rx: Receiver<bool>) -> Result<()> {
+    let name = config.name;
+    let listener = BoundListener::bind(name, config.listen_addr).await?;
+
+    let tls = match config.tls {
+        Some(tls_config) => Some(TlsReloader::start(tls_config.clone(), config.http2, shutdown_rx.clone())?),
+        None => None,
+    };
+
+    info!(
+        "🚀 启动 {} 在 {} (tls: {}, http2: {})",
+        name,
+        config.listen_addr,
+        tls.is_some(),
+        config.http2
+    );
+
+    let mut builder = auto::Builder::new(TokioExecutor::new());
+    if !config.http2 {
+        builder = builder.http1_only();
+    }
+    let builder = Arc::new(builder);
+
+    // 每个连接持有一个close_rx，所有连接结束后close_tx.closed()才会返回
+    let (close_tx, close_rx) = watch::channel(());
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(stream) => stream,
+                Err(err) => {
+                    // 文件句柄耗尽等错误是暂时的，稍等片刻后继续接收
+                    warn!("{} accept connection failed: {}", name, err);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            },
+            _ = wait_for_shutdown(&mut shutdown_rx) => break,
+        };
+
+        let connection = Connection {
+            acceptor: tls.as_ref().map(TlsReloader::acceptor),
+            builder: Arc::clone(&builder),
+            router: router.clone(),
+            shutdown_rx: shutdown_rx.clone(),
+            _close_rx: close_rx.clone(),
+        };
+
+        OPEN_CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+        tokio::spawn(async move {
+            let result = match stream {
+                AcceptedStream::Tcp(stream) => connection.serve(stream).await,
+                #[cfg(unix)]
+                AcceptedStream::Unix(stream) => connection.serve(stream).await,
+            };
+            OPEN_CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+            if let Err(err) = result {
+                debug!("{} connection closed with error: {}", name, err);
+            }
+        });
+    }
+
+    info!("🛑 {} 正在关闭...", name);
+
+    // 停止接收新连接，等待已有连接处理完毕
+    drop(listener);
+    drop(close_rx);
+    debug!("{}: waiting for {} connections to finish", name, close_tx.receiver_count());
+    close_tx.closed().await;
+
+    Ok(())
+}
+
+/// 已经绑定的监听器
+enum BoundListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+/// 接收到的连接
+enum AcceptedStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixStream),
+}
+
+impl BoundListener {
+    /// 绑定监听地址，失败时错误信息中会包含地址
+    async fn bind(name: &str, addr: &ListenAddr) -> Result<Self> {
+        match addr {
+            ListenAddr::Tcp(tcp_addr) => {
+                let listener = TcpListener::bind(tcp_addr)
+                    .await
+                    .with_context(|| format!("Failed to bind {name} to {addr}"))?;
+                Ok(BoundListener::Tcp(listener))
+            }
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => {
+                remove_stale_socket(path).with_context(|| format!("Failed to remove stale socket file for {addr}"))?;
+                let listener = tokio::net::UnixListener::bind(path).with_context(|| format!("Failed to bind {name} to {addr}"))?;
+                Ok(BoundListener::Unix(listener, path.clone()))
+            }
+            #[cfg(not(unix))]
+            ListenAddr::Unix(_) => Err(color_eyre::eyre::eyre!(
+                "Failed to bind {name} to {addr}: unix socket is not supported on this platform"
+            )),
+        }
+    }
+
+    async fn accept(&self) -> std::io::Result<AcceptedStream> {
+        match self {
+            BoundListener::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+                // 关闭Nagle算法，降低小响应的延迟（与axum::serve保持一致）
+                stream.set_nodelay(true)?;
+                Ok(AcceptedStream::Tcp(stream))
+            }
+            #[cfg(unix)]
+            BoundListener::Unix(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+                Ok(AcceptedStream::Unix(stream))
+            }
+        }
+    }
+}
+
+/// 删除上次异常退出遗留的socket文件，否则绑定会失败
+///
+/// 只删除没有进程监听的socket文件；普通文件或者其他实例正在使用的socket保持不变，绑定时返回地址已被占用的错误
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {}
+        _ => return Ok(()),
+    }
+    match std::os::unix::net::UnixStream::connect(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::ConnectionRefused => std::fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+impl Drop for BoundListener {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let BoundListener::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// 单个连接的处理上下文
+struct Connection {
+    acceptor: Option<TlsAcceptor>,
+    builder: Arc<auto::Builder<TokioExecutor>>,
+    router: Router,
+    shutdown_rx: Receiver<bool>,
+    /// 仅用于告知 [`serve`] 当前连接仍在处理中
+    _close_rx: Receiver<()>,
+}
+
+impl Connection {
+    /// 处理连接，启用TLS时先完成握手
+    ///
+    /// 握手超过 [`TLS_HANDSHAKE_TIMEOUT`] 或者握手期间收到关闭信号时直接关闭连接
+    async fn serve<IO>(mut self, stream: IO) -> Result<()>
+    where
+        IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        match self.acceptor.clone() {
+            Some(acceptor) => {
+                let stream = tokio::select! {
+                    result = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)) => {
+                        result.context("TLS handshake timed out")?.context("TLS handshake failed")?
+                    }
+                    _ = wait_for_shutdown(&mut self.shutdown_rx) => return Ok(()),
+                };
+                self.serve_http(stream).await
+            }
+            None => self.serve_http(stream).await,
+        }
+    }
+
+    /// 处理HTTP请求，收到关闭信号后不再接收新请求，当前请求处理完成后关闭连接
+    async fn serve_http<IO>(mut self, stream: IO) -> Result<()>
+    where
+        IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let service = TowerToHyperService::new(self.router.clone());
+        let conn = self.builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+        tokio::pin!(conn);
+
+        let result = tokio::select! {
+            result = conn.as_mut() => result,
+            _ = wait_for_shutdown(&mut self.shutdown_rx) => {
+                conn.as_mut().graceful_shutdown();
+                conn.await
+            }
+        };
+
+        result.map_err(|err| color_eyre::eyre::eyre!(err))
+    }
+}
+
+/// TLS配置热加载
+///
+/// 定期检查证书和私钥文件的修改时间，发生变化后重新加载。
+/// 重新加载失败时（例如证书只更新了一半）会继续使用旧配置，并在下个周期重试。
+struct TlsReloader {
+    current: Arc<RwLock<Arc<ServerConfig>>>,
+}
+
+impl TlsReloader {
+    /// 加载证书并启动后台检查任务
+    fn start(tls_config: TlsConfig, http2: bool, mut shutdown_rx: Receiver<bool>) -> Result<Self> {
+        let server_config = load_server_config(&tls_config, http2)?;
+        let current = Arc::new(RwLock::new(server_config));
+
+        let reloading = Arc::clone(&current);
+        tokio::spawn(async move {
+            let mut last_modified = files_modified(&tls_config);
+            let mut interval = tokio::time::interval(TLS_RELOAD_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = wait_for_shutdown(&mut shutdown_rx) => break,
+                }
+
+                let modified = files_modified(&tls_config);
+                if modified == last_modified {
+                    continue;
+                }
+
+                match load_server_config(&tls_config, http2) {
+                    Ok(server_config) => {
+                        *reloading.write().unwrap_or_else(|e| e.into_inner()) = server_config;
+                        last_modified = modified;
+                        info!("🔐 TLS证书已重新加载: {}", tls_config.cert_file.display());
+                    }
+                    Err(err) => warn!("Failed to reload TLS certificate, keep using the old one: {:?}", err),
+                }
+            }
+        });
+
+        Ok(Self { current })
+    }
+
+    /// 获取当前生效的TLS acceptor
+    fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(Arc::clone(&self.current.read().unwrap_or_else(|e| e.into_inner())))
+    }
+}
+
+/// 获取证书和私钥文件的修改时间
+fn files_modified(tls_config: &TlsConfig) -> [Option<SystemTime>; 2] {
+    let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    [modified(&tls_config.cert_file), modified(&tls_config.key_file)]
+}
+
+/// 从PEM文件加载rustls配置
+fn load_server_config(tls_config: &TlsConfig, http2: bool) -> Result<Arc<ServerConfig>> {
+    let certs = CertificateDer::pem_file_iter(&tls_config.cert_file)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to load TLS certificate from {}", tls_config.cert_file.display()))?;
+
+    let key = PrivateKeyDer::from_pem_file(&tls_config.key_file)
+        .with_context(|| format!("Failed to load TLS private key from {}", tls_config.key_file.display()))?;
+
+    let mut server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .context("Failed to configure TLS protocol versions")?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("Invalid TLS certificate or private key")?;
+
+    server_config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+
+    Ok(Arc::new(server_config))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bind_unix_socket() {
+        let dir = std::env::temp_dir().join(format!("web-service-socket-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("web.sock");
+        let addr = ListenAddr::Unix(path.clone());
+
+        // 普通文件不会被删除
+        std::fs::write(&path, "data").unwrap();
+        let err = BoundListener::bind("web", &addr).await.err().unwrap();
+        assert!(format!("{err:?}").contains(&addr.to_string()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "data");
+        std::fs::remove_file(&path).unwrap();
+
+        // 其他实例正在监听的socket不会被删除
+        let listener = BoundListener::bind("web", &addr).await.unwrap();
+        let err = BoundListener::bind("web", &addr).await.err().unwrap();
+        assert_eq!(
+            err.root_cause().downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::AddrInUse
+        );
+        assert!(path.exists());
+
+        // 遗留的socket文件会被删除后重新绑定
+        drop(listener);
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        drop(BoundListener::bind("web", &addr).await.unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
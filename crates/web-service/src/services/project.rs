@@ -2,8 +2,24 @@
 //!
 //! 提供项目相关的业务逻辑操作
 
+use crate::models::err::AppError;
 use crate::services::traits::ProjectServiceTrait;
-use database::{DatabaseResult, ProjectCreate, ProjectInfo, ProjectRepositoryTrait, ProjectSearchResult, ProjectUpdate};
+use axum::body::Bytes;
+use database::{
+    AttachmentCreate, AttachmentInfo, DatabaseResult, ProjectCreate, ProjectFilter, ProjectInfo, ProjectRankedSearchResult,
+    ProjectRepositoryTrait, ProjectSearchResult, ProjectUpdate, TagCreate, TagInfo, TagUpdate,
+};
+use futures::{Stream, StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use shared_lib::models::tenant::TenantId;
+use std::io::SeekFrom;
+use std::ops::Range;
+use std::sync::Arc;
+use storage::{ByteStream, ObjectMeta, Storage, StorageError};
+use tokio::fs::File;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+use tracing::{debug, warn};
 
 #[derive(Debug, Clone)]
 pub struct ProjectService<PR>
@@ -11,14 +27,29 @@ where
     PR: ProjectRepositoryTrait,
 {
     project_repository: PR,
+
+    /// 附件存储，附件元数据保存在仓库中
+    storage: Arc<dyn Storage>,
 }
 
 impl<PR> ProjectService<PR>
 where
     PR: ProjectRepositoryTrait,
 {
-    pub fn new(project_repository: PR) -> Self {
-        Self { project_repository }
+    pub fn new(project_repository: PR, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            project_repository,
+            storage,
+        }
+    }
+
+    /// 删除附件存储中的内容
+    ///
+    /// 元数据已经删除，内容删除失败只会留下无人引用的对象，不影响业务，所以只记录日志
+    async fn delete_object(&self, storage_key: &str) {
+        if let Err(err) = self.storage.delete(storage_key).await {
+            warn!("⚠️ 删除附件内容 {} 失败: {}", storage_key, err);
+        }
     }
 }
 
@@ -27,8 +58,31 @@ impl<PR> ProjectServiceTrait for ProjectService<PR>
 where
     PR: ProjectRepositoryTrait,
 {
-    async fn find_projects(&self, name: Option<String>, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult> {
-        self.project_repository.find_projects(name, page_size, offset).await
+    fn with_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            project_repository: self.project_repository.with_tenant(tenant),
+            storage: Arc::clone(&self.storage),
+        }
+    }
+
+    async fn find_projects(&self, filter: ProjectFilter, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult> {
+        self.project_repository.find_projects(filter, page_size, offset).await
+    }
+
+    async fn search_projects_ranked(
+        &self,
+        keyword: String,
+        threshold: f32,
+        page_size: i64,
+        offset: i64,
+    ) -> DatabaseResult<ProjectRankedSearchResult> {
+        self.project_repository
+            .search_projects_ranked(keyword, threshold, page_size, offset)
+            .await
+    }
+
+    async fn suggest_project_names(&self, prefix: String, limit: i64) -> DatabaseResult<Vec<String>> {
+        self.project_repository.suggest_project_names(prefix, limit).await
     }
 
     async fn create_project(&self, project: ProjectCreate) -> DatabaseResult<ProjectInfo> {
@@ -39,11 +93,154 @@ where
         self.project_repository.get_project_by_id(id).await
     }
 
+    async fn get_projects_by_ids(&self, ids: &[i32]) -> DatabaseResult<Vec<ProjectInfo>> {
+        self.project_repository.get_projects_by_ids(ids).await
+    }
+
     async fn update_project(&self, id: i32, update: ProjectUpdate) -> DatabaseResult<ProjectInfo> {
         self.project_repository.update_project(id, update).await
     }
 
+    /// 删除项目
+    ///
+    /// 附件元数据随项目级联删除，这里在删除项目之前记录附件列表，删除成功后再删除附件内容。
+    /// 附件列表从主库读取，避免只读副本的复制延迟导致漏删刚刚上传的附件
     async fn delete_project(&self, id: i32) -> DatabaseResult<ProjectInfo> {
-        self.project_repository.delete_project(id).await
+        let attachments = self.project_repository.read_from_primary().find_attachments(id).await?;
+        let project = self.project_repository.delete_project(id).await?;
+
+        for attachment in attachments {
+            self.delete_object(&attachment.storage_key).await;
+        }
+
+        Ok(project)
+    }
+
+    async fn get_project_ancestors(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>> {
+        self.project_repository.get_project_ancestors(id).await
+    }
+
+    async fn get_project_subtree(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>> {
+        self.project_repository.get_project_subtree(id).await
+    }
+
+    async fn move_project(&self, id: i32, parent_id: Option<i32>) -> DatabaseResult<ProjectInfo> {
+        self.project_repository.move_project(id, parent_id).await
+    }
+
+    async fn find_tags(&self) -> DatabaseResult<Vec<TagInfo>> {
+        self.project_repository.find_tags().await
+    }
+
+    async fn create_tag(&self, tag: TagCreate) -> DatabaseResult<TagInfo> {
+        self.project_repository.create_tag(tag).await
+    }
+
+    async fn get_tag_by_id(&self, id: i32) -> DatabaseResult<TagInfo> {
+        self.project_repository.get_tag_by_id(id).await
+    }
+
+    async fn update_tag(&self, id: i32, update: TagUpdate) -> DatabaseResult<TagInfo> {
+        self.project_repository.update_tag(id, update).await
+    }
+
+    async fn delete_tag(&self, id: i32) -> DatabaseResult<TagInfo> {
+        self.project_repository.delete_tag(id).await
+    }
+
+    async fn attach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo> {
+        self.project_repository.attach_tag(project_id, tag_id).await
+    }
+
+    async fn detach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo> {
+        self.project_repository.detach_tag(project_id, tag_id).await
+    }
+
+    async fn find_attachments(&self, project_id: i32) -> DatabaseResult<Vec<AttachmentInfo>> {
+        self.project_repository.find_attachments(project_id).await
+    }
+
+    /// 上传附件
+    ///
+    /// 1. 检查项目是否存在，避免为不存在的项目接收整个文件
+    /// 2. 将内容写入临时文件，同时计算大小和sha256（S3上传需要提前知道长度和sha256）
+    /// 3. 将临时文件写入附件存储，路径为 `projects/<项目ID>/<uuid>`
+    /// 4. 保存元数据，失败时（例如项目在上传过程中被删除）删除已经写入的内容
+    async fn upload_attachment<S, E>(
+        &self,
+        project_id: i32,
+        file_name: String,
+        content_type: String,
+        mut body: S,
+    ) -> Result<AttachmentInfo, AppError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + Unpin,
+        E: Into<AppError> + Send,
+    {
+        // 从主库检查项目是否存在，刚刚创建的项目可能还没有复制到只读副本
+        self.project_repository.read_from_primary().get_project_by_id(project_id).await?;
+
+        let mut file = File::from_std(tempfile::tempfile().map_err(StorageError::from)?);
+        let mut hasher = Sha256::new();
+        let mut size = 0u64;
+        while let Some(chunk) = body.try_next().await.map_err(Into::into)? {
+            hasher.update(&chunk);
+            size += chunk.len() as u64;
+            file.write_all(&chunk).await.map_err(StorageError::from)?;
+        }
+        file.flush().await.map_err(StorageError::from)?;
+        file.seek(SeekFrom::Start(0)).await.map_err(StorageError::from)?;
+
+        let sha256 = hex::encode(hasher.finalize());
+        let storage_key = format!("projects/{project_id}/{}", uuid::Uuid::new_v4());
+        debug!("📎 上传附件 {} 到 {} ({} 字节)", file_name, storage_key, size);
+
+        let meta = ObjectMeta {
+            content_type: content_type.clone(),
+            content_length: size,
+            sha256: sha256.clone(),
+        };
+        self.storage.put(&storage_key, meta, ReaderStream::new(file).boxed()).await?;
+
+        let attachment = AttachmentCreate {
+            project_id,
+            file_name,
+            content_type,
+            size: size as i64,
+            sha256,
+            storage_key: storage_key.clone(),
+        };
+        match self.project_repository.create_attachment(attachment).await {
+            Ok(attachment) => Ok(attachment),
+            Err(err) => {
+                self.delete_object(&storage_key).await;
+                Err(err.into())
+            }
+        }
+    }
+
+    async fn get_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo> {
+        self.project_repository.get_attachment(project_id, id).await
+    }
+
+    async fn read_attachment(&self, attachment: &AttachmentInfo, range: Option<Range<u64>>) -> Result<ByteStream, AppError> {
+        Ok(self.storage.get(&attachment.storage_key, range).await?)
+    }
+
+    /// 删除附件
+    ///
+    /// 先删除元数据再删除内容，内容删除失败时只记录日志
+    async fn delete_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo> {
+        let attachment = self.project_repository.delete_attachment(project_id, id).await?;
+        self.delete_object(&attachment.storage_key).await;
+        Ok(attachment)
+    }
+
+    async fn ping(&self) -> DatabaseResult<()> {
+        self.project_repository.ping().await
+    }
+
+    async fn ping_replica(&self) -> Option<DatabaseResult<()>> {
+        self.project_repository.ping_replica().await
     }
 }
@@ -2,7 +2,16 @@
 //!
 //! 定义服务层的抽象接口，遵循六边形架构的端口适配器模式
 
-use database::{DatabaseResult, ProjectCreate, ProjectInfo, ProjectSearchResult, ProjectUpdate};
+use crate::models::err::AppError;
+use axum::body::Bytes;
+use database::{
+    AttachmentInfo, DatabaseResult, ProjectCreate, ProjectFilter, ProjectInfo, ProjectRankedSearchResult, ProjectSearchResult,
+    ProjectUpdate, TagCreate, TagInfo, TagUpdate,
+};
+use futures::Stream;
+use shared_lib::models::tenant::TenantId;
+use std::ops::Range;
+use storage::ByteStream;
 
 /// 项目服务 trait 定义
 ///
@@ -11,16 +20,47 @@ use database::{DatabaseResult, ProjectCreate, ProjectInfo, ProjectSearchResult,
 /// 该 trait 作为业务逻辑的抽象接口，具体实现由 [`crate::services::project::ProjectService`] 提供
 #[async_trait::async_trait]
 pub trait ProjectServiceTrait: Send + Sync + Clone + 'static {
+    /// 返回处理指定租户请求的服务实例，所有操作只能访问该租户的数据
+    fn with_tenant(&self, tenant: &TenantId) -> Self;
+
     /// 根据查询参数搜索项目
     ///
     /// # 参数
-    /// - `name`: 项目名称（模糊搜索）
+    /// - `filter`: 搜索条件，包括项目名称（模糊搜索）和标签
     /// - `page_size`: 页面大小
     /// - `offset`: 偏移量
     ///
     /// # 返回值
     /// 返回包含项目列表和总数的结果
-    async fn find_projects(&self, name: Option<String>, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult>;
+    async fn find_projects(&self, filter: ProjectFilter, page_size: i64, offset: i64) -> DatabaseResult<ProjectSearchResult>;
+
+    /// 按相关度搜索项目
+    ///
+    /// # 参数
+    /// - `keyword`: 搜索关键字，同时匹配项目名称和项目说明
+    /// - `threshold`: 相似度阈值（0~1）
+    /// - `page_size`: 页面大小
+    /// - `offset`: 偏移量
+    ///
+    /// # 返回值
+    /// 返回按相关度排序、带高亮片段的命中列表和总数
+    async fn search_projects_ranked(
+        &self,
+        keyword: String,
+        threshold: f32,
+        page_size: i64,
+        offset: i64,
+    ) -> DatabaseResult<ProjectRankedSearchResult>;
+
+    /// 项目名称输入提示
+    ///
+    /// # 参数
+    /// - `prefix`: 用户已经输入的内容
+    /// - `limit`: 最多返回的名称个数
+    ///
+    /// # 返回值
+    /// 返回去重后的项目名称列表
+    async fn suggest_project_names(&self, prefix: String, limit: i64) -> DatabaseResult<Vec<String>>;
 
     /// 创建新项目
     ///
@@ -40,6 +80,15 @@ pub trait ProjectServiceTrait: Send + Sync + Clone + 'static {
     /// 返回项目信息
     async fn get_project_by_id(&self, id: i32) -> DatabaseResult<ProjectInfo>;
 
+    /// 根据多个 ID 批量获取项目信息
+    ///
+    /// # 参数
+    /// - `ids`: 项目 ID 列表
+    ///
+    /// # 返回值
+    /// 返回按 ID 排序的项目列表，不存在的 ID 会被忽略
+    async fn get_projects_by_ids(&self, ids: &[i32]) -> DatabaseResult<Vec<ProjectInfo>>;
+
     /// 更新项目信息
     ///
     /// # 参数
@@ -50,7 +99,7 @@ pub trait ProjectServiceTrait: Send + Sync + Clone + 'static {
     /// 返回更新后的项目信息
     async fn update_project(&self, id: i32, update: ProjectUpdate) -> DatabaseResult<ProjectInfo>;
 
-    /// 删除项目
+    /// 删除项目，子项目挂到被删除项目的父项目下，同时删除项目的所有附件
     ///
     /// # 参数
     /// - `id`: 项目 ID
@@ -58,4 +107,164 @@ pub trait ProjectServiceTrait: Send + Sync + Clone + 'static {
     /// # 返回值
     /// 返回被删除的项目信息
     async fn delete_project(&self, id: i32) -> DatabaseResult<ProjectInfo>;
+
+    /// 查询项目的所有祖先
+    ///
+    /// # 参数
+    /// - `id`: 项目 ID
+    ///
+    /// # 返回值
+    /// 返回从顶层项目到直接父项目的列表，不包含项目自身
+    async fn get_project_ancestors(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>>;
+
+    /// 查询以项目为根的子树
+    ///
+    /// # 参数
+    /// - `id`: 项目 ID
+    ///
+    /// # 返回值
+    /// 返回包含项目自身在内的所有子孙项目，第一个元素为项目自身
+    async fn get_project_subtree(&self, id: i32) -> DatabaseResult<Vec<ProjectInfo>>;
+
+    /// 移动项目到新的父项目下
+    ///
+    /// # 参数
+    /// - `id`: 项目 ID
+    /// - `parent_id`: 新的父项目 ID，为 `None` 时移动为顶层项目
+    ///
+    /// # 返回值
+    /// 返回移动后的项目信息
+    async fn move_project(&self, id: i32, parent_id: Option<i32>) -> DatabaseResult<ProjectInfo>;
+
+    /// 查询所有标签
+    ///
+    /// # 返回值
+    /// 返回按名称排序的标签列表
+    async fn find_tags(&self) -> DatabaseResult<Vec<TagInfo>>;
+
+    /// 创建新标签
+    ///
+    /// # 参数
+    /// - `tag`: 标签创建信息
+    ///
+    /// # 返回值
+    /// 返回创建的标签信息
+    async fn create_tag(&self, tag: TagCreate) -> DatabaseResult<TagInfo>;
+
+    /// 根据 ID 获取标签信息
+    ///
+    /// # 参数
+    /// - `id`: 标签 ID
+    ///
+    /// # 返回值
+    /// 返回标签信息
+    async fn get_tag_by_id(&self, id: i32) -> DatabaseResult<TagInfo>;
+
+    /// 更新标签信息
+    ///
+    /// # 参数
+    /// - `id`: 标签 ID
+    /// - `update`: 更新信息
+    ///
+    /// # 返回值
+    /// 返回更新后的标签信息
+    async fn update_tag(&self, id: i32, update: TagUpdate) -> DatabaseResult<TagInfo>;
+
+    /// 删除标签
+    ///
+    /// # 参数
+    /// - `id`: 标签 ID
+    ///
+    /// # 返回值
+    /// 返回被删除的标签信息
+    async fn delete_tag(&self, id: i32) -> DatabaseResult<TagInfo>;
+
+    /// 为项目添加标签
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `tag_id`: 标签 ID
+    ///
+    /// # 返回值
+    /// 返回添加标签后的项目信息
+    async fn attach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo>;
+
+    /// 从项目中移除标签
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `tag_id`: 标签 ID
+    ///
+    /// # 返回值
+    /// 返回移除标签后的项目信息
+    async fn detach_tag(&self, project_id: i32, tag_id: i32) -> DatabaseResult<ProjectInfo>;
+
+    /// 查询项目的所有附件
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    ///
+    /// # 返回值
+    /// 返回按 ID 排序的附件列表
+    async fn find_attachments(&self, project_id: i32) -> DatabaseResult<Vec<AttachmentInfo>>;
+
+    /// 上传附件
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `file_name`: 原始文件名
+    /// - `content_type`: 内容类型
+    /// - `body`: 附件内容，读取出错时上传失败，错误原样返回
+    ///
+    /// # 返回值
+    /// 返回保存后的附件信息，包括大小和sha256
+    async fn upload_attachment<S, E>(
+        &self,
+        project_id: i32,
+        file_name: String,
+        content_type: String,
+        body: S,
+    ) -> Result<AttachmentInfo, AppError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + Unpin,
+        E: Into<AppError> + Send;
+
+    /// 获取项目的附件信息
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `id`: 附件 ID
+    ///
+    /// # 返回值
+    /// 返回附件信息
+    async fn get_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo>;
+
+    /// 读取附件内容
+    ///
+    /// # 参数
+    /// - `attachment`: 附件信息
+    /// - `range`: 读取的字节范围 `[start, end)`，为 `None` 时读取全部内容，调用方需要保证范围不超过附件大小
+    ///
+    /// # 返回值
+    /// 返回附件内容的字节流
+    async fn read_attachment(&self, attachment: &AttachmentInfo, range: Option<Range<u64>>) -> Result<ByteStream, AppError>;
+
+    /// 删除附件
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `id`: 附件 ID
+    ///
+    /// # 返回值
+    /// 返回被删除的附件信息
+    async fn delete_attachment(&self, project_id: i32, id: i32) -> DatabaseResult<AttachmentInfo>;
+
+    /// 检查依赖的数据存储是否可用
+    ///
+    /// # 返回值
+    /// 数据存储不可用时返回错误
+    async fn ping(&self) -> DatabaseResult<()>;
+
+    /// 检查只读副本是否可用，没有配置只读副本时返回 `None`，参考 [`database::ProjectRepositoryTrait::ping_replica`]
+    async fn ping_replica(&self) -> Option<DatabaseResult<()>>;
 }
@@ -0,0 +1,288 @@
+//! 测试辅助工具
+//!
+//! 基于 [`MemoryProjectRepository`] 构建完整的路由（包括中间件），不依赖数据库等外部服务，
+//! 可以直接测试路由、参数校验和错误码转换。
+//!
+//! 业务请求默认携带 [`TEST_TOKEN`]，属于租户 [`TEST_TENANT`]。
+//!
+//! 本crate的单元测试可以直接使用，其他crate需要在 `dev-dependencies` 中启用 `test-support` feature。
+//!
+//! ```ignore
+//! let app = TestApp::new();
+//! let response = app.get("/api/v1/projects/1").await;
+//! assert_eq!(response.status, StatusCode::NOT_FOUND);
+//! ```
+
+use crate::AppState;
+use crate::middleware::Deprecation;
+use crate::routes::{AppRouters, create_app_routers};
+use crate::services::ProjectService;
+use axum::Router;
+use axum::body::{Body, Bytes};
+use axum::http::{HeaderMap, HeaderValue, Method, Request, StatusCode, header};
+use database::MemoryProjectRepository;
+use database::ProjectRepositoryTrait;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use shared_lib::logging::LogFilter;
+use shared_lib::models::config::{AuthConfig, DEFAULT_V1_DEPRECATED_AT, DEFAULT_V1_SUNSET, MiddlewareConfig};
+use shared_lib::models::config_reload::ConfigReloader;
+use shared_lib::models::config_source::{ConfigOverrides, ConfigSource, Origin};
+use shared_lib::models::tenant::TenantId;
+use shared_lib::subsystems::Subsystems;
+use std::collections::HashMap;
+use std::sync::Arc;
+use storage::MemoryStorage;
+use tower::ServiceExt;
+
+/// 测试时附件上传的大小限制
+pub const TEST_MAX_UPLOAD_SIZE: usize = 1024 * 1024;
+
+/// 测试时使用的访问令牌
+pub const TEST_TOKEN: &str = "test-token";
+
+/// [`TEST_TOKEN`] 所属的租户
+pub const TEST_TENANT: &str = "test";
+
+/// multipart请求的分隔符
+const MULTIPART_BOUNDARY: &str = "test-multipart-boundary";
+
+/// 基于内存仓库的项目服务
+pub type MemoryProjectService = ProjectService<MemoryProjectRepository>;
+
+/// 测试租户
+pub fn test_tenant() -> TenantId {
+    TEST_TENANT.parse().expect("test tenant should be valid")
+}
+
+/// 测试时的认证配置，只有 [`TEST_TOKEN`] 一个令牌，没有默认租户
+pub fn test_auth_config() -> AuthConfig {
+    AuthConfig {
+        tokens: HashMap::from([(TEST_TOKEN.to_string(), test_tenant())]),
+        default_tenant: None,
+    }
+}
+
+/// 测试时的配置热加载器，只设置了必需的链接字符串，其他配置都使用默认值
+pub fn test_config_reloader() -> ConfigReloader {
+    ConfigReloader::from_source(test_config_source(&[]), ConfigOverrides::default()).expect("test config should be valid")
+}
+
+/// 测试时的配置项，在必需的链接字符串之外设置指定的配置项，可以通过 [`ConfigReloader::apply`] 模拟热加载
+pub fn test_config_source(values: &[(&str, &str)]) -> ConfigSource {
+    let mut source = ConfigSource::default();
+    for (key, value) in [("database.url", "postgres://localhost/test"), ("redis.url", "redis://127.0.0.1/")]
+        .iter()
+        .chain(values)
+    {
+        source
+            .set(key, value.to_string(), Origin::Cli)
+            .expect("test config key should be known");
+    }
+    source
+}
+
+/// 创建基于内存仓库的共享状态，附件保存在内存存储中
+pub fn memory_app_state(repository: MemoryProjectRepository) -> AppState<MemoryProjectService> {
+    memory_app_state_with_storage(repository, MemoryStorage::new())
+}
+
+/// 创建基于内存仓库的共享状态，使用指定的内存存储，方便检查附件内容
+pub fn memory_app_state_with_storage(repository: MemoryProjectRepository, storage: MemoryStorage) -> AppState<MemoryProjectService> {
+    AppState {
+        project_service: Arc::new(ProjectService::new(repository, Arc::new(storage))),
+        auth: Arc::new(test_auth_config()),
+        metrics: crate::metrics::prometheus_handle(),
+        max_upload_size: TEST_MAX_UPLOAD_SIZE,
+        v1_deprecation: Deprecation::new(DEFAULT_V1_DEPRECATED_AT, DEFAULT_V1_SUNSET),
+        reloader: Arc::new(test_config_reloader()),
+        log_filter: Arc::new(LogFilter::detached("info").expect("test log filter should be valid")),
+        subsystems: Subsystems::default(),
+        draining_rx: tokio::sync::watch::channel(false).1,
+    }
+}
+
+/// 测试用的路由
+///
+/// 直接调用路由处理请求，不需要监听端口
+pub struct TestApp {
+    /// 测试租户的内存仓库，可以用来准备数据或者检查数据
+    pub repository: MemoryProjectRepository,
+
+    /// 内存附件存储，可以用来检查附件内容
+    pub storage: MemoryStorage,
+
+    /// 子系统状态，可以用来测试存活探针
+    pub subsystems: Subsystems,
+
+    /// 配置热加载器，可以用来测试修改配置后的行为
+    pub reloader: Arc<ConfigReloader>,
+
+    /// 业务路由和运维路由
+    pub routers: AppRouters,
+}
+
+/// 测试请求的返回结果
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl TestApp {
+    /// 使用默认的中间件配置创建
+    pub fn new() -> Self {
+        Self::with_middleware_config(&MiddlewareConfig::default())
+    }
+
+    /// 使用指定的中间件配置创建，例如测试请求体大小限制
+    pub fn with_middleware_config(middleware_config: &MiddlewareConfig) -> Self {
+        let repository = MemoryProjectRepository::new();
+        let storage = MemoryStorage::new();
+        let state = memory_app_state_with_storage(repository.clone(), storage.clone());
+        let subsystems = state.subsystems.clone();
+        let reloader = Arc::clone(&state.reloader);
+        let routers = create_app_routers(state, middleware_config).expect("middleware config for tests should be valid");
+
+        Self {
+            repository: repository.with_tenant(&test_tenant()),
+            storage,
+            subsystems,
+            reloader,
+            routers,
+        }
+    }
+
+    /// 发送GET请求到业务路由
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.request(empty_request(Method::GET, uri)).await
+    }
+
+    /// 发送不带请求体的PUT请求到业务路由
+    pub async fn put(&self, uri: &str) -> TestResponse {
+        self.request(empty_request(Method::PUT, uri)).await
+    }
+
+    /// 发送DELETE请求到业务路由
+    pub async fn delete(&self, uri: &str) -> TestResponse {
+        self.request(empty_request(Method::DELETE, uri)).await
+    }
+
+    /// 发送POST请求到业务路由，请求体序列化为json
+    pub async fn post_json(&self, uri: &str, body: &impl Serialize) -> TestResponse {
+        self.request(json_request(Method::POST, uri, body)).await
+    }
+
+    /// 发送PUT请求到业务路由，请求体序列化为json
+    pub async fn put_json(&self, uri: &str, body: &impl Serialize) -> TestResponse {
+        self.request(json_request(Method::PUT, uri, body)).await
+    }
+
+    /// 发送PATCH请求到业务路由，请求体序列化为json
+    pub async fn patch_json(&self, uri: &str, body: &impl Serialize) -> TestResponse {
+        self.request(json_request(Method::PATCH, uri, body)).await
+    }
+
+    /// 发送multipart/form-data格式的POST请求到业务路由，文件放在 `file` 字段中
+    pub async fn post_file(&self, uri: &str, file_name: &str, content_type: &str, content: &[u8]) -> TestResponse {
+        let mut body = format!(
+            "--{MULTIPART_BOUNDARY}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\n\
+             Content-Type: {content_type}\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend_from_slice(content);
+        body.extend_from_slice(format!("\r\n--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}"))
+            .body(Body::from(body))
+            .expect("test request should be valid");
+        self.request(request).await
+    }
+
+    /// 发送GET请求到运维路由
+    pub async fn admin_get(&self, uri: &str) -> TestResponse {
+        send(&self.routers.admin, empty_request(Method::GET, uri)).await
+    }
+
+    /// 发送PUT请求到运维路由，请求体序列化为json
+    pub async fn admin_put_json(&self, uri: &str, body: &impl Serialize) -> TestResponse {
+        send(&self.routers.admin, json_request(Method::PUT, uri, body)).await
+    }
+
+    /// 发送DELETE请求到运维路由
+    pub async fn admin_delete(&self, uri: &str) -> TestResponse {
+        send(&self.routers.admin, empty_request(Method::DELETE, uri)).await
+    }
+
+    /// 发送任意请求到业务路由，没有 `Authorization` 请求头时使用 [`TEST_TOKEN`]
+    pub async fn request(&self, mut request: Request<Body>) -> TestResponse {
+        request
+            .headers_mut()
+            .entry(header::AUTHORIZATION)
+            .or_insert_with(|| HeaderValue::from_str(&format!("Bearer {TEST_TOKEN}")).expect("test token should be valid"));
+        self.anonymous_request(request).await
+    }
+
+    /// 发送不带访问令牌的请求到业务路由
+    pub async fn anonymous_request(&self, request: Request<Body>) -> TestResponse {
+        send(&self.routers.public, request).await
+    }
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestResponse {
+    /// 将返回值反序列化为指定类型
+    ///
+    /// 反序列化失败时panic，并在错误信息中带上原始返回值
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).unwrap_or_else(|err| panic!("Invalid json response ({err}): {}", self.text()))
+    }
+
+    /// 返回值文本
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+fn empty_request(method: Method, uri: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(Body::empty())
+        .expect("test request should be valid")
+}
+
+fn json_request(method: Method, uri: &str, body: &impl Serialize) -> Request<Body> {
+    let body = serde_json::to_vec(body).expect("test request body should be serializable");
+
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .expect("test request should be valid")
+}
+
+async fn send(router: &Router, request: Request<Body>) -> TestResponse {
+    let response = router.clone().oneshot(request).await.expect("router is infallible");
+    let (parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .expect("response body should be readable");
+
+    TestResponse {
+        status: parts.status,
+        headers: parts.headers,
+        body,
+    }
+}
@@ -0,0 +1 @@
+drop index if exists hm.idx_project_comment_search;
@@ -0,0 +1,3 @@
+-- 在comment上也建立trigram索引，配合project_name上的索引支持按相关度排序的模糊搜索
+-- 相似度运算符(% 和 <%)都可以命中gin_trgm_ops索引
+create index idx_project_comment_search on hm.projects using gin (comment gin_trgm_ops);
@@ -0,0 +1,3 @@
+drop table if exists hm.project_tags;
+
+drop table if exists hm.tags;
@@ -0,0 +1,23 @@
+-- 项目标签，例如 team:payments、env:prod
+create table hm.tags
+(
+    id         serial primary key not null,
+    -- 标签名称，全局唯一
+    name       varchar(100)       not null unique,
+    -- 标签说明
+    comment    varchar(255)       not null default '',
+    created_at timestamp          not null,
+    updated_at timestamp          not null
+);
+
+-- 项目与标签的多对多关系，删除项目或标签时自动解除关联
+create table hm.project_tags
+(
+    project_id integer   not null references hm.projects (id) on delete cascade,
+    tag_id     integer   not null references hm.tags (id) on delete cascade,
+    created_at timestamp not null,
+    primary key (project_id, tag_id)
+);
+
+-- 主键已经覆盖按项目查询标签，这里再建立反向索引，方便按标签过滤项目
+create index idx_project_tags_tag on hm.project_tags (tag_id, project_id);
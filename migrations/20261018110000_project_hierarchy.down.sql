@@ -0,0 +1,5 @@
+drop index if exists hm.idx_projects_parent;
+
+alter table hm.projects
+    drop constraint if exists projects_parent_not_self,
+    drop column if exists parent_id;
@@ -0,0 +1,9 @@
+-- 项目层级：项目集 -> 项目 -> 子项目，parent_id为空时为顶层项目
+--
+-- 删除项目时由仓库层先将子项目挂到被删除项目的父项目下，外键不使用级联，避免误删整棵子树
+alter table hm.projects
+    add column parent_id integer references hm.projects (id),
+    add constraint projects_parent_not_self check (parent_id <> id);
+
+-- 按父项目查询子项目（递归查询子树时使用）
+create index idx_projects_parent on hm.projects (parent_id);
@@ -0,0 +1,19 @@
+-- 项目附件的元数据，内容保存在附件存储（本地文件系统或S3兼容对象存储）中
+create table hm.project_attachments
+(
+    id           serial primary key not null,
+    -- 删除项目时自动删除附件元数据，存储中的内容由服务层删除
+    project_id   integer            not null references hm.projects (id) on delete cascade,
+    -- 上传时的原始文件名
+    file_name    varchar(255)       not null,
+    content_type varchar(255)       not null,
+    -- 内容大小（字节）
+    size         bigint             not null check (size >= 0),
+    -- 内容的sha256（小写十六进制），同时用作下载时的ETag
+    sha256       char(64)           not null,
+    -- 内容在附件存储中的路径
+    storage_key  varchar(255)       not null unique,
+    created_at   timestamp          not null
+);
+
+create index idx_project_attachments_project on hm.project_attachments (project_id, id);
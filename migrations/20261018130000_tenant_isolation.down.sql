@@ -0,0 +1,42 @@
+drop policy if exists tenant_isolation on hm.project_attachments;
+drop policy if exists tenant_isolation on hm.project_tags;
+drop policy if exists tenant_isolation on hm.tags;
+drop policy if exists tenant_isolation on hm.projects;
+
+alter table hm.project_attachments no force row level security, disable row level security;
+alter table hm.project_tags no force row level security, disable row level security;
+alter table hm.tags no force row level security, disable row level security;
+alter table hm.projects no force row level security, disable row level security;
+
+alter table hm.project_attachments
+    drop constraint project_attachments_project_id_fkey,
+    add constraint project_attachments_project_id_fkey foreign key (project_id) references hm.projects (id) on delete cascade;
+
+alter table hm.project_tags
+    drop constraint project_tags_project_id_fkey,
+    drop constraint project_tags_tag_id_fkey,
+    add constraint project_tags_project_id_fkey foreign key (project_id) references hm.projects (id) on delete cascade,
+    add constraint project_tags_tag_id_fkey foreign key (tag_id) references hm.tags (id) on delete cascade;
+
+alter table hm.projects
+    drop constraint projects_parent_id_fkey,
+    add constraint projects_parent_id_fkey foreign key (parent_id) references hm.projects (id),
+    drop constraint projects_tenant_id_id_key;
+
+-- 不同租户可能有同名标签，需要先手动处理重名的标签
+alter table hm.tags
+    drop constraint tags_tenant_id_id_key,
+    drop constraint tags_tenant_id_name_key,
+    add constraint tags_name_key unique (name);
+
+alter table hm.project_attachments drop column tenant_id;
+alter table hm.project_tags drop column tenant_id;
+alter table hm.tags drop column tenant_id;
+alter table hm.projects drop column tenant_id;
+
+-- 角色可能在其他数据库中被使用，只回收权限，不删除角色
+alter default privileges in schema hm revoke usage, select on sequences from hm_tenant;
+alter default privileges in schema hm revoke select, insert, update, delete on tables from hm_tenant;
+revoke usage, select on all sequences in schema hm from hm_tenant;
+revoke select, insert, update, delete on all tables in schema hm from hm_tenant;
+revoke usage on schema hm from hm_tenant;
@@ -0,0 +1,85 @@
+-- 多租户隔离：所有业务表增加tenant_id，通过行级安全策略（RLS）按租户隔离数据
+--
+-- 服务在每个事务开始时执行：
+--   set_config('role', 'hm_tenant', true)        -- 等同于 SET LOCAL ROLE hm_tenant
+--   set_config('app.tenant_id', '<tenant>', true) -- 等同于 SET LOCAL app.tenant_id
+-- 两个设置都只在当前事务内有效，连接归还连接池后不会影响其他请求。
+-- 超级用户和带有BYPASSRLS属性的角色不受RLS限制，所以需要切换到普通角色hm_tenant执行查询。
+
+-- 业务查询使用的角色，不能直接登录，只能由服务的数据库账号切换
+do
+$$
+    begin
+        if not exists (select 1 from pg_roles where rolname = 'hm_tenant') then
+            create role hm_tenant nologin;
+        end if;
+    end
+$$;
+grant hm_tenant to current_user;
+
+grant usage on schema hm to hm_tenant;
+grant select, insert, update, delete on all tables in schema hm to hm_tenant;
+grant usage, select on all sequences in schema hm to hm_tenant;
+alter default privileges in schema hm grant select, insert, update, delete on tables to hm_tenant;
+alter default privileges in schema hm grant usage, select on sequences to hm_tenant;
+
+-- 已有数据归属默认租户 `default`，之后插入数据时tenant_id默认取当前事务的租户，
+-- 没有设置租户时为null，违反非空约束
+alter table hm.projects add column tenant_id varchar(64) not null default 'default';
+alter table hm.tags add column tenant_id varchar(64) not null default 'default';
+alter table hm.project_tags add column tenant_id varchar(64) not null default 'default';
+alter table hm.project_attachments add column tenant_id varchar(64) not null default 'default';
+
+alter table hm.projects alter column tenant_id set default nullif(current_setting('app.tenant_id', true), '');
+alter table hm.tags alter column tenant_id set default nullif(current_setting('app.tenant_id', true), '');
+alter table hm.project_tags alter column tenant_id set default nullif(current_setting('app.tenant_id', true), '');
+alter table hm.project_attachments alter column tenant_id set default nullif(current_setting('app.tenant_id', true), '');
+
+-- 标签名称在租户内唯一
+alter table hm.tags
+    drop constraint tags_name_key,
+    add constraint tags_tenant_id_name_key unique (tenant_id, name),
+    add constraint tags_tenant_id_id_key unique (tenant_id, id);
+
+-- 外键包含tenant_id，外键检查不受RLS限制，需要由约束保证不能引用其他租户的数据
+alter table hm.projects
+    add constraint projects_tenant_id_id_key unique (tenant_id, id);
+
+alter table hm.projects
+    drop constraint projects_parent_id_fkey,
+    add constraint projects_parent_id_fkey foreign key (tenant_id, parent_id) references hm.projects (tenant_id, id);
+
+alter table hm.project_tags
+    drop constraint project_tags_project_id_fkey,
+    drop constraint project_tags_tag_id_fkey,
+    add constraint project_tags_project_id_fkey foreign key (tenant_id, project_id) references hm.projects (tenant_id, id) on delete cascade,
+    add constraint project_tags_tag_id_fkey foreign key (tenant_id, tag_id) references hm.tags (tenant_id, id) on delete cascade;
+
+alter table hm.project_attachments
+    drop constraint project_attachments_project_id_fkey,
+    add constraint project_attachments_project_id_fkey foreign key (tenant_id, project_id) references hm.projects (tenant_id, id) on delete cascade;
+
+-- 行级安全策略：只能读写当前租户的数据，没有设置租户时看不到任何数据
+alter table hm.projects enable row level security;
+alter table hm.projects force row level security;
+create policy tenant_isolation on hm.projects
+    using (tenant_id = current_setting('app.tenant_id', true))
+    with check (tenant_id = current_setting('app.tenant_id', true));
+
+alter table hm.tags enable row level security;
+alter table hm.tags force row level security;
+create policy tenant_isolation on hm.tags
+    using (tenant_id = current_setting('app.tenant_id', true))
+    with check (tenant_id = current_setting('app.tenant_id', true));
+
+alter table hm.project_tags enable row level security;
+alter table hm.project_tags force row level security;
+create policy tenant_isolation on hm.project_tags
+    using (tenant_id = current_setting('app.tenant_id', true))
+    with check (tenant_id = current_setting('app.tenant_id', true));
+
+alter table hm.project_attachments enable row level security;
+alter table hm.project_attachments force row level security;
+create policy tenant_isolation on hm.project_attachments
+    using (tenant_id = current_setting('app.tenant_id', true))
+    with check (tenant_id = current_setting('app.tenant_id', true));
@@ -0,0 +1,420 @@
+//! 命令行
+//!
+//! 不带子命令时等同于 `serve`，启动 `ROLES` 中的服务，`serve --roles` 可以覆盖环境变量。
+//! 其他子命令用于运维管理，执行完成后直接退出：
+//!
+//! - `migrate up/down/status`: 执行、回滚（最后一个）、查看数据库迁移
+//! - `seed`: 为租户写入演示数据
+//! - `user create`: 为租户生成访问令牌
+//! - `task enqueue <stream> <json>`: 向Redis流中投递一条任务
+//! - `streams inspect`: 查看Redis流、消费者组和消费者心跳
+//! - `rebalance now`: 立即执行一次Redis消息重平衡
+//! - `config print`: 打印当前配置，密钥会被隐藏
+//!
+//! 所有子命令都通过 [`AppConfig::load_with`] 加载配置，与服务使用同一套配置文件和环境变量，
+//! 全局参数 `--config` 指定配置文件，`--set key=value` 覆盖单个配置项（优先级最高）。
+//! `task`、`streams` 需要启用 `consumer` feature，`rebalance` 需要启用 `cron` feature。
+
+#[cfg(feature = "consumer")]
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
+use color_eyre::Result;
+use color_eyre::eyre::eyre;
+#[cfg(feature = "consumer")]
+use color_eyre::eyre::{Context, bail};
+#[cfg(feature = "consumer")]
+use consumer_service::redis_interaction::{consumer_heartbeats, enqueue_task, inspect_stream};
+#[cfg(feature = "consumer")]
+use consumer_service::task_stream_names;
+use database::migration::{migration_status, revert_last_migration, run_migrations};
+use database::{ProjectCreate, ProjectFilter, ProjectRepository, ProjectRepositoryTrait, TagCreate, connect_database};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+use shared_lib::models::config::{AppConfig, Roles};
+use shared_lib::models::config_source::ConfigOverrides;
+use shared_lib::models::tenant::TenantId;
+#[cfg(feature = "consumer")]
+use shared_lib::startup::connect_redis;
+use std::path::PathBuf;
+
+/// 生成的访问令牌长度
+const TOKEN_LENGTH: usize = 40;
+
+/// rust-backend 命令行
+#[derive(Debug, Parser)]
+#[command(name = "rust-backend", version, about = "使用Rust开发的Web后端，不带子命令时启动服务")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// TOML配置文件路径，默认使用 `RUST_BACKEND_CONFIG` 环境变量，都没有设置时读取当前目录下的 `config.toml`
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// 覆盖配置项，优先于配置文件和环境变量，可以重复使用，例如 `--set consumer.handler_concurrency=10`
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub overrides: Vec<(String, String)>,
+}
+
+impl Cli {
+    /// 命令行中指定的配置
+    pub fn config_overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            file: self.config.clone(),
+            values: self.overrides.clone(),
+        }
+    }
+}
+
+/// 解析 `key=value` 格式的参数
+fn parse_key_value(s: &str) -> Result<(String, String)> {
+    let (key, value) = s.split_once('=').ok_or_else(|| eyre!("expected `key=value`"))?;
+    Ok((key.trim().to_string(), value.to_string()))
+}
+
+/// 子命令
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// 启动服务（默认）
+    Serve(ServeArgs),
+
+    /// 管理数据库迁移
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+
+    /// 为租户写入演示数据，租户已经有项目时跳过
+    Seed {
+        /// 租户ID，默认使用 `AUTH_DEFAULT_TENANT`
+        #[arg(long)]
+        tenant: Option<TenantId>,
+    },
+
+    /// 管理访问令牌
+    User {
+        #[command(subcommand)]
+        action: UserAction,
+    },
+
+    /// 管理Redis任务
+    #[cfg(feature = "consumer")]
+    Task {
+        #[command(subcommand)]
+        action: TaskAction,
+    },
+
+    /// 查看Redis流
+    #[cfg(feature = "consumer")]
+    Streams {
+        #[command(subcommand)]
+        action: StreamsAction,
+    },
+
+    /// Redis消息重平衡
+    #[cfg(feature = "cron")]
+    Rebalance {
+        #[command(subcommand)]
+        action: RebalanceAction,
+    },
+
+    /// 查看配置
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Command::Serve(ServeArgs::default())
+    }
+}
+
+#[derive(Debug, Default, Args)]
+pub struct ServeArgs {
+    /// 启用的角色，逗号分隔，可选 web、grpc、consumer、cron、all，默认使用 `ROLES` 环境变量（未设置时为all）
+    #[arg(long)]
+    pub roles: Option<Roles>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateAction {
+    /// 执行所有未应用的迁移
+    Up,
+    /// 回滚最后一个已应用的迁移
+    Down,
+    /// 查看所有迁移的应用状态
+    Status,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UserAction {
+    /// 为租户生成访问令牌，生成后需要添加到 `AUTH_TOKENS` 中才能生效
+    Create {
+        /// 租户ID
+        tenant: TenantId,
+    },
+}
+
+#[cfg(feature = "consumer")]
+#[derive(Debug, Subcommand)]
+pub enum TaskAction {
+    /// 向Redis流中投递一条任务
+    Enqueue {
+        /// 流名称，例如 `task_type_a`
+        stream: String,
+        /// 任务内容（JSON）
+        json: String,
+    },
+}
+
+#[cfg(feature = "consumer")]
+#[derive(Debug, Subcommand)]
+pub enum StreamsAction {
+    /// 查看流的消息数量、消费者组和消费者心跳
+    Inspect {
+        /// 流名称，默认查看所有任务处理器读取的流
+        streams: Vec<String>,
+    },
+}
+
+#[cfg(feature = "cron")]
+#[derive(Debug, Subcommand)]
+pub enum RebalanceAction {
+    /// 立即执行一次重平衡，将失效消费者的pending消息分发给同组的其他消费者
+    Now,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigAction {
+    /// 打印当前配置，密钥会被隐藏
+    Print,
+}
+
+impl Command {
+    /// 执行运维子命令，`serve` 由入口函数处理
+    pub async fn run(self, conf: &AppConfig) -> Result<()> {
+        match self {
+            Command::Serve(_) => unreachable!("serve is handled by main"),
+            Command::Migrate { action } => migrate(conf, action).await,
+            Command::Seed { tenant } => seed(conf, tenant).await,
+            Command::User {
+                action: UserAction::Create { tenant },
+            } => create_user(conf, &tenant),
+            #[cfg(feature = "consumer")]
+            Command::Task {
+                action: TaskAction::Enqueue { stream, json },
+            } => enqueue(conf, &stream, &json).await,
+            #[cfg(feature = "consumer")]
+            Command::Streams {
+                action: StreamsAction::Inspect { streams },
+            } => inspect_streams(conf, streams).await,
+            #[cfg(feature = "cron")]
+            Command::Rebalance {
+                action: RebalanceAction::Now,
+            } => {
+                cronjob_service::rebalance_now(conf).await?;
+                println!("rebalance finished");
+                Ok(())
+            }
+            Command::Config {
+                action: ConfigAction::Print,
+            } => {
+                println!("{conf:#?}");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 管理数据库迁移
+async fn migrate(conf: &AppConfig, action: MigrateAction) -> Result<()> {
+    let pool = connect_database(conf).await?;
+
+    match action {
+        MigrateAction::Up => run_migrations(&pool).await?,
+        MigrateAction::Down => match revert_last_migration(&pool).await? {
+            Some(migration) => println!("reverted {} {}", migration.version, migration.description),
+            None => println!("no applied migration"),
+        },
+        MigrateAction::Status => {
+            for migration in migration_status(&pool).await? {
+                let state = if migration.applied { "applied" } else { "pending" };
+                println!("{:<16}{:<10}{}", migration.version, state, migration.description);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 写入演示数据：两个标签、一个顶层项目和两个子项目
+async fn seed(conf: &AppConfig, tenant: Option<TenantId>) -> Result<()> {
+    let tenant = tenant
+        .or_else(|| conf.auth.default_tenant.clone())
+        .ok_or_else(|| eyre!("Missing tenant, use --tenant or set AUTH_DEFAULT_TENANT"))?;
+    let repository = ProjectRepository::new(connect_database(conf).await?).with_tenant(&tenant);
+
+    if repository.find_projects(ProjectFilter::default(), 1, 0).await?.total > 0 {
+        println!("tenant {tenant} already has projects, skip seeding");
+        return Ok(());
+    }
+
+    let backend = repository
+        .create_tag(TagCreate {
+            name: "backend".to_string(),
+            comment: "后端服务".to_string(),
+        })
+        .await?;
+    let frontend = repository
+        .create_tag(TagCreate {
+            name: "frontend".to_string(),
+            comment: "前端应用".to_string(),
+        })
+        .await?;
+
+    let platform = repository
+        .create_project(ProjectCreate {
+            project_name: "platform".to_string(),
+            comment: "演示项目".to_string(),
+            parent_id: None,
+        })
+        .await?;
+    for (name, comment, tag) in [("platform-api", "接口服务", &backend), ("platform-web", "管理后台", &frontend)] {
+        let project = repository
+            .create_project(ProjectCreate {
+                project_name: name.to_string(),
+                comment: comment.to_string(),
+                parent_id: Some(platform.id),
+            })
+            .await?;
+        repository.attach_tag(project.id, tag.id).await?;
+    }
+
+    println!("seeded tenant {tenant}: 3 projects, 2 tags");
+    Ok(())
+}
+
+/// 生成访问令牌
+///
+/// 令牌只保存在环境变量中，这里只负责生成，不会修改配置
+fn create_user(conf: &AppConfig, tenant: &TenantId) -> Result<()> {
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect();
+
+    let existing = conf.auth.tokens.values().filter(|t| *t == tenant).count();
+    if existing > 0 {
+        eprintln!("tenant {tenant} already has {existing} token(s), the new token will be added");
+    }
+
+    println!("{tenant}:{token}");
+    eprintln!("append the line above to AUTH_TOKENS (comma separated) and restart the service");
+    Ok(())
+}
+
+/// 投递任务
+#[cfg(feature = "consumer")]
+async fn enqueue(conf: &AppConfig, stream: &str, json: &str) -> Result<()> {
+    let streams = task_stream_names();
+    if !streams.contains(&stream) {
+        bail!("Unknown stream `{stream}`, expected one of {streams:?}");
+    }
+    serde_json::from_str::<serde_json::Value>(json).context("Task payload is not valid JSON")?;
+
+    let mut conn = connect_redis(conf).await?;
+    let id = enqueue_task(&mut conn, stream, json).await?;
+
+    println!("enqueued {stream} {id}");
+    Ok(())
+}
+
+/// 查看流状态
+#[cfg(feature = "consumer")]
+async fn inspect_streams(conf: &AppConfig, mut streams: Vec<String>) -> Result<()> {
+    if streams.is_empty() {
+        streams = task_stream_names().map(str::to_string).to_vec();
+    }
+
+    let mut conn = connect_redis(conf).await?;
+    for stream in &streams {
+        let stats = inspect_stream(&mut conn, stream).await?;
+        println!("stream {}: {} messages", stats.stream_name, stats.length);
+        for group in stats.groups {
+            println!(
+                "  group {}: {} consumers, {} pending, last delivered {}, lag {}",
+                group.name,
+                group.consumers,
+                group.pending,
+                group.last_delivered_id,
+                group.lag.map_or("unknown".to_string(), |lag| lag.to_string()),
+            );
+        }
+    }
+
+    let now = Utc::now().timestamp();
+    println!("consumers:");
+    for heartbeat in consumer_heartbeats(&mut conn, &conf.consumer.heartbeat_key).await? {
+        if streams.contains(&heartbeat.stream_name) {
+            println!(
+                "  {} ({}): last heartbeat {}s ago",
+                heartbeat.consumer_name,
+                heartbeat.stream_name,
+                now - heartbeat.last_heartbeat
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_parse_commands() {
+        Cli::command().debug_assert();
+
+        assert!(Cli::parse_from(["rust-backend"]).command.is_none());
+        assert!(matches!(
+            Cli::parse_from(["rust-backend", "serve", "--roles", "consumer,cron"]).command,
+            Some(Command::Serve(ServeArgs { roles: Some(roles) })) if !roles.needs_database()
+        ));
+        assert!(Cli::try_parse_from(["rust-backend", "serve", "--roles", "worker"]).is_err());
+        assert!(matches!(
+            Cli::parse_from(["rust-backend", "migrate", "down"]).command,
+            Some(Command::Migrate {
+                action: MigrateAction::Down
+            })
+        ));
+        #[cfg(feature = "consumer")]
+        assert!(matches!(
+            Cli::parse_from(["rust-backend", "task", "enqueue", "task_type_a", "{}"]).command,
+            Some(Command::Task {
+                action: TaskAction::Enqueue { .. }
+            })
+        ));
+        assert!(Cli::try_parse_from(["rust-backend", "user", "create", "bad tenant"]).is_err());
+
+        let cli = Cli::parse_from([
+            "rust-backend",
+            "config",
+            "print",
+            "--config",
+            "prod.toml",
+            "--set",
+            "redis.max_pool_size=20",
+            "--set",
+            "web.listen_addr=0.0.0.0:80",
+        ]);
+        let overrides = cli.config_overrides();
+        assert_eq!(overrides.file, Some(PathBuf::from("prod.toml")));
+        assert_eq!(overrides.values[1], ("web.listen_addr".to_string(), "0.0.0.0:80".to_string()));
+        assert!(Cli::try_parse_from(["rust-backend", "--set", "redis.max_pool_size"]).is_err());
+    }
+}
@@ -4,76 +4,292 @@
 //!
 //! - 对外提供的`json-api`
 //! - 可视化的文档
+//! - 供内部服务调用的`gRPC`接口
 //! - 异步消息处理器(`redis`)
 //! - `cron`任务处理器(定时任务)
 //!
 //! 所有代码都放在一个程序中，方便部署和维护(适用于小型系统)
+//!
+//! 同一个程序还提供了运维用的子命令（数据库迁移、投递任务等），参考 [`cli`]
+//!
+//! 各个服务异常退出时按重启策略处理，参考 [`supervisor`]；收到退出信号后分阶段关闭各个服务，参考 [`shutdown`]
 
 // 🚀 使用mimalloc作为全局内存分配器，提升内存分配性能
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
+mod cli;
+mod shutdown;
+mod supervisor;
+
+use clap::Parser;
+use cli::{Cli, Command};
 use color_eyre::Result;
-use color_eyre::eyre::Context;
-use consumer_service::start_job_consumers;
+use color_eyre::eyre::{Context, bail};
+#[cfg(feature = "consumer")]
+use consumer_service::{in_flight_messages, start_job_consumers};
+#[cfg(feature = "cron")]
 use cronjob_service::start_cron_tasks;
-use database::initialize_database;
-use shared_lib::models::config::AppConfig;
+#[cfg(feature = "web")]
+use database::{DatabasePool, connect_database_lazy, initialize_database, prepare_database};
+#[cfg(feature = "web")]
+use grpc_service::start_grpc_service;
+use shared_lib::logging::{self, LogFilter};
+use shared_lib::models::config::{AppConfig, RestartPolicy, Role, Roles};
+use shared_lib::models::config_reload::{ConfigReceiver, ConfigReloader};
+#[cfg(feature = "web")]
+use shared_lib::subsystems::Subsystems;
+use shutdown::{Phase, ShutdownTimedOut};
 use std::sync::Arc;
-use tokio::sync::watch::Sender;
-use tokio::{signal, try_join};
-use tracing::info;
+use supervisor::Supervisor;
+use tokio::signal;
+use tokio::sync::watch::Receiver;
+use tracing::{Level, error, info};
+use tracing_subscriber::fmt;
+#[cfg(feature = "web")]
+use web_service::server::open_connections;
+#[cfg(feature = "web")]
 use web_service::start_web_service;
 
+/// 编译到程序中的角色，由cargo feature决定
+///
+/// - `web`: Web服务和gRPC服务（gRPC与Web服务共用服务层）
+/// - `consumer`: Redis任务消费者
+/// - `cron`: 定时任务
+const COMPILED_ROLES: &[Role] = &[
+    #[cfg(feature = "web")]
+    Role::Web,
+    #[cfg(feature = "web")]
+    Role::Grpc,
+    #[cfg(feature = "consumer")]
+    Role::Consumer,
+    #[cfg(feature = "cron")]
+    Role::Cron,
+];
+
 /// 入口函数
 ///
+/// - 解析命令行参数，不带子命令时启动所有服务
 /// - 使用tokio作为异步运行时，因此需要增加 `#[tokio::main]`
 #[tokio::main]
 async fn main() -> Result<()> {
     // 安装错误提示器
     color_eyre::install()?;
 
-    // 使用tracing作为日志记录器
-    tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).init();
+    let cli = Cli::parse();
+    let overrides = cli.config_overrides();
+    let command = cli.command.unwrap_or_default();
+
+    match command {
+        Command::Serve(args) => {
+            // 加载配置数据（配置文件、环境变量或者本地的.env文件、命令行参数）
+            // 日志需要根据配置初始化，加载配置期间的日志临时输出到stderr
+            let bootstrap = fmt().with_max_level(Level::INFO).with_writer(std::io::stderr).finish();
+            let reloader = tracing::subscriber::with_default(bootstrap, || ConfigReloader::load(overrides))?;
+            let conf = reloader.current();
+
+            // 使用tracing作为日志记录器，参考 [`shared_lib::logging`]，log_guard需要持有到服务退出
+            let (log_filter, log_guard) = logging::init(&conf.log)?;
+
+            // 命令行参数优先于环境变量
+            let roles = args.roles.unwrap_or_else(|| conf.roles.clone());
+            let result = serve(Arc::new(reloader), roles, log_filter).await;
+
+            // 退出超时时还有任务没有停止，关闭tokio运行时会一直等待，直接结束进程，结束前写完缓冲区中的日志
+            if let Err(err) = &result
+                && err.is::<ShutdownTimedOut>()
+            {
+                drop(log_guard);
+                eprintln!("Error: {err}");
+                std::process::exit(1);
+            }
+            result
+        }
+        command => {
+            // 运维子命令的日志输出到stderr，避免和命令的输出混在一起
+            fmt().with_max_level(Level::INFO).with_writer(std::io::stderr).init();
+
+            let conf = AppConfig::load_with(&overrides)?;
+            command.run(&conf).await
+        }
+    }
+}
 
-    // 加载配置数据（从环境变量或者本地的.env文件）
-    let conf = AppConfig::load()?;
+/// 启动选中角色的服务，收到退出信号后分阶段关闭所有服务
+///
+/// 没有选中的服务不会启动，也不会创建它需要的数据库或者Redis连接。
+/// 没有编译到程序中的角色会被跳过，例如只启用 `consumer` feature 编译的程序在 `ROLES=all` 时只启动消费者。
+///
+/// 服务异常退出时按照 `[supervisor]` 配置的重启策略处理，策略为 `escalate` 的服务异常退出时分阶段关闭其他服务，然后返回这个服务的错误。
+///
+/// 运行期间收到 `SIGHUP` 信号时重新加载配置，参考 [`shared_lib::models::config_reload`]
+async fn serve(reloader: Arc<ConfigReloader>, mut roles: Roles, log_filter: Arc<LogFilter>) -> Result<()> {
+    for role in roles.iter().filter(|role| !COMPILED_ROLES.contains(role)) {
+        info!("🧩 角色 {role} 没有编译到程序中，跳过");
+    }
+    roles.retain(|role| COMPILED_ROLES.contains(role));
+    if roles.is_empty() {
+        bail!("No role to start, roles compiled into this binary: {COMPILED_ROLES:?}");
+    }
+    info!("🧩 启用的角色: {roles}");
 
-    let pool = initialize_database(Arc::clone(&conf))
-        .await
-        .context("Failed to initialize database")?;
+    // 只有Web和gRPC服务需要数据库，数据库、gRPC服务和重启策略的配置不支持热加载
+    let conf = reloader.current();
+    // 数据库暂时不可用时按 `startup` 配置重试；降级启动时不等待数据库，由连接池子系统在后台等待数据库可用并执行迁移
+    #[cfg(feature = "web")]
+    let pool = if !roles.needs_database() {
+        None
+    } else if conf.startup.degraded {
+        info!("🚧 降级启动，数据库可用并完成迁移前就绪探针返回503");
+        Some(connect_database_lazy(&conf)?)
+    } else {
+        let pool = initialize_database(Arc::clone(&conf))
+            .await
+            .context("Failed to initialize database")?;
+        Some(pool)
+    };
 
-    // 优雅退出通知机制，通过watch来通知需要感知的协程优雅退出
-    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    // 每个服务在所属阶段的退出信号到来后停止，参考 [`shutdown`]；异常退出时按重启策略处理，参考 [`supervisor`]
+    let mut supervisor = Supervisor::new(&conf.supervisor);
 
-    // 如果有任何一个服务启动失败，那么应该会退出并打印错误信息
-    _ = try_join!(
-        start_shutdown_signal(shutdown_tx),
-        // 启动web-api服务
-        start_web_service(pool, shutdown_rx.clone()),
-        // 启动redis-consumer服务
-        start_job_consumers(Arc::clone(&conf), shutdown_rx.clone()),
-        // 启动cron-jobs服务
-        start_cron_tasks(Arc::clone(&conf), shutdown_rx.clone()),
-    )?;
+    #[cfg(feature = "web")]
+    if let Some(pool) = &pool {
+        // 启动web-api服务，开始退出时就绪探针先返回503
+        if roles.contains(Role::Web) {
+            let (reloader, log_filter, pool, statuses) =
+                (Arc::clone(&reloader), Arc::clone(&log_filter), pool.clone(), supervisor.statuses());
+            let (draining_rx, shutdown_rx) = (supervisor.receiver(Phase::Readiness), supervisor.receiver(Phase::Web));
+            let web = move || {
+                start_web_service(
+                    Arc::clone(&reloader),
+                    Arc::clone(&log_filter),
+                    pool.clone(),
+                    statuses.clone(),
+                    draining_rx.clone(),
+                    shutdown_rx.clone(),
+                )
+            };
+            supervisor
+                .spawn(Phase::Web, "web", conf.supervisor.restart_policy(Role::Web), web)
+                .pending_work(|| format!("{} 个连接没有关闭", open_connections()));
+        }
+        // 启动gRPC服务
+        if roles.contains(Role::Grpc) {
+            let restart_policy = conf.supervisor.restart_policy(Role::Grpc);
+            let (conf, pool, shutdown_rx) = (Arc::clone(&conf), pool.clone(), supervisor.receiver(Phase::Web));
+            let grpc = move || start_grpc_service(Arc::clone(&conf), pool.clone(), shutdown_rx.clone());
+            supervisor.spawn(Phase::Web, "grpc", restart_policy, grpc);
+        }
+        // 所有使用数据库的服务停止后关闭连接池
+        let (in_use, pool, shutdown_rx) = (pool.clone(), pool.clone(), supervisor.receiver(Phase::Pools));
+        let prepare = conf.startup.degraded.then(|| (Arc::clone(&conf), supervisor.statuses()));
+        supervisor
+            .spawn(Phase::Pools, DATABASE_POOL, RestartPolicy::Escalate, move || {
+                manage_pool(pool.clone(), prepare.clone(), shutdown_rx.clone())
+            })
+            .pending_work(move || format!("{} 个连接没有归还", in_use.connections_in_use()));
+    }
+    // 启动redis-consumer服务
+    #[cfg(feature = "consumer")]
+    if roles.contains(Role::Consumer) {
+        let (reloader, shutdown_rx) = (Arc::clone(&reloader), supervisor.receiver(Phase::Consumer));
+        let consumer = move || start_job_consumers(reloader.subscribe(), shutdown_rx.clone());
+        supervisor
+            .spawn(
+                Phase::Consumer,
+                "consumer",
+                conf.supervisor.restart_policy(Role::Consumer),
+                consumer,
+            )
+            .pending_work(|| format!("{} 条消息没有处理完", in_flight_messages()));
+    }
+    // 启动cron-jobs服务
+    #[cfg(feature = "cron")]
+    if roles.contains(Role::Cron) {
+        let (reloader, shutdown_rx) = (Arc::clone(&reloader), supervisor.receiver(Phase::Cron));
+        let cron = move || start_cron_tasks(reloader.subscribe(), shutdown_rx.clone());
+        supervisor.spawn(Phase::Cron, "cron", conf.supervisor.restart_policy(Role::Cron), cron);
+    }
+    // 退出过程中日志过滤规则仍然跟随配置变化
+    let (config_reloader, shutdown_rx) = (Arc::clone(&reloader), supervisor.receiver(Phase::Pools));
+    let follow = move || follow_log_filter(config_reloader.subscribe(), Arc::clone(&log_filter), shutdown_rx.clone());
+    supervisor.spawn(Phase::Pools, "log-filter", RestartPolicy::Escalate, follow);
+
+    // 收到退出信号或者重启策略为escalate的服务异常退出后，分阶段关闭所有服务
+    let result = supervisor.run_until(wait_for_signal(Arc::clone(&reloader))).await;
+    if result.is_err() {
+        error!("❌ 运行出错，开始关闭所有服务");
+    }
+    supervisor.stop(&reloader.current().shutdown).await?;
+    result?;
 
     info!("rust backend exit successfully");
 
     Ok(())
 }
 
-/// 发送退出信号
+/// 数据库连接池子系统的名称
+#[cfg(feature = "web")]
+const DATABASE_POOL: &str = "database-pool";
+
+/// 管理数据库连接池，收到退出信号后关闭连接池，等待所有连接归还
+///
+/// 降级启动时（传入 `prepare`）先等待数据库可用并执行迁移，期间子系统状态为 `starting`，就绪探针返回503
+#[cfg(feature = "web")]
+async fn manage_pool(pool: DatabasePool, prepare: Option<(Arc<AppConfig>, Subsystems)>, mut shutdown_rx: Receiver<bool>) -> Result<()> {
+    if let Some((conf, statuses)) = prepare {
+        statuses.starting(DATABASE_POOL);
+        tokio::select! {
+            result = prepare_database(&pool, &conf) => {
+                result.context("Failed to initialize database")?;
+                statuses.running(DATABASE_POOL);
+                info!("✅ 数据库已就绪，开始接收流量");
+            }
+            _ = shutdown_rx.wait_for(|shutdown| *shutdown) => {}
+        }
+    }
+
+    _ = shutdown_rx.wait_for(|shutdown| *shutdown).await;
+    pool.close().await;
+    Ok(())
+}
+
+/// 配置中的日志过滤规则变化时调整过滤规则，收到退出信号后结束
+///
+/// 通过运维接口临时设置了过滤规则时，新的规则在临时规则被清除后才生效
+async fn follow_log_filter(mut config: ConfigReceiver, log_filter: Arc<LogFilter>, mut shutdown_rx: Receiver<bool>) -> Result<()> {
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.changed() => return Ok(()),
+            Ok(()) = config.changed() => {
+                let directives = config.borrow_and_update().log.filter.clone();
+                if log_filter.directives().configured == directives {
+                    continue;
+                }
+                // 先记录日志，新的规则可能会过滤掉这条日志
+                info!("📝 日志过滤规则修改为 {directives}");
+                if let Err(err) = log_filter.set_configured(&directives) {
+                    error!("❌ 修改日志过滤规则失败: {:?}", err);
+                }
+            }
+        }
+    }
+}
+
+/// 等待退出信号，同时处理重新加载配置的信号
 ///
 /// 退出场景包括：
 /// - 用户在控制台发送ctrl+c信号（全平台支持）
 /// - SIGTerm: Unix系统下接受到的结束信号
 ///
-/// 发送退出信号，让整个系统处理完当前业务，尽快退出。避免直接结束进程可能导致的：
+/// 收到退出信号后让整个系统处理完当前业务，尽快退出。避免直接结束进程可能导致的：
 /// - 脏数据
 /// - 系统资源长时间占用
 /// - 跟其他系统对接导致其他系统处理异常
 /// - 多余的错误日志（计划内重启/停机）
-async fn start_shutdown_signal(shutdown_tx: Sender<bool>) -> Result<()> {
+///
+/// Unix系统下收到SIGHUP信号时重新加载配置，不会退出
+async fn wait_for_signal(reloader: Arc<ConfigReloader>) -> Result<()> {
     // 监听ctrl+c信号
     let ctrl_c = async { signal::ctrl_c().await.context("failed to install Ctrl+C handler") };
 
@@ -88,14 +304,35 @@ async fn start_shutdown_signal(shutdown_tx: Sender<bool>) -> Result<()> {
     #[cfg(not(unix))]
     let terminate = std::future::pending::<Result<Option<()>>>();
 
-    // 只要有监听到任何退出信号，就结束select!监听
-    tokio::select! {
-        result = ctrl_c => {result?; info!("Received Ctrl+C, initiating shutdown...");},
-        result = terminate => {result?; info!("Received SIGTERM, initiating shutdown...");},
-    }
+    #[cfg(unix)]
+    let mut hangup = signal::unix::signal(signal::unix::SignalKind::hangup()).context("Failed to install SIGHUP handler")?;
+
+    tokio::pin!(ctrl_c, terminate);
 
-    // 发送关闭信号，通知其他模块退出
-    shutdown_tx.send(true).context("Failed to send shutdown signal")?;
+    // 只要有监听到任何退出信号，就结束监听，由调用方开始退出
+    loop {
+        // windows下没有SIGHUP，只能通过运维接口重新加载配置
+        #[cfg(unix)]
+        let reload = hangup.recv();
+        #[cfg(not(unix))]
+        let reload = std::future::pending::<Option<()>>();
+
+        tokio::select! {
+            result = &mut ctrl_c => {result?; info!("Received Ctrl+C, initiating shutdown..."); break;},
+            result = &mut terminate => {result?; info!("Received SIGTERM, initiating shutdown..."); break;},
+            Some(()) = reload => reload_config(&reloader),
+        }
+    }
 
     Ok(())
 }
+
+/// 重新加载配置，失败时继续使用原有配置
+fn reload_config(reloader: &ConfigReloader) {
+    info!("Received SIGHUP, reloading config...");
+    match reloader.reload() {
+        Ok(changed) if changed.is_empty() => info!("🔄 配置没有变化"),
+        Ok(changed) => info!("🔄 配置已重新加载，变化的配置项: {}", changed.join(", ")),
+        Err(err) => error!("❌ 重新加载配置失败，继续使用原有配置: {:?}", err),
+    }
+}
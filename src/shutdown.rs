@@ -0,0 +1,82 @@
+//! 分阶段优雅退出
+//!
+//! 收到退出信号后按 [`Phase`] 的顺序依次关闭各个服务，前一个阶段的服务全部停止后才进入下一个阶段：
+//!
+//! 1. `readiness`: 就绪探针返回503，等待 `shutdown.readiness_delay_secs`，让负载均衡摘除流量，期间仍然正常处理请求；
+//!    没有运行Web和gRPC服务时跳过等待
+//! 2. `web`: Web和gRPC服务停止接收新连接，等待处理中的请求完成
+//! 3. `consumer`: Redis消费者停止读取新消息，等待已经读取的消息处理完并确认（`xack`）
+//! 4. `cron`: 停止定时任务调度器
+//! 5. `pools`: 关闭数据库连接池，同时停止其他后台任务
+//!
+//! 每个阶段的超时时间参考 [`ShutdownConfig`]。某个阶段超时后记录所有没有停止的服务和放弃的工作，
+//! 然后返回 [`ShutdownTimedOut`]，由 `main` 强制退出进程，不再等待后续阶段。
+//!
+//! 退出过程由 [`crate::supervisor::Supervisor::stop`] 执行，重启策略为 `escalate` 的服务异常退出时同样按阶段关闭其他服务。
+
+use shared_lib::models::config::ShutdownConfig;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// 退出阶段，按声明的顺序执行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// 就绪探针返回503，有Web或gRPC服务时固定等待 `readiness_delay`，不运行任务
+    Readiness,
+    /// Web和gRPC服务
+    Web,
+    /// Redis消费者
+    Consumer,
+    /// 定时任务
+    Cron,
+    /// 数据库连接池和其他后台任务
+    Pools,
+}
+
+impl Phase {
+    /// 所有阶段，按执行顺序排列
+    pub const ALL: [Phase; 5] = [Phase::Readiness, Phase::Web, Phase::Consumer, Phase::Cron, Phase::Pools];
+
+    /// 阶段名称，与配置项中的名称一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Readiness => "readiness",
+            Phase::Web => "web",
+            Phase::Consumer => "consumer",
+            Phase::Cron => "cron",
+            Phase::Pools => "pools",
+        }
+    }
+
+    /// 阶段的超时时间，就绪阶段固定等待这么长时间
+    pub fn timeout(&self, config: &ShutdownConfig) -> Duration {
+        match self {
+            Phase::Readiness => config.readiness_delay,
+            Phase::Web => config.web_timeout,
+            Phase::Consumer => config.consumer_timeout,
+            Phase::Cron => config.cron_timeout,
+            Phase::Pools => config.pool_timeout,
+        }
+    }
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 某个退出阶段超时，还有没有停止的任务，需要强制退出进程
+#[derive(Debug)]
+pub struct ShutdownTimedOut {
+    pub phase: Phase,
+    pub timeout: Duration,
+}
+
+impl Display for ShutdownTimedOut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Shutdown phase `{}` timed out after {:?}", self.phase, self.timeout)
+    }
+}
+
+impl std::error::Error for ShutdownTimedOut {}
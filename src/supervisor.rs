@@ -0,0 +1,397 @@
+//! 子系统监督器
+//!
+//! 每个子系统（Web、gRPC、消费者、定时任务等）作为独立的tokio任务运行，异常退出（返回错误或者panic）时按照
+//! [`RestartPolicy`] 处理，一个子系统失败不会影响其他正常运行的子系统：
+//!
+//! - `never`: 不重启，记录错误，其他子系统继续运行
+//! - `on-failure`: 等待一段时间后重启，连续失败时等待时间翻倍，从 `supervisor.initial_backoff_secs` 直到 `supervisor.max_backoff_secs`；
+//!   正常运行超过最大等待时间后再失败时重新开始计算
+//! - `escalate`: 分阶段关闭所有子系统，然后返回这个子系统的错误，程序退出
+//!
+//! 子系统的状态和重启次数记录在 [`Subsystems`] 中，通过运维接口 `/health` 查看。
+//! 收到退出信号后按阶段关闭所有子系统（参考 [`crate::shutdown`]），正在等待重启的子系统不会再重启。
+
+use crate::shutdown::{Phase, ShutdownTimedOut};
+use color_eyre::{Report, Result};
+use shared_lib::models::config::{RestartPolicy, ShutdownConfig, SupervisorConfig};
+use shared_lib::subsystems::Subsystems;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch::{self, Receiver, Sender};
+use tokio::task::{Id, JoinError, JoinSet};
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+/// 启动子系统的函数，每次重启都会重新调用
+type StartSubsystem = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync>;
+
+/// 受监督的子系统
+pub struct Subsystem {
+    phase: Phase,
+    name: &'static str,
+    restart_policy: RestartPolicy,
+    start: StartSubsystem,
+
+    /// 下次重启前等待的时间
+    backoff: Duration,
+
+    /// 本次开始运行的时间
+    started_at: Instant,
+
+    /// 描述还没有完成的工作，超时放弃时记录到日志中
+    pending_work: Option<Box<dyn Fn() -> String + Send + Sync>>,
+}
+
+impl Subsystem {
+    /// 设置描述未完成工作的函数，例如还有多少条消息在处理中
+    #[cfg_attr(not(any(feature = "web", feature = "consumer")), allow(dead_code))]
+    pub fn pending_work(&mut self, describe: impl Fn() -> String + Send + Sync + 'static) {
+        self.pending_work = Some(Box::new(describe));
+    }
+}
+
+impl Display for Subsystem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.pending_work {
+            Some(describe) => write!(f, "{}（{}）", self.name, describe()),
+            None => f.write_str(self.name),
+        }
+    }
+}
+
+/// 子系统监督器
+///
+/// 每个退出阶段对应一个退出信号，子系统通过 [`Supervisor::receiver`] 获取所属阶段的信号，
+/// 然后通过 [`Supervisor::spawn`] 在后台运行
+pub struct Supervisor {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    senders: [Sender<bool>; Phase::ALL.len()],
+    tasks: JoinSet<Result<()>>,
+    subsystems: HashMap<Id, Subsystem>,
+    statuses: Subsystems,
+}
+
+impl Supervisor {
+    pub fn new(config: &SupervisorConfig) -> Self {
+        Supervisor {
+            initial_backoff: config.initial_backoff,
+            max_backoff: config.max_backoff,
+            senders: std::array::from_fn(|_| watch::Sender::new(false)),
+            tasks: JoinSet::new(),
+            subsystems: HashMap::new(),
+            statuses: Subsystems::default(),
+        }
+    }
+
+    /// 所有子系统的运行状态
+    #[cfg_attr(not(feature = "web"), allow(dead_code))]
+    pub fn statuses(&self) -> Subsystems {
+        self.statuses.clone()
+    }
+
+    /// 指定阶段的退出信号，进入该阶段时变为 `true`
+    pub fn receiver(&self, phase: Phase) -> Receiver<bool> {
+        self.senders[phase as usize].subscribe()
+    }
+
+    /// 在后台运行子系统，子系统需要在收到所属阶段的退出信号后尽快结束
+    ///
+    /// 每次重启都会重新调用 `start`
+    pub fn spawn<S, F>(&mut self, phase: Phase, name: &'static str, restart_policy: RestartPolicy, start: S) -> &mut Subsystem
+    where
+        S: Fn() -> F + Send + Sync + 'static,
+        F: Future<Output = Result<()>> + Send + 'static,
+    {
+        // 先记录状态，子系统启动后可能立即修改自己的状态
+        self.statuses.register(name, restart_policy);
+        let start: StartSubsystem = Arc::new(move || Box::pin(start()));
+        let id = self.tasks.spawn(start()).id();
+
+        self.subsystems.entry(id).or_insert(Subsystem {
+            phase,
+            name,
+            restart_policy,
+            start,
+            backoff: self.initial_backoff,
+            started_at: Instant::now(),
+            pending_work: None,
+        })
+    }
+
+    /// 等待退出信号，期间按重启策略处理异常退出的子系统，策略为 `escalate` 时立即返回它的错误
+    pub async fn run_until(&mut self, signal: impl Future<Output = Result<()>>) -> Result<()> {
+        tokio::pin!(signal);
+
+        loop {
+            tokio::select! {
+                result = &mut signal => return result,
+                Some(joined) = self.tasks.join_next_with_id() => self.supervise(joined)?,
+            }
+        }
+    }
+
+    /// 按阶段依次关闭所有子系统
+    ///
+    /// 某个阶段超时后返回 [`ShutdownTimedOut`]，此时还有任务没有停止，调用方需要强制退出进程
+    pub async fn stop(mut self, config: &ShutdownConfig) -> Result<()> {
+        for phase in Phase::ALL {
+            let timeout = phase.timeout(config);
+            let deadline = Instant::now() + timeout;
+            info!("🛑 退出阶段 {phase}，超时时间 {timeout:?}");
+            self.senders[phase as usize].send_replace(true);
+
+            // 没有Web和gRPC服务时没有需要摘除的流量，不用等待
+            if phase == Phase::Readiness && self.subsystems.values().any(|subsystem| subsystem.phase == Phase::Web) {
+                tokio::time::sleep_until(deadline).await;
+            }
+
+            while self.subsystems.values().any(|subsystem| subsystem.phase == phase) {
+                match tokio::time::timeout_at(deadline, self.tasks.join_next_with_id()).await {
+                    // 退出期间异常退出的子系统不再重启，只记录错误，继续关闭其他子系统
+                    Ok(Some(joined)) => {
+                        if let Some((subsystem, err)) = self.finished(joined) {
+                            self.statuses.failed(subsystem.name, &err);
+                            error!("❌ {:?}", err);
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(_) => {
+                        self.abandon(phase, timeout);
+                        return Err(ShutdownTimedOut { phase, timeout }.into());
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 按重启策略处理已经结束的子系统，策略为 `escalate` 时返回错误
+    fn supervise(&mut self, joined: Result<(Id, Result<()>), JoinError>) -> Result<()> {
+        let Some((subsystem, err)) = self.finished(joined) else {
+            return Ok(());
+        };
+
+        match subsystem.restart_policy {
+            RestartPolicy::Escalate => {
+                self.statuses.failed(subsystem.name, &err);
+                Err(err)
+            }
+            RestartPolicy::Never => {
+                self.statuses.failed(subsystem.name, &err);
+                error!("❌ {} 异常退出，不再重启: {:?}", subsystem.name, err);
+                Ok(())
+            }
+            RestartPolicy::OnFailure => {
+                self.restart(subsystem, &err);
+                Ok(())
+            }
+        }
+    }
+
+    /// 移除已经结束的子系统，返回异常退出（返回错误或者panic）的子系统和错误
+    fn finished(&mut self, joined: Result<(Id, Result<()>), JoinError>) -> Option<(Subsystem, Report)> {
+        let (id, result) = match joined {
+            Ok((id, result)) => (id, result),
+            Err(err) => (err.id(), Err(Report::new(err))),
+        };
+        let subsystem = self.subsystems.remove(&id)?;
+
+        match result {
+            Ok(()) => {
+                info!("✅ {} 已停止", subsystem.name);
+                self.statuses.stopped(subsystem.name);
+                None
+            }
+            Err(err) => {
+                let err = err.wrap_err(format!("{} failed", subsystem.name));
+                Some((subsystem, err))
+            }
+        }
+    }
+
+    /// 等待退避时间后重新启动子系统
+    fn restart(&mut self, mut subsystem: Subsystem, err: &Report) {
+        // 正常运行了足够长的时间，不是连续失败，重新计算等待时间
+        if subsystem.started_at.elapsed() >= self.max_backoff {
+            subsystem.backoff = self.initial_backoff;
+        }
+        let delay = subsystem.backoff;
+        subsystem.backoff = (delay * 2).min(self.max_backoff);
+        subsystem.started_at = Instant::now() + delay;
+
+        let name = subsystem.name;
+        let restarts = self.statuses.restarting(name, err);
+        warn!("🔁 {} 异常退出，{:?} 后第 {} 次重启: {:?}", name, delay, restarts, err);
+
+        let start = Arc::clone(&subsystem.start);
+        let statuses = self.statuses.clone();
+        let mut shutdown_rx = self.receiver(subsystem.phase);
+        let id = self
+            .tasks
+            .spawn(async move {
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    // 等待期间进入了所属的退出阶段，不再重启
+                    _ = shutdown_rx.wait_for(|shutdown| *shutdown) => return Ok(()),
+                }
+                statuses.running(name);
+                start().await
+            })
+            .id();
+        self.subsystems.insert(id, subsystem);
+    }
+
+    /// 记录超时放弃的子系统和未完成的工作
+    fn abandon(&self, phase: Phase, timeout: Duration) {
+        let mut abandoned: Vec<_> = self.subsystems.values().collect();
+        abandoned.sort_by_key(|subsystem| (subsystem.phase, subsystem.name));
+        let abandoned: Vec<_> = abandoned
+            .iter()
+            .map(|subsystem| format!("[{}] {}", subsystem.phase, subsystem))
+            .collect();
+
+        error!(
+            "⏰ 退出阶段 {phase} 超过 {timeout:?} 没有完成，强制退出，放弃: {}",
+            abandoned.join("，")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use color_eyre::eyre::bail;
+    use shared_lib::subsystems::SubsystemState;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn test_supervisor() -> Supervisor {
+        Supervisor::new(&SupervisorConfig {
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(40),
+            ..SupervisorConfig::default()
+        })
+    }
+
+    fn shutdown_config(timeout: Duration) -> ShutdownConfig {
+        ShutdownConfig {
+            readiness_delay: Duration::ZERO,
+            web_timeout: timeout,
+            consumer_timeout: timeout,
+            cron_timeout: timeout,
+            pool_timeout: timeout,
+        }
+    }
+
+    /// 等待一段时间后返回的退出信号
+    async fn signal_after(millis: u64) -> Result<()> {
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stop_in_order() {
+        let mut supervisor = test_supervisor();
+        let order = watch::Sender::new(Vec::new());
+        for phase in [Phase::Pools, Phase::Cron, Phase::Consumer, Phase::Web] {
+            let shutdown_rx = supervisor.receiver(phase);
+            let order = order.clone();
+            supervisor.spawn(phase, phase.as_str(), RestartPolicy::Escalate, move || {
+                let mut shutdown_rx = shutdown_rx.clone();
+                let order = order.clone();
+                async move {
+                    _ = shutdown_rx.wait_for(|shutdown| *shutdown).await;
+                    order.send_modify(|order| order.push(phase));
+                    Ok(())
+                }
+            });
+        }
+        let draining_rx = supervisor.receiver(Phase::Readiness);
+
+        supervisor.run_until(signal_after(0)).await.unwrap();
+        assert!(!*draining_rx.borrow());
+
+        supervisor.stop(&shutdown_config(Duration::from_secs(1))).await.unwrap();
+        assert!(*draining_rx.borrow());
+        assert_eq!(*order.borrow(), [Phase::Web, Phase::Consumer, Phase::Cron, Phase::Pools]);
+    }
+
+    #[tokio::test]
+    async fn test_stop_timed_out() {
+        let mut supervisor = test_supervisor();
+        // 忽略退出信号的子系统
+        supervisor
+            .spawn(Phase::Consumer, "consumer", RestartPolicy::OnFailure, std::future::pending)
+            .pending_work(|| "1 message".to_string());
+
+        let err = supervisor.stop(&shutdown_config(Duration::from_millis(50))).await.unwrap_err();
+        let timed_out = err.downcast_ref::<ShutdownTimedOut>().unwrap();
+        assert_eq!(timed_out.phase, Phase::Consumer);
+    }
+
+    #[tokio::test]
+    async fn test_stop_without_web() {
+        let mut supervisor = test_supervisor();
+        let shutdown_rx = supervisor.receiver(Phase::Consumer);
+        supervisor.spawn(Phase::Consumer, "consumer", RestartPolicy::OnFailure, move || {
+            let mut shutdown_rx = shutdown_rx.clone();
+            async move {
+                _ = shutdown_rx.wait_for(|shutdown| *shutdown).await;
+                Ok(())
+            }
+        });
+
+        // 只有消费者时不等待 readiness_delay
+        let config = ShutdownConfig {
+            readiness_delay: Duration::from_secs(10),
+            ..shutdown_config(Duration::from_secs(1))
+        };
+        tokio::time::timeout(Duration::from_secs(1), supervisor.stop(&config))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restart_policies() {
+        let mut supervisor = test_supervisor();
+        let statuses = supervisor.statuses();
+
+        // 前两次启动失败，之后正常运行到退出
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&attempts);
+        let shutdown_rx = supervisor.receiver(Phase::Cron);
+        supervisor.spawn(Phase::Cron, "cron", RestartPolicy::OnFailure, move || {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            let mut shutdown_rx = shutdown_rx.clone();
+            async move {
+                if attempt < 2 {
+                    bail!("redis is down");
+                }
+                _ = shutdown_rx.wait_for(|shutdown| *shutdown).await;
+                Ok(())
+            }
+        });
+        supervisor.spawn(Phase::Web, "grpc", RestartPolicy::Never, || async { bail!("address in use") });
+
+        supervisor.run_until(signal_after(200)).await.unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        let snapshot = statuses.snapshot();
+        assert_eq!((snapshot[0].state, snapshot[0].restarts), (SubsystemState::Running, 2));
+        assert_eq!((snapshot[1].state, snapshot[1].restarts), (SubsystemState::Failed, 0));
+        assert_eq!(snapshot[1].last_error.as_deref(), Some("grpc failed: address in use"));
+
+        supervisor.stop(&shutdown_config(Duration::from_secs(1))).await.unwrap();
+        assert_eq!(statuses.snapshot()[0].state, SubsystemState::Stopped);
+
+        // 策略为escalate时立即返回错误
+        let mut supervisor = test_supervisor();
+        supervisor.spawn(Phase::Consumer, "consumer", RestartPolicy::Escalate, || async { bail!("boom") });
+        let err = supervisor.run_until(std::future::pending()).await.unwrap_err();
+        assert_eq!(err.to_string(), "consumer failed");
+    }
+}